@@ -5,5 +5,6 @@
 extern crate test;
 
 mod hash;
+mod parse;
 mod path;
 mod time;
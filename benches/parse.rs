@@ -0,0 +1,107 @@
+use core::hint::black_box;
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+// About half a megabyte of header, built by repeating a 20 KB header written in the
+// style of str_format's internals. It stands in for a large header, but repeats the
+// same declarations rather than varying them as real headers do.
+fn large_header() -> String {
+    let header = include_str!("data/format_internals.h");
+    header.repeat(500_000 / header.len() + 1)
+}
+
+#[bench]
+#[cfg_attr(miri, ignore)] // Miri isn't fast...
+fn bench_extract_function_details_large_header(b: &mut test::Bencher) {
+    let src = large_header();
+    b.bytes = src.len() as u64;
+    b.iter(|| astd::extract_function_details(black_box(&src)));
+}
+
+#[bench]
+#[cfg_attr(miri, ignore)] // Miri isn't fast...
+fn bench_extract_function_details_checked_large_header(b: &mut test::Bencher) {
+    let src = large_header();
+    b.bytes = src.len() as u64;
+    b.iter(|| astd::extract_function_details_checked(black_box(&src)));
+}
+
+#[bench]
+#[cfg_attr(miri, ignore)] // Miri isn't fast...
+fn bench_tokenize_large_header(b: &mut test::Bencher) {
+    let src = large_header();
+    b.bytes = src.len() as u64;
+    b.iter(|| astd::tokenize(black_box(&src)));
+}
+
+// The declarator patterns of the regex scanner the tokenizer replaced, in the order it
+// tried them: constructors, operators, functions returning function pointers and then
+// plain functions.
+static DECLARATOR_REGEXES: LazyLock<[Regex; 4]> = LazyLock::new(|| {
+    [
+        r"\A\s*((?:\w+\s+)*?)(~?)\s*(\w+)\s*\(",
+        r#"\A\s*([\w:\*&<>\s]*?)\boperator\b\s*(\(\s*\)|\[\s*\]|""\s*\w+|(?:new|delete)(?:\s*\[\s*\])?|co_await|->\*|->|<=>|<<=|>>=|<<|>>|&&|\|\||\+\+|--|[-+*/%^&|~!=<>]=?|,|[\w:][\w:\s<>,\*&]*?)\s*\("#,
+        r"\A\s*([\w:\*&<>\s]+?)\s*\(\s*([*&])\s*((?:\w+::)*\w+)\s*\(",
+        r"\A\s*([\w:\*&<>\s]+)\s+((?:\w+::)*\w+)\s*\(",
+    ]
+    .map(|re| Regex::new(re).expect("Failed to compile regex"))
+});
+
+// A baseline for the benches above, standing in for the regex scanner the tokenizer
+// replaced: blanks comments, splits the source into statements, matches each against
+// DECLARATOR_REGEXES and counts the line and column of each match from the start of the
+// source, as the scanner did. It leaves out the scopes, attributes and parameters the
+// scanner also worked out. Returns the sum of the lines and columns, so the counting is
+// not optimized away.
+fn regex_scan(src: &str) -> usize {
+    let mut code = src.as_bytes().to_vec();
+    let mut i = 0;
+    while i + 1 < code.len() {
+        let end = match &code[i..i + 2] {
+            b"//" => code[i..]
+                .iter()
+                .position(|&b| b == b'\n')
+                .map_or(code.len(), |n| i + n),
+            b"/*" => src[i + 2..].find("*/").map_or(code.len(), |n| i + n + 4),
+            _ => {
+                i += 1;
+                continue;
+            }
+        };
+        code[i..end].fill(b' ');
+        i = end;
+    }
+    let code = String::from_utf8_lossy(&code);
+    let mut lines = 0;
+    let mut start = 0;
+    for statement in code.split([';', '{', '}']) {
+        if DECLARATOR_REGEXES
+            .iter()
+            .any(|re| re.captures(statement).is_some())
+        {
+            let pos = start + statement.len() - statement.trim_start().len();
+            let line = src.as_bytes()[..pos]
+                .iter()
+                .filter(|&&b| b == b'\n')
+                .count()
+                + 1;
+            let column = src[..pos]
+                .rsplit('\n')
+                .next()
+                .map_or(0, |line| line.chars().count())
+                + 1;
+            lines += line + column;
+        }
+        start += statement.len() + 1;
+    }
+    lines
+}
+
+#[bench]
+#[cfg_attr(miri, ignore)] // Miri isn't fast...
+fn bench_regex_scan_large_header(b: &mut test::Bencher) {
+    let src = large_header();
+    b.bytes = src.len() as u64;
+    b.iter(|| regex_scan(black_box(&src)));
+}
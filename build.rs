@@ -2,220 +2,6195 @@
 
 extern crate regex;
 use regex::Regex;
+
+pub mod gather_libs;
 use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    env,
+    fmt::{Debug, Display},
     fs::{self, File},
+    hash::{DefaultHasher, Hash, Hasher},
     io::{BufWriter, Result, Write},
+    iter::FusedIterator,
+    ops::Range,
     path::{Path, PathBuf},
     process::Command,
-    sync::{LazyLock, Mutex},
+    rc::Rc,
+    sync::{
+        LazyLock, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    thread,
+    time::Duration,
 };
 
-static FUNC_REGEX: LazyLock<Regex> = LazyLock::new(|| {
-    Regex::new(r"(?m)^\s*(template\s*<[^;:{]+>\s*)?([\w:\*&<>\s]+)\s+(\w+)\s*\(")
-        .expect("Failed to compile regex")
-});
+static CLASS_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(\benum\s+)?\b(class|struct|union)\s+(?:[\w:]+\s+)*?([\w:]+)\s*(?:final\s*)?(?::[^{]*)?$",
+    )
+    .expect("Failed to compile regex")
+});
+static CLASS_HEAD_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\A\s*(template\s*<[^;{]*>\s*)?\b(class|struct|union)\s+(?:[\w:]+\s+)*?([\w:]+)\s*(final\s*)?(?::([^{]*))?\z")
+        .expect("Failed to compile regex")
+});
+static ENUM_HEAD_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\A\s*enum\s+(?:(class|struct)\s+)?(\w+)?\s*(?::\s*([^{]+?))?\s*\z")
+        .expect("Failed to compile regex")
+});
+static USING_ALIAS_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?s)\A\s*(template\s*<[^;{]*>\s*)?using\s+(\w+)\s*=\s*(.+?)\s*\z")
+        .expect("Failed to compile regex")
+});
+static DECLTYPE_END_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\bdecltype\s*\z").expect("Failed to compile regex"));
+static INCLUDE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?m)^[ \t]*#[ \t]*include[ \t]*(?:<([^>\n]+)>|"([^"\n]+)")"#)
+        .expect("Failed to compile regex")
+});
+static TYPEDEF_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?s)\A\s*typedef\s+(.+?)\s*\z").expect("Failed to compile regex")
+});
+// The name of a pointer or reference declarator, as in `void (*Callback)(int)`.
+static TYPEDEF_DECLARATOR_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\(\s*(?:[*&]|(?:\w+::)+\*)\s*(\w+)\s*\)").expect("Failed to compile regex")
+});
+// The name of a plain declarator, with any array bounds after it.
+static TYPEDEF_NAME_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(\w+)\s*((?:\[[^\]]*\]\s*)*)\z").expect("Failed to compile regex")
+});
+static TYPE_CONST_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\bconst\b").expect("Failed to compile regex"));
+// The `operator` keyword, after which `<` and `>` are operator names, not brackets.
+static OPERATOR_WORD_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\Aoperator\b").expect("Failed to compile regex"));
+static CONVERSION_WORD_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\Aoperator\s+([\w:]+)").expect("Failed to compile regex"));
+static TEMPLATE_KEYWORD_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\A\s*template\s*<").expect("Failed to compile regex"));
+static ACCESS_LABEL_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*(public|protected|private)\s*:").expect("Failed to compile regex")
+});
+static LINKAGE_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\A\s*extern\s*"(C|C\+\+)""#).expect("Failed to compile regex"));
+static WORD_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\w+").expect("Failed to compile regex"));
+static NAMESPACE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b(inline\s+)?namespace\s*([\w:]*)\s*$").expect("Failed to compile regex")
+});
+
+/// A function declaration found by [`extract_function_details`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FunctionDetail {
+    /// The `template <...>` clause, trimmed; empty when the function is not a template.
+    pub template_prefix: String,
+    /// Function specifiers such as `static` or `virtual`, in source order.
+    /// See [`FUNCTION_SPECIFIERS`].
+    pub specifiers: Vec<String>,
+    /// The return type, trimmed. Cv-qualifiers and `*`/`&` are kept as written.
+    /// For `auto f() -> T` this is `T`.
+    pub return_type: String,
+    /// Whether the return type was spelled as a trailing `-> T`.
+    pub has_trailing_return: bool,
+    /// Whether the return type is deduced: `auto` with no trailing return type.
+    pub is_deduced: bool,
+    /// The unqualified function name; destructors keep their `~`.
+    pub name: String,
+    /// What kind of function this is.
+    pub kind: FunctionKind,
+    /// The parameters in declaration order; empty for `()` and `(void)`.
+    /// A trailing C-style `...` is not included; see `is_variadic`.
+    pub parameters: Vec<Parameter>,
+    /// Whether the parameter list ends in a C-style `...`.
+    pub is_variadic: bool,
+    /// Qualifiers after the parameter list, in source order: `const`, `volatile`,
+    /// `noexcept` or `noexcept(expr)`, a legacy `throw(...)`, `override`, `final`, `&`,
+    /// `&&` and `= 0`.
+    pub trailing_qualifiers: Vec<String>,
+    /// Set when the declaration ends in `= default` or `= delete`.
+    pub special_definition: Option<SpecialDefinition>,
+    /// The enclosing namespaces joined with `::`, or `None` at global scope.
+    /// Anonymous namespaces appear as [`ANONYMOUS_NAMESPACE`], and inline namespaces,
+    /// such as the one [`NAMESPACE_MACROS`] open, are left out. The qualifier of a
+    /// qualified name such as `absl::container_internal::Foo` is appended.
+    pub namespace: Option<String>,
+    /// The enclosing classes and structs joined with `::` (e.g. `Outer::Inner`),
+    /// or `None` for free functions.
+    pub enclosing_type: Option<String>,
+    /// The language linkage, from an `extern "C"` block or prefix.
+    pub linkage: Linkage,
+    /// A calling-convention keyword such as `__stdcall` or `WINAPI`, removed from the
+    /// return type. See [`CALLING_CONVENTIONS`].
+    pub calling_convention: Option<String>,
+    /// The 1-based line the declaration starts on.
+    pub line: usize,
+    /// The 1-based column, in characters, the declaration starts at.
+    pub column: usize,
+    /// The header the declaration was read from, when it was parsed from a file.
+    pub path: Option<PathBuf>,
+    /// The access level of a member function, or `None` for a free function.
+    pub access: Option<Access>,
+    /// The byte range of the declaration in the source as given, before
+    /// [`normalize_source`], from its first token up to but not including the closing `;`
+    /// or the opening `{` of the body.
+    pub byte_span: Range<usize>,
+    /// Attributes on the declaration in source order: C++11 attributes without their
+    /// brackets, such as `nodiscard` or `deprecated("msg")`, and attribute-like macros
+    /// such as `ABSL_MUST_USE_RESULT`, with arguments kept verbatim.
+    /// See [`ATTRIBUTE_MACROS`].
+    pub attributes: Vec<String>,
+    /// The message of a `[[deprecated("...")]]` or `ABSL_DEPRECATED("...")` attribute
+    /// with its escapes decoded, empty for a bare `[[deprecated]]` or a message that is
+    /// not a string literal, or `None` if the function is not deprecated.
+    pub deprecated: Option<String>,
+    /// Whether the declaration is followed by a body rather than `;`. The body itself is
+    /// not scanned.
+    pub is_definition: bool,
+    /// The `//` or `/* */` comment block on the lines directly above the declaration,
+    /// with the comment markers and common indentation removed. A blank line or any
+    /// other code in between detaches the comment, so of several overloads sharing a
+    /// banner only the first gets it.
+    pub doc: Option<String>,
+}
+
+/// Keywords moved from the return type into [`FunctionDetail::specifiers`].
+pub const FUNCTION_SPECIFIERS: &[&str] = &[
+    "static",
+    "inline",
+    "virtual",
+    "explicit",
+    "constexpr",
+    "consteval",
+    "friend",
+    "extern",
+];
+
+/// Keywords moved from the type into [`ConstantDetail::specifiers`].
+pub const STORAGE_SPECIFIERS: &[&str] = &[
+    "static",
+    "inline",
+    "constexpr",
+    "constinit",
+    "extern",
+    "thread_local",
+];
+
+// Keywords followed by a parenthesis that are never function names, so statements such
+// as `static_assert(sizeof(T) == 8, "");` or `decltype(Foo()) x;` are not declarations.
+const NON_FUNCTION_NAMES: &[&str] = &[
+    "static_assert",
+    "decltype",
+    "alignof",
+    "sizeof",
+    "if",
+    "for",
+    "while",
+    "switch",
+    "return",
+];
+
+// Leading keywords of declarations that cannot be constants.
+const NON_CONSTANT_KEYWORDS: &[&str] = &[
+    "using",
+    "typedef",
+    "template",
+    "friend",
+    "return",
+    "class",
+    "struct",
+    "union",
+    "enum",
+    "namespace",
+];
+
+/// Attribute-like macros removed from declarations and recorded in
+/// [`FunctionDetail::attributes`].
+pub const ATTRIBUTE_MACROS: &[&str] = &[
+    "ABSL_MUST_USE_RESULT",
+    "ABSL_DEPRECATED",
+    "ABSL_ATTRIBUTE_NOINLINE",
+    "ABSL_ATTRIBUTE_ALWAYS_INLINE",
+    "ABSL_ATTRIBUTE_LIFETIME_BOUND",
+    "ABSL_ATTRIBUTE_PURE_FUNCTION",
+    "ABSL_ATTRIBUTE_CONST_FUNCTION",
+    "ABSL_ATTRIBUTE_NORETURN",
+    "ABSL_ATTRIBUTE_COLD",
+    "ABSL_ATTRIBUTE_HOT",
+    "ABSL_ATTRIBUTE_WEAK",
+    "ABSL_ATTRIBUTE_UNUSED",
+    "ABSL_ATTRIBUTE_RETURNS_NONNULL",
+    "ABSL_PRINTF_ATTRIBUTE",
+    "ABSL_SCANF_ATTRIBUTE",
+    "ABSL_DLL",
+    "ABSL_CONST_INIT",
+];
+
+/// Calling-convention keywords and macros removed from return types and recorded in
+/// [`FunctionDetail::calling_convention`].
+pub const CALLING_CONVENTIONS: &[&str] = &[
+    "__cdecl",
+    "__stdcall",
+    "__fastcall",
+    "__thiscall",
+    "__vectorcall",
+    "__clrcall",
+    "WINAPI",
+    "WINAPIV",
+    "APIENTRY",
+    "CALLBACK",
+    "NTAPI",
+];
+
+/// A pair of macros that open and close an inline namespace, as `ABSL_NAMESPACE_BEGIN`
+/// and `ABSL_NAMESPACE_END` wrap Abseil's versioned namespace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NamespaceMacro {
+    /// The macro opening the namespace.
+    pub begin: &'static str,
+    /// The macro closing it.
+    pub end: &'static str,
+    /// The name of the namespace the macros expand to, reported when inline namespaces
+    /// are kept.
+    pub name: &'static str,
+}
+
+/// Namespace macros every parser recognizes. The Abseil name is that of the default
+/// release; builds of other releases spell it differently.
+pub const NAMESPACE_MACROS: &[NamespaceMacro] = &[NamespaceMacro {
+    begin: "ABSL_NAMESPACE_BEGIN",
+    end: "ABSL_NAMESPACE_END",
+    name: "lts_20250127",
+}];
+
+/// How an anonymous namespace is spelled in [`FunctionDetail::namespace`].
+pub const ANONYMOUS_NAMESPACE: &str = "<anon>";
+
+/// The kind of a [`FunctionDetail`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FunctionKind {
+    /// A function with a return type and a plain name, free or member.
+    Regular,
+    /// A constructor; the return type is empty.
+    Constructor,
+    /// A destructor; the return type is empty.
+    Destructor,
+    /// An overloaded operator such as `operator==`, `operator[]`, `operator new` or a
+    /// literal operator.
+    Operator,
+    /// A conversion operator such as `operator bool`; the return type is the type
+    /// converted to.
+    Conversion,
+}
+
+/// A definition given in place of a body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SpecialDefinition {
+    /// `= default`
+    Default,
+    /// `= delete`
+    Delete,
+}
+
+/// The name this type had before it became [`FunctionDetail`].
+pub type FunctionSignature = FunctionDetail;
+
+impl FunctionDetail {
+    /// Formats where the declaration was found as `path:line:col: name`, with the
+    /// qualified name. Declarations not read from a file use `<input>` as the path.
+    pub fn diagnostic(&self) -> String {
+        format!(
+            "{}:{}:{}: {}",
+            self.path
+                .as_deref()
+                .map_or("<input>".into(), Path::to_string_lossy),
+            self.line,
+            self.column,
+            qualified_name(self)
+        )
+    }
+
+    /// Returns true if the declaration has a `template <...>` clause.
+    pub fn is_templated(&self) -> bool {
+        !self.template_prefix.is_empty()
+    }
+
+    /// Returns true if a `[[deprecated]]` or `ABSL_DEPRECATED` attribute marks the
+    /// declaration.
+    pub fn is_deprecated(&self) -> bool {
+        self.deprecated.is_some()
+    }
+
+    /// Returns true if a `[[nodiscard]]` or `ABSL_MUST_USE_RESULT` attribute asks callers
+    /// to use the result.
+    pub fn must_use(&self) -> bool {
+        self.attributes.iter().any(|attr| {
+            let name = attr.split_once('(').map_or(attr.as_str(), |(name, _)| name);
+            matches!(name.trim(), "nodiscard" | "ABSL_MUST_USE_RESULT")
+        })
+    }
+
+    /// Returns true if the function is declared `friend` inside a class. Such a function
+    /// is not a member, though `enclosing_type` names the class it is declared in.
+    pub fn is_friend(&self) -> bool {
+        self.specifiers
+            .iter()
+            .any(|specifier| specifier == "friend")
+    }
+
+    /// Returns true unless the function is a private or protected class member.
+    pub fn is_public(&self) -> bool {
+        self.access.is_none_or(|access| access == Access::Public)
+    }
+
+    /// Parses the `template <...>` clause into its parameters.
+    pub fn template_parameters(&self) -> Vec<TemplateParameter> {
+        parse_template_parameters(&self.template_prefix)
+    }
+
+    /// Returns the parameters of the `template <...>` clause as written, such as
+    /// `typename... Args`.
+    pub fn template_parameter_list(&self) -> Vec<String> {
+        split_template_clause(&self.template_prefix)
+            .into_iter()
+            .map(str::to_owned)
+            .collect()
+    }
+
+    /// Returns the number of declared parameters.
+    pub fn arity(&self) -> usize {
+        self.parameters.len()
+    }
+
+    /// Returns the return type split into its base and qualifiers. See [`parse_type`].
+    pub fn return_type_info(&self) -> TypeInfo {
+        parse_type(&self.return_type)
+    }
+
+    /// Returns the parameter types in order, for telling overloads apart.
+    pub fn parameter_types(&self) -> Vec<&str> {
+        self.parameters
+            .iter()
+            .map(|param| param.type_name.as_str())
+            .collect()
+    }
+
+    /// Returns true if `other` declares the same function: same scope, name, template
+    /// clause, parameter types and member qualifiers. Parameter names, defaults and
+    /// the declaration's location are ignored.
+    pub fn same_signature(&self, other: &FunctionDetail) -> bool {
+        self.namespace == other.namespace
+            && self.enclosing_type == other.enclosing_type
+            && self.name == other.name
+            && self.template_prefix == other.template_prefix
+            && self.parameter_types() == other.parameter_types()
+            && self.is_variadic == other.is_variadic
+            && self.is_const() == other.is_const()
+            && self.ref_qualifier() == other.ref_qualifier()
+    }
+
+    /// Returns true for a `const` member function.
+    pub fn is_const(&self) -> bool {
+        self.has_qualifier("const")
+    }
+
+    /// Returns true if the function is declared `noexcept`, `noexcept(expr)` or with
+    /// the legacy non-throwing `throw()`.
+    pub fn is_noexcept(&self) -> bool {
+        self.trailing_qualifiers
+            .iter()
+            .any(|q| q == "noexcept" || q.starts_with("noexcept(") || q == "throw()")
+    }
+
+    /// Returns true if the function is declared with the `virtual` keyword. A method that
+    /// only says `override` is not counted; see [`FunctionDetail::is_override`].
+    pub fn is_virtual(&self) -> bool {
+        self.specifiers
+            .iter()
+            .any(|specifier| specifier == "virtual")
+    }
+
+    /// Returns true if the function is declared pure virtual with `= 0`.
+    pub fn is_pure_virtual(&self) -> bool {
+        self.has_qualifier("= 0")
+    }
+
+    /// Returns true if the function is marked `override`.
+    pub fn is_override(&self) -> bool {
+        self.has_qualifier("override")
+    }
+
+    /// Returns true if the function is marked `final`.
+    pub fn is_final(&self) -> bool {
+        self.has_qualifier("final")
+    }
+
+    /// Returns the `&` or `&&` ref-qualifier of a member function, if any.
+    pub fn ref_qualifier(&self) -> Option<RefQualifier> {
+        self.trailing_qualifiers
+            .iter()
+            .find_map(|q| match q.as_str() {
+                "&" => Some(RefQualifier::LValue),
+                "&&" => Some(RefQualifier::RValue),
+                _ => None,
+            })
+    }
+
+    fn has_qualifier(&self, qualifier: &str) -> bool {
+        self.trailing_qualifiers.iter().any(|q| q == qualifier)
+    }
+}
+
+/// The language linkage of a [`FunctionDetail`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Linkage {
+    /// The default C++ linkage, or an explicit `extern "C++"`.
+    #[default]
+    Cpp,
+    /// `extern "C"`: callable without a wrapper.
+    C,
+}
+
+impl Linkage {
+    // Maps the string of an `extern "..."` specification to a linkage.
+    fn from_name(name: &str) -> Self {
+        if name == "C" {
+            Linkage::C
+        } else {
+            Linkage::Cpp
+        }
+    }
+}
+
+/// The access level of a class member.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Access {
+    Public,
+    Protected,
+    Private,
+}
+
+impl Access {
+    // Maps an access specifier keyword to its level.
+    fn from_keyword(keyword: &str) -> Self {
+        match keyword {
+            "public" => Access::Public,
+            "protected" => Access::Protected,
+            _ => Access::Private,
+        }
+    }
+}
+
+/// A class, struct or union definition found by [`extract_class_details`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClassDetail {
+    /// The `template <...>` clause, trimmed; empty when the class is not a template.
+    pub template_prefix: String,
+    /// Which keyword introduced the class.
+    pub kind: ClassKind,
+    /// The unqualified class name.
+    pub name: String,
+    /// The enclosing namespaces joined with `::`, or `None` at global scope.
+    pub namespace: Option<String>,
+    /// The enclosing classes joined with `::`, or `None` unless the class is nested.
+    pub enclosing_type: Option<String>,
+    /// The base classes as written, without access specifiers or `virtual`.
+    pub bases: Vec<String>,
+    /// Whether the class is declared `final`.
+    pub is_final: bool,
+    /// Whether the class can be copied: no copy constructor or copy assignment is
+    /// deleted or non-public, and a user-declared move constructor does not suppress
+    /// the implicit copy constructor.
+    pub is_copyable: bool,
+    /// Whether the class can be moved, by a move constructor or by a usable copy.
+    pub is_movable: bool,
+    /// The public member functions, constructors and destructors included.
+    pub methods: Vec<FunctionDetail>,
+    /// The 1-based line the class head starts on.
+    pub line: usize,
+}
+
+/// A `typedef` or `using` alias found by [`extract_type_aliases`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TypeAlias {
+    /// The `template <...>` clause, trimmed; empty unless this is an alias template.
+    pub template_prefix: String,
+    /// The alias name.
+    pub name: String,
+    /// The aliased type, with the name removed from declarators such as
+    /// `void (*)(int)`.
+    pub aliased_type: String,
+    /// The enclosing namespaces joined with `::`, or `None` at global scope.
+    pub namespace: Option<String>,
+    /// The enclosing classes joined with `::`, or `None` outside a class.
+    pub enclosing_type: Option<String>,
+    /// The 1-based line the alias starts on.
+    pub line: usize,
+}
+
+impl TypeAlias {
+    /// Returns true if this is an alias template.
+    pub fn is_templated(&self) -> bool {
+        !self.template_prefix.is_empty()
+    }
+}
+
+impl ClassDetail {
+    /// Parses the `template <...>` clause into its parameters.
+    pub fn template_parameters(&self) -> Vec<TemplateParameter> {
+        parse_template_parameters(&self.template_prefix)
+    }
+}
+
+/// A `const` or `constexpr` variable found by [`extract_constants`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConstantDetail {
+    /// Storage specifiers such as `static`, `inline` and `constexpr`, in source order.
+    pub specifiers: Vec<String>,
+    /// The declared type, including any `const` and array bounds.
+    pub type_name: String,
+    /// The variable name.
+    pub name: String,
+    /// The initializer after `=`, or the braced initializer itself, verbatim apart
+    /// from collapsed whitespace; `None` for a declaration without one.
+    pub initializer: Option<String>,
+    /// The enclosing namespaces joined with `::`, or `None` at global scope.
+    pub namespace: Option<String>,
+    /// The enclosing classes joined with `::`, or `None` outside a class.
+    pub enclosing_type: Option<String>,
+    /// The 1-based line the declaration starts on.
+    pub line: usize,
+}
+
+impl ConstantDetail {
+    /// Returns true if the variable is declared `constexpr`.
+    pub fn is_constexpr(&self) -> bool {
+        self.specifiers.iter().any(|s| s == "constexpr")
+    }
+}
+
+/// How an `#include` names its header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IncludeKind {
+    /// `#include <header>`, searched for on the system include path.
+    System,
+    /// `#include "header"`, searched for next to the including file first.
+    Quoted,
+}
+
+/// An `#include` directive found by [`extract_includes`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IncludeDirective {
+    /// The header as written between the brackets or quotes.
+    pub path: String,
+    /// Whether the header was named in `<>` or `""`.
+    pub kind: IncludeKind,
+    /// The 1-based line of the directive.
+    pub line: usize,
+}
+
+/// What [`extract_from_file`] found in one header.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FileDetails {
+    /// The file that was read.
+    pub path: PathBuf,
+    /// The file's own `#include` directives, in source order.
+    pub includes: Vec<IncludeDirective>,
+    /// The functions declared in the file, each with `path` set.
+    pub functions: Vec<FunctionDetail>,
+}
+
+impl FileDetails {
+    /// Returns the headers included with `<>`.
+    pub fn system_includes(&self) -> impl Iterator<Item = &str> {
+        self.includes_of(IncludeKind::System)
+    }
+
+    /// Returns the headers included with `""`.
+    pub fn quoted_includes(&self) -> impl Iterator<Item = &str> {
+        self.includes_of(IncludeKind::Quoted)
+    }
+
+    fn includes_of(&self, kind: IncludeKind) -> impl Iterator<Item = &str> {
+        self.includes
+            .iter()
+            .filter(move |include| include.kind == kind)
+            .map(|include| include.path.as_str())
+    }
+}
+
+/// The headers read by [`extract_from_dir`].
+#[derive(Debug, Default)]
+pub struct DirDetails {
+    /// Each header that was read with its functions, sorted by path.
+    pub headers: Vec<(PathBuf, Vec<FunctionDetail>)>,
+    /// Each header that could not be read with the error, sorted by path.
+    pub errors: Vec<(PathBuf, std::io::Error)>,
+}
+
+/// An enumeration found by [`extract_enum_details`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EnumDetail {
+    /// The enum name, or `None` for an anonymous enum.
+    pub name: Option<String>,
+    /// Whether this is a scoped `enum class` or `enum struct`.
+    pub is_scoped: bool,
+    /// The underlying type after `:`, if one is given.
+    pub underlying_type: Option<String>,
+    /// The enumerators in declaration order.
+    pub enumerators: Vec<Enumerator>,
+    /// The enclosing namespaces joined with `::`, or `None` at global scope.
+    pub namespace: Option<String>,
+    /// The enclosing classes joined with `::`, or `None` outside a class.
+    pub enclosing_type: Option<String>,
+    /// The 1-based line the enum starts on.
+    pub line: usize,
+}
+
+/// Another name for [`EnumDetail`], as returned by [`extract_enums`].
+pub type EnumDef = EnumDetail;
+
+/// One enumerator of an [`EnumDetail`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Enumerator {
+    /// The enumerator name.
+    pub name: String,
+    /// The explicit value expression after `=`, verbatim apart from collapsed whitespace.
+    pub value: Option<String>,
+}
+
+/// The keyword that introduced a [`ClassDetail`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ClassKind {
+    Class,
+    Struct,
+    Union,
+}
+
+impl ClassKind {
+    // Maps `class`, `struct` or `union` to its kind.
+    fn from_keyword(keyword: &str) -> Self {
+        match keyword {
+            "struct" => ClassKind::Struct,
+            "union" => ClassKind::Union,
+            _ => ClassKind::Class,
+        }
+    }
+
+    // Members of a `class` are private until an access label says otherwise.
+    fn default_access(self) -> Access {
+        match self {
+            ClassKind::Class => Access::Private,
+            ClassKind::Struct | ClassKind::Union => Access::Public,
+        }
+    }
+}
+
+/// One parameter of a `template <...>` clause, as parsed by
+/// [`parse_template_parameters`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TemplateParameter {
+    /// What the parameter stands for.
+    pub kind: TemplateParameterKind,
+    /// The parameter name, if the clause gives one.
+    pub name: Option<String>,
+    /// The default argument after `=`, trimmed.
+    pub default: Option<String>,
+    /// Whether this is a parameter pack such as `typename... Ts`.
+    pub is_pack: bool,
+}
+
+/// The kind of a [`TemplateParameter`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TemplateParameterKind {
+    /// A type parameter: `typename T` or `class T`.
+    Type,
+    /// A non-type parameter, holding its type: `int` for `int N`.
+    NonType(String),
+    /// A template template parameter, holding its own parameters:
+    /// `template <typename> class Container`.
+    Template(Vec<TemplateParameter>),
+}
+
+/// The ref-qualifier of a member function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RefQualifier {
+    /// `&`: callable on lvalues only.
+    LValue,
+    /// `&&`: callable on rvalues only.
+    RValue,
+}
+
+/// A statement that looked like a function declaration but could not be parsed, as
+/// reported by [`extract_function_details_checked`], or an enumeration skipped by
+/// [`extract_enum_details_checked`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ParseDiagnostic {
+    /// Why the statement was skipped.
+    pub reason: &'static str,
+    /// The statement with its whitespace collapsed.
+    pub text: String,
+    /// The 1-based line the statement starts on.
+    pub line: usize,
+    /// The 1-based column the statement starts at, in characters.
+    pub column: usize,
+}
+
+/// Options for [`extract_function_details_with_options`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExtractOptions {
+    /// The macros to treat as defined, each with its replacement, or `None` for a macro
+    /// defined without one, which counts as `1` as with `-DNAME`.
+    pub defines: HashMap<String, Option<String>>,
+    /// Namespace macros recognized in addition to [`NAMESPACE_MACROS`].
+    pub namespace_macros: Vec<NamespaceMacro>,
+    /// Reports inline namespaces in [`FunctionDetail::namespace`], such as
+    /// `absl::lts_20250127`, instead of leaving them out.
+    pub keep_inline_namespaces: bool,
+}
+
+/// A single parameter of a [`FunctionDetail`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Parameter {
+    /// The parameter type with the name removed, trimmed.
+    pub type_name: String,
+    /// The parameter name, if the declaration gives one.
+    pub name: Option<String>,
+    /// The default argument after `=`, trimmed.
+    pub default: Option<String>,
+    /// Whether this is a pack expansion such as `const Args&... args`. The `...` is
+    /// not part of `type_name`.
+    pub is_pack: bool,
+}
+
+impl Parameter {
+    /// Returns the parameter type split into its base and qualifiers. See [`parse_type`].
+    pub fn type_info(&self) -> TypeInfo {
+        parse_type(&self.type_name)
+    }
+
+    /// Returns the parameter name, or the `arg<index>` placeholder wrappers use for an
+    /// unnamed parameter such as the `int` in `void f(int);`.
+    pub fn name_or_placeholder(&self, index: usize) -> String {
+        self.name.clone().unwrap_or_else(|| format!("arg{}", index))
+    }
+}
+
+/// Parses a `template <...>` clause such as `template <typename T, int N = 4>` into its
+/// parameters. Returns an empty list for an empty or malformed clause.
+pub fn parse_template_parameters(prefix: &str) -> Vec<TemplateParameter> {
+    template_parameters(prefix, 0)
+}
+
+// Parses a template clause nested `depth` template template parameters deep, giving up
+// with no parameters past `MAX_NESTING`.
+fn template_parameters(prefix: &str, depth: usize) -> Vec<TemplateParameter> {
+    if depth > MAX_NESTING {
+        return Vec::new();
+    }
+    split_template_clause(prefix)
+        .into_iter()
+        .map(|param| parse_template_parameter(param, depth))
+        .collect()
+}
+
+// Splits a `template <...>` clause into its parameters as written.
+fn split_template_clause(prefix: &str) -> Vec<&str> {
+    // Stripped by hand rather than matched, as this runs once per level of nesting.
+    let list = prefix
+        .trim_start()
+        .strip_prefix("template")
+        .and_then(|rest| rest.trim_start().strip_prefix('<'))
+        .and_then(|rest| rest.trim_end().strip_suffix('>'));
+    let Some(list) = list else {
+        return Vec::new();
+    };
+    split_top_level(list)
+        .into_iter()
+        .map(|part| list[part].trim())
+        .filter(|part| !part.is_empty())
+        .collect()
+}
+
+// Parses one parameter of a template clause.
+fn parse_template_parameter(param: &str, depth: usize) -> TemplateParameter {
+    let (decl, default) = match split_top_level_char(param, '=') {
+        Some((decl, default)) => (decl.trim_end(), Some(default.trim().to_owned())),
+        None => (param, None),
+    };
+    let (decl, is_pack) = strip_pack_expansion(decl);
+    let decl = decl.trim();
+    let mut words = decl.split_whitespace();
+    let (kind, name) = if decl.starts_with("template") {
+        // The nested clause ends at the `>` matching its `<`.
+        let mut angles = 0usize;
+        let close = decl
+            .char_indices()
+            .find(|&(_, c)| {
+                match c {
+                    '<' => angles += 1,
+                    '>' => angles = angles.saturating_sub(1),
+                    _ => return false,
+                }
+                angles == 0
+            })
+            .map_or(decl.len(), |(i, _)| i + 1);
+        let name = decl[close..]
+            .split_whitespace()
+            .rfind(|word| !matches!(*word, "class" | "typename"))
+            .map(str::to_owned);
+        (
+            TemplateParameterKind::Template(template_parameters(&decl[..close], depth + 1)),
+            name,
+        )
+    } else if matches!(words.next(), Some("typename" | "class"))
+        && words.clone().count() <= 1
+        && words.clone().all(is_identifier)
+    {
+        (TemplateParameterKind::Type, words.next().map(str::to_owned))
+    } else {
+        let parameter = parse_declarator(decl, None);
+        (
+            TemplateParameterKind::NonType(parameter.type_name),
+            parameter.name,
+        )
+    };
+    TemplateParameter {
+        kind,
+        name,
+        default,
+        is_pack,
+    }
+}
+
+// Extracts function details from C++ source.
+pub fn extract_function_details(src: &str) -> Vec<FunctionDetail> {
+    extract_function_details_with_attributes(src, &[])
+}
+
+/// Like [`extract_function_details`], but also treats each name in `extra_macros` as an
+/// attribute-like macro, in addition to [`ATTRIBUTE_MACROS`].
+pub fn extract_function_details_with_attributes(
+    src: &str,
+    extra_macros: &[&str],
+) -> Vec<FunctionDetail> {
+    FunctionScanner::new(src, extra_macros).collect()
+}
+
+/// Like [`extract_function_details`], but also treats each name in `extra_conventions` as a
+/// calling convention, in addition to [`CALLING_CONVENTIONS`].
+pub fn extract_function_details_with_conventions(
+    src: &str,
+    extra_conventions: &[&str],
+) -> Vec<FunctionDetail> {
+    let mut scanner = FunctionScanner::new(src, &[]);
+    scanner
+        .conventions
+        .extend(extra_conventions.iter().map(|&c| c.to_owned()));
+    scanner.collect()
+}
+
+/// Like [`extract_function_details`], but leaves out the private and protected members
+/// of classes, which code outside the class cannot call.
+pub fn extract_public_function_details(src: &str) -> Vec<FunctionDetail> {
+    FunctionScanner::new(src, &[])
+        .filter(FunctionDetail::is_public)
+        .collect()
+}
+
+/// Like [`extract_function_details`], but also reports the statements that looked like
+/// function declarations (words followed by `(` outside a function body) yet could not
+/// be parsed, instead of dropping them silently.
+pub fn extract_function_details_checked(src: &str) -> (Vec<FunctionDetail>, Vec<ParseDiagnostic>) {
+    scan_checked(src, &[])
+}
+
+/// Like [`extract_function_details_checked`], but evaluates `#if`, `#ifdef`, `#ifndef`,
+/// `#elif` and `#else` against `options.defines` and skips the groups they turn off, as
+/// the compiler would. Conditions may use integers, `defined`, macros, `!`, comparisons,
+/// `&&`, `||` and parentheses, and names not defined count as `0`. A condition using
+/// anything else, such as a function-like macro, keeps its group and is reported with
+/// the reason "unknown preprocessor condition".
+pub fn extract_function_details_with_options(
+    src: &str,
+    options: &ExtractOptions,
+) -> (Vec<FunctionDetail>, Vec<ParseDiagnostic>) {
+    let mut scanner = FunctionScanner::with_options(src, &[], Some(options));
+    let details = scanner.by_ref().collect();
+    (details, scanner.diagnostics)
+}
+
+// Scans `src` with `extra_macros` as attribute macros, keeping the diagnostics.
+fn scan_checked(src: &str, extra_macros: &[&str]) -> (Vec<FunctionDetail>, Vec<ParseDiagnostic>) {
+    let mut scanner = FunctionScanner::new(src, extra_macros);
+    let details = scanner.by_ref().collect();
+    (details, scanner.diagnostics)
+}
+
+/// Like [`extract_function_details`], but yields each function as the scan reaches it,
+/// so a caller that stops early does not pay for the rest of the source. The iterator
+/// is fused.
+pub fn extract_function_details_iter(src: &str) -> impl Iterator<Item = FunctionDetail> + '_ {
+    FunctionScanner::new(src, &[])
+}
+
+// Scans source statement by statement, yielding the functions declared or defined.
+struct FunctionScanner<'a> {
+    // The source after `normalize_source`, which every offset below refers to.
+    src: Cow<'a, str>,
+    // Maps offsets back to the source as given, for byte spans.
+    source_map: SourceMap,
+    lines: LineIndex,
+    code: Rc<str>,
+    mask: Rc<str>,
+    // Attribute macros removed from `code`, by position.
+    found: Vec<(usize, String)>,
+    statements: std::vec::IntoIter<Range<usize>>,
+    scopes: ScopeTracker,
+    // End of the body of the last definition; nothing inside it is a declaration.
+    body_end: usize,
+    // Whether the last statement was skipped for being nested too deeply, so a deep
+    // region is reported once rather than for each statement in it.
+    too_deep: bool,
+    // Statements that looked like declarations but were skipped.
+    diagnostics: Vec<ParseDiagnostic>,
+    // Words taken out of return types as calling conventions.
+    conventions: Vec<String>,
+}
+
+impl<'a> FunctionScanner<'a> {
+    fn new(src: &'a str, extra_macros: &[&str]) -> Self {
+        FunctionScanner::with_options(src, extra_macros, None)
+    }
+
+    // Like `new`, but with `options` also skips the conditional groups its defines turn
+    // off, and applies its namespace settings.
+    fn with_options(src: &'a str, extra_macros: &[&str], options: Option<&ExtractOptions>) -> Self {
+        let source_map = SourceMap::new(src);
+        let src = normalize_source(src);
+        let (code, mask, unknown) =
+            prepare_source_with_defines(&src, options.map(|options| &options.defines));
+        let macros: Vec<&str> = ATTRIBUTE_MACROS
+            .iter()
+            .chain(extra_macros)
+            .copied()
+            .collect();
+        let (code, mask, found) = strip_attribute_macros(&code, &mask, &macros);
+        let namespace_macros: Vec<NamespaceMacro> = NAMESPACE_MACROS
+            .iter()
+            .chain(options.map_or(&[][..], |options| &options.namespace_macros))
+            .copied()
+            .collect();
+        let (code, mask, inline) = expand_namespace_macros(&code, &mask, &namespace_macros);
+        let keep_inline = options.is_some_and(|options| options.keep_inline_namespaces);
+        let (code, mask): (Rc<str>, Rc<str>) = (code.into(), mask.into());
+        let lines = LineIndex::new(&src);
+        let diagnostics = unknown
+            .into_iter()
+            .map(|range| {
+                parse_diagnostic(
+                    &src,
+                    &lines,
+                    range.start,
+                    &src[range],
+                    "unknown preprocessor condition",
+                )
+            })
+            .collect();
+        FunctionScanner {
+            src,
+            source_map,
+            lines,
+            statements: statements(&mask).into_iter(),
+            scopes: ScopeTracker::shared(code.clone(), mask.clone())
+                .with_inline_namespaces(inline, keep_inline),
+            code,
+            mask,
+            found,
+            body_end: 0,
+            too_deep: false,
+            diagnostics,
+            conventions: CALLING_CONVENTIONS.iter().map(|&c| c.to_owned()).collect(),
+        }
+    }
+}
+
+impl Iterator for FunctionScanner<'_> {
+    type Item = FunctionDetail;
+
+    fn next(&mut self) -> Option<FunctionDetail> {
+        let FunctionScanner {
+            src,
+            source_map,
+            lines,
+            code,
+            mask,
+            found,
+            statements,
+            scopes,
+            body_end,
+            too_deep,
+            diagnostics,
+            conventions,
+        } = self;
+        let (src, code, mask): (&str, &str, &str) = (src, code, mask);
+        loop {
+            let mut statement = statements.next()?;
+            if statement.start < *body_end {
+                continue;
+            }
+            scopes.advance_to(statement.start);
+            if scopes.too_deep() {
+                if !*too_deep {
+                    diagnostics.push(parse_diagnostic(
+                        src,
+                        lines,
+                        statement.start,
+                        &mask[statement],
+                        "scopes nested too deeply",
+                    ));
+                }
+                *too_deep = true;
+                continue;
+            }
+            *too_deep = false;
+            if let Some(label) = ACCESS_LABEL_REGEX.captures(&mask[statement.clone()]) {
+                scopes.set_access(Access::from_keyword(&label[1]));
+                statement.start += label.get_match().end();
+            }
+            let text = &mask[statement.clone()];
+            let attributes: Vec<_> = found
+                .iter()
+                .filter(|(pos, _)| statement.contains(pos))
+                .collect();
+            let decl_start = (statement.start + text.len() - text.trim_start().len())
+                .min(attributes.first().map_or(usize::MAX, |(pos, _)| *pos));
+            let mut linkage = None;
+            if let Some(cap) = LINKAGE_REGEX.captures(&code[statement.clone()]) {
+                linkage = Some(Linkage::from_name(&cap[1]));
+                statement.start += cap.get_match().end();
+            }
+            let text = &mask[statement.clone()];
+            // A typedef of a function type names a type, not a function.
+            if TYPEDEF_REGEX.is_match(text) {
+                continue;
+            }
+            scopes.advance_to(statement.start);
+            let class = scopes.enclosing_type();
+            let access = scopes.access();
+            let class_name = class.as_deref().map(|c| c.rsplit("::").next().unwrap_or(c));
+            // Matched in place of `text`, so `std::function<int(int)> f()` and
+            // `std::pair<int, int> f()` are not cut short inside their template arguments.
+            // The template clause is blanked, being matched by bracket depth instead.
+            let template_end = template_clause_end(text);
+            let mut head = blank_template_arguments(text);
+            head.replace_range(..template_end, &" ".repeat(template_end));
+            let tokens = tokenize(&head);
+            let Some(Declarator {
+                leading,
+                name,
+                kind,
+                open,
+                pointer,
+            }) = match_declarator(&head, &tokens, text, class_name)
+            else {
+                if called_name(&head, &tokens)
+                    .is_some_and(|name| !NON_FUNCTION_NAMES.contains(&name))
+                {
+                    diagnostics.push(parse_diagnostic(
+                        src,
+                        lines,
+                        decl_start,
+                        text,
+                        "no function declarator matched",
+                    ));
+                }
+                continue;
+            };
+            if NON_FUNCTION_NAMES.contains(&name.as_str()) {
+                continue;
+            }
+            let (qualifier, name) = match name.rsplit_once("::") {
+                Some((qualifier, name)) if kind == FunctionKind::Regular => {
+                    (Some(qualifier.to_owned()), name.to_owned())
+                }
+                _ => (None, name),
+            };
+            let namespace = match (scopes.namespace(), qualifier) {
+                (Some(outer), Some(qualifier)) => Some(format!("{}::{}", outer, qualifier)),
+                (outer, qualifier) => outer.or(qualifier),
+            };
+            let list = param_list(mask, statement.start + open);
+            if list.end == mask.len() {
+                diagnostics.push(parse_diagnostic(
+                    src,
+                    lines,
+                    decl_start,
+                    text,
+                    "unclosed parameter list",
+                ));
+                continue;
+            }
+            let (calling_convention, leading) =
+                split_calling_convention(&text[leading], conventions);
+            let (specifiers, mut return_type) = split_specifiers(&leading);
+            if kind == FunctionKind::Conversion {
+                return_type = normalize_type(name.trim_start_matches("operator "));
+            }
+            let is_definition = mask.as_bytes().get(statement.end) == Some(&b'{');
+            if is_definition {
+                *body_end = block_end(mask, statement.end + 1);
+            }
+            let trailing_start = list.end + 1;
+            let mut trailing = mask
+                .get(trailing_start..statement.end)
+                .unwrap_or("")
+                .to_owned();
+            if let Some(pointer) = pointer {
+                // The pointee's parameter list follows the `)` closing the declarator; the
+                // function's own qualifiers sit before that `)`.
+                let rest = mask.get(trailing_start..statement.end).unwrap_or("");
+                let pointee_open = rest
+                    .find(')')
+                    .and_then(|close| Some((trailing_start + close, rest[close + 1..].find('(')?)));
+                let Some((close, pointee_open)) = pointee_open else {
+                    diagnostics.push(parse_diagnostic(
+                        src,
+                        lines,
+                        decl_start,
+                        text,
+                        "function pointer return type has no parameter list",
+                    ));
+                    continue;
+                };
+                let pointee = param_list(mask, close + pointee_open + 2);
+                return_type = format!(
+                    "{} ({})({})",
+                    return_type,
+                    pointer,
+                    collapse_whitespace(&mask[pointee.clone()])
+                );
+                trailing = format!(
+                    "{} {}",
+                    &mask[trailing_start..close],
+                    mask.get(pointee.end + 1..statement.end).unwrap_or("")
+                );
+            }
+            let trailing_return = parse_trailing_return(&trailing);
+            let has_trailing_return = trailing_return.is_some();
+            if let Some(ty) = trailing_return {
+                return_type = ty;
+            }
+            let is_deduced = !has_trailing_return
+                && return_type
+                    .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+                    .any(|word| word == "auto");
+            let mut parameters = parse_parameters(&mask[list.clone()], &code[list.clone()]);
+            let is_variadic = parameters.last().is_some_and(|p| p.type_name == "...");
+            if is_variadic {
+                parameters.pop();
+            }
+            let attributes: Vec<String> = attributes
+                .into_iter()
+                .map(|(_, attr)| attr.clone())
+                .collect();
+            return Some(FunctionDetail {
+                template_prefix: collapse_whitespace(&text[..template_end]),
+                specifiers,
+                return_type,
+                has_trailing_return,
+                is_deduced,
+                name,
+                kind,
+                parameters,
+                is_variadic,
+                trailing_qualifiers: parse_trailing_qualifiers(&trailing),
+                special_definition: parse_special_definition(&trailing),
+                namespace,
+                enclosing_type: class,
+                linkage: linkage.unwrap_or_else(|| scopes.linkage()),
+                calling_convention,
+                line: lines.line(decl_start),
+                column: lines.column(src, decl_start),
+                path: None,
+                access,
+                byte_span: source_map.span(decl_start..statement.end),
+                deprecated: deprecation_message(&attributes),
+                attributes,
+                is_definition,
+                doc: doc_comment(src, decl_start),
+            });
+        }
+    }
+}
+
+impl FusedIterator for FunctionScanner<'_> {}
+
+// A function declarator at the start of a statement, as found by `match_declarator`.
+// Offsets are into the statement.
+struct Declarator {
+    // The specifiers and return type before the name.
+    leading: Range<usize>,
+    name: String,
+    kind: FunctionKind,
+    // Offset just past the `(` opening the parameter list.
+    open: usize,
+    // The `*` or `&` of a returned function pointer, as in `void (*signal(int))(int)`.
+    pointer: Option<String>,
+}
+
+// Matches the declarator of the statement `head`, tokenized as `tokens`: a constructor
+// or destructor of `class_name`, an operator, a function returning a function pointer,
+// or a plain function, in that order. `text` is the statement before `head` had its
+// template arguments blanked, and spells operator names.
+fn match_declarator(
+    head: &str,
+    tokens: &[Token],
+    text: &str,
+    class_name: Option<&str>,
+) -> Option<Declarator> {
+    let parser = DeclaratorParser { head, tokens };
+    class_name
+        .and_then(|class_name| parser.constructor(class_name))
+        .or_else(|| parser.operator(text))
+        .or_else(|| parser.function_pointer())
+        .or_else(|| parser.function())
+}
+
+// Returns the first identifier in `head` called like a function before any `=`, if the
+// statement looks like a declaration the matcher could not parse rather than a variable
+// initialized by a call.
+fn called_name<'a>(head: &'a str, tokens: &'a [Token]) -> Option<&'a str> {
+    let parser = DeclaratorParser { head, tokens };
+    (0..tokens.len())
+        .take_while(|&i| !parser.is(i, "="))
+        .find(|&i| parser.is_identifier(i) && parser.is(i + 1, "("))
+        .map(|i| parser.text(i))
+}
+
+// Walks the tokens of a statement head. Indices past the last token read as an empty
+// token, so lookahead never needs bounds checks.
+struct DeclaratorParser<'a> {
+    head: &'a str,
+    tokens: &'a [Token],
+}
+
+impl<'a> DeclaratorParser<'a> {
+    fn text(&self, i: usize) -> &'a str {
+        self.tokens
+            .get(i)
+            .map_or("", |token| &self.head[token.span.clone()])
+    }
+
+    fn is(&self, i: usize, text: &str) -> bool {
+        self.text(i) == text
+    }
+
+    fn is_identifier(&self, i: usize) -> bool {
+        self.tokens
+            .get(i)
+            .is_some_and(|token| token.kind == TokenKind::Identifier)
+    }
+
+    // True for the tokens a return type is spelled with: words, `::`, `*`, `&`, and the
+    // angle brackets left once template arguments are blanked.
+    fn is_type_token(&self, i: usize) -> bool {
+        match self.tokens.get(i).map(|token| token.kind) {
+            Some(TokenKind::Identifier | TokenKind::Number) => true,
+            Some(TokenKind::Punct) => matches!(self.text(i), ":" | "*" | "&" | "<" | ">"),
+            _ => false,
+        }
+    }
+
+    // True if the token at `i` starts right where the one before it ends.
+    fn touches(&self, i: usize) -> bool {
+        i > 0 && i < self.tokens.len() && self.tokens[i - 1].span.end == self.tokens[i].span.start
+    }
+
+    // The offsets covered by the tokens `from..to`, empty at `from` if there are none.
+    fn span(&self, from: usize, to: usize) -> Range<usize> {
+        let start = self
+            .tokens
+            .get(from)
+            .map_or(self.head.len(), |token| token.span.start);
+        match to.checked_sub(1).filter(|&last| last >= from) {
+            Some(last) => start..self.tokens[last].span.end,
+            None => start..start,
+        }
+    }
+
+    // The offsets before the token at `to`, where the return type and specifiers are.
+    // Blanked punctuation, such as the parentheses of `decltype(auto)`, stays inside.
+    fn leading(&self, to: usize) -> Range<usize> {
+        let start = |i: usize| {
+            self.tokens
+                .get(i)
+                .map_or(self.head.len(), |token| token.span.start)
+        };
+        start(0)..start(to)
+    }
+
+    // The index of the first token that cannot be part of a return type.
+    fn type_end(&self, from: usize) -> usize {
+        (from..self.tokens.len())
+            .find(|&i| !self.is_type_token(i))
+            .unwrap_or(self.tokens.len())
+    }
+
+    // Parses a name such as `absl::StrCat` at `i`, returning the index just past it.
+    fn qualified_name(&self, i: usize) -> Option<usize> {
+        if !self.is_identifier(i) {
+            return None;
+        }
+        let mut end = i + 1;
+        while self.is(end, ":")
+            && self.is(end + 1, ":")
+            && self.is_identifier(end + 2)
+            && (end..end + 3).all(|j| self.touches(j))
+        {
+            end += 3;
+        }
+        Some(end)
+    }
+
+    // `ClassName(` or `~ClassName(`, after nothing but specifiers.
+    fn constructor(&self, class_name: &str) -> Option<Declarator> {
+        let mut i = 0;
+        while self.is_identifier(i) && !self.is(i + 1, "(") {
+            i += 1;
+        }
+        let tilde = self.is(i, "~");
+        let name = i + usize::from(tilde);
+        if !self.is_identifier(name) || !self.is(name + 1, "(") || self.text(name) != class_name {
+            return None;
+        }
+        let leading = self.leading(i);
+        if !split_specifiers(&self.head[leading.clone()]).1.is_empty() {
+            return None;
+        }
+        Some(Declarator {
+            leading,
+            name: format!("{}{}", if tilde { "~" } else { "" }, class_name),
+            kind: if tilde {
+                FunctionKind::Destructor
+            } else {
+                FunctionKind::Constructor
+            },
+            open: self.tokens[name + 1].span.end,
+            pointer: None,
+        })
+    }
+
+    // `operator` followed by its symbol or conversion type, then `(`.
+    fn operator(&self, text: &str) -> Option<Declarator> {
+        let keyword = (0..self.type_end(0)).find_map(|i| {
+            if !self.is(i, "operator") {
+                return None;
+            }
+            self.operator_symbol_end(i + 1).map(|end| (i, end))
+        })?;
+        let (keyword, end) = keyword;
+        let symbol = &text[self.span(keyword + 1, end)];
+        Some(Declarator {
+            leading: self.leading(keyword),
+            name: operator_name(symbol),
+            kind: operator_kind(symbol),
+            open: self.tokens[end].span.end,
+            pointer: None,
+        })
+    }
+
+    // Parses an operator symbol starting at `i`, returning the index of the `(` after
+    // it. The first reading followed by `(` wins, so `operator()(` is the call operator
+    // and `operator<<=(` a compound assignment rather than `<<`.
+    fn operator_symbol_end(&self, i: usize) -> Option<usize> {
+        let mut ends = Vec::new();
+        match self.text(i) {
+            "(" if self.is(i + 1, ")") => ends.push(i + 2),
+            "[" if self.is(i + 1, "]") => ends.push(i + 2),
+            "\"\"" if self.is_type_token(i + 1) && !self.is_punct(i + 1) => ends.push(i + 2),
+            "new" | "delete" => {
+                if self.is(i + 1, "[") && self.is(i + 2, "]") {
+                    ends.push(i + 3);
+                }
+                ends.push(i + 1);
+            }
+            "co_await" => ends.push(i + 1),
+            _ => {}
+        }
+        // The longest run of touching punctuation that spells an operator.
+        let mut symbol = String::new();
+        let mut runs = Vec::new();
+        for j in i..i + 3 {
+            if !self.is_punct(j) || (j > i && !self.touches(j)) {
+                break;
+            }
+            symbol.push_str(self.text(j));
+            if OPERATOR_SYMBOLS.contains(&symbol.as_str()) {
+                runs.push(j + 1);
+            }
+        }
+        ends.extend(runs.into_iter().rev());
+        // A conversion function, whose type runs up to the `(`.
+        if self.is_identifier(i) || self.is(i, ":") {
+            let end = (i..self.tokens.len())
+                .find(|&j| !(self.is_type_token(j) || self.is(j, ",")))
+                .unwrap_or(self.tokens.len());
+            ends.push(end);
+        }
+        ends.into_iter().find(|&end| self.is(end, "("))
+    }
+
+    fn is_punct(&self, i: usize) -> bool {
+        self.tokens
+            .get(i)
+            .is_some_and(|token| token.kind == TokenKind::Punct)
+    }
+
+    // `(*name(` or `(&name(`: a function returning a pointer to a function.
+    fn function_pointer(&self) -> Option<Declarator> {
+        let paren = self.type_end(0);
+        if paren == 0 || !self.is(paren, "(") || !matches!(self.text(paren + 1), "*" | "&") {
+            return None;
+        }
+        let end = self.qualified_name(paren + 2)?;
+        if !self.is(end, "(") {
+            return None;
+        }
+        Some(Declarator {
+            leading: self.leading(paren),
+            name: self.head[self.span(paren + 2, end)].to_owned(),
+            kind: FunctionKind::Regular,
+            open: self.tokens[end].span.end,
+            pointer: Some(self.text(paren + 1).to_owned()),
+        })
+    }
+
+    // A return type, then a possibly qualified name and `(`. The name is set off from
+    // the type by whitespace, or by the `*` or `&` of `int *f(` and `int&f(`.
+    fn function(&self) -> Option<Declarator> {
+        let open = self.type_end(0);
+        if !self.is(open, "(") || !self.is_identifier(open.checked_sub(1)?) {
+            return None;
+        }
+        let mut name = open - 1;
+        while name >= 3
+            && self.is(name - 1, ":")
+            && self.is(name - 2, ":")
+            && self.is_identifier(name - 3)
+            && (name - 2..=name).all(|j| self.touches(j))
+        {
+            name -= 3;
+        }
+        if name == 0 || (self.touches(name) && !matches!(self.text(name - 1), "*" | "&")) {
+            return None;
+        }
+        Some(Declarator {
+            leading: self.leading(name),
+            name: self.head[self.span(name, open)].to_owned(),
+            kind: FunctionKind::Regular,
+            open: self.tokens[open].span.end,
+            pointer: None,
+        })
+    }
+}
+
+// The symbols an operator function can be named with.
+const OPERATOR_SYMBOLS: &[&str] = &[
+    "->*", "->", "<=>", "<<=", ">>=", "<<", ">>", "&&", "||", "++", "--", "-", "+", "*", "/", "%",
+    "^", "&", "|", "~", "!", "=", "<", ">", "-=", "+=", "*=", "/=", "%=", "^=", "&=", "|=", "!=",
+    "==", "<=", ">=", ",",
+];
+
+// Returns the message of the first deprecation attribute in `attributes`, decoded from
+// its string literal, or an empty string if it has none.
+fn deprecation_message(attributes: &[String]) -> Option<String> {
+    attributes.iter().find_map(|attr| {
+        let (name, args) = attr.split_once('(').unwrap_or((attr, ""));
+        if !matches!(name.trim(), "deprecated" | "ABSL_DEPRECATED") {
+            return None;
+        }
+        let args = args.trim_end().strip_suffix(')').unwrap_or(args);
+        Some(string_literal_value(args).unwrap_or_default())
+    })
+}
+
+// Decodes the string literals in `text`, such as `"Use \"Foo\" " "instead"`, into the
+// text they spell, or returns `None` if `text` holds anything else.
+fn string_literal_value(text: &str) -> Option<String> {
+    let mut value = String::new();
+    let mut chars = text.trim().chars();
+    let mut found = false;
+    loop {
+        match chars.find(|c| !c.is_whitespace()) {
+            Some('"') => found = true,
+            Some(_) => return None,
+            None => return found.then_some(value),
+        }
+        loop {
+            match chars.next()? {
+                '"' => break,
+                '\\' => value.push(match chars.next()? {
+                    'n' => '\n',
+                    't' => '\t',
+                    'r' => '\r',
+                    '0' => '\0',
+                    c => c,
+                }),
+                c => value.push(c),
+            }
+        }
+    }
+}
+
+// Builds the diagnostic for the statement `text` starting at `pos` in `src`.
+fn parse_diagnostic(
+    src: &str,
+    lines: &LineIndex,
+    pos: usize,
+    text: &str,
+    reason: &'static str,
+) -> ParseDiagnostic {
+    ParseDiagnostic {
+        reason,
+        text: collapse_whitespace(text),
+        line: lines.line(pos),
+        column: lines.column(src, pos),
+    }
+}
+
+// The offsets at which the lines of a source start, so positions can be turned into
+// line numbers without counting newlines from the start each time.
+struct LineIndex {
+    starts: Vec<usize>,
+}
+
+impl LineIndex {
+    fn new(src: &str) -> Self {
+        let newlines = src.match_indices('\n').map(|(i, _)| i + 1);
+        LineIndex {
+            starts: std::iter::once(0).chain(newlines).collect(),
+        }
+    }
+
+    // The 1-based line that `pos` is on.
+    fn line(&self, pos: usize) -> usize {
+        self.starts.partition_point(|&start| start <= pos)
+    }
+
+    // The 1-based column of `pos` in `src`, counted in characters.
+    fn column(&self, src: &str, pos: usize) -> usize {
+        src[self.starts[self.line(pos) - 1]..pos].chars().count() + 1
+    }
+}
+
+// Spells an operator function name the way it is usually written: `operator==`,
+// `operator()`, `operator new[]`, `operator""_s`, or `operator bool` for conversions.
+fn operator_name(token: &str) -> String {
+    let token = collapse_whitespace(token);
+    if token.starts_with("new") || token.starts_with("delete") {
+        format!("operator {}", token.replace(' ', ""))
+    } else if token.starts_with(|c: char| c.is_alphanumeric() || c == '_') {
+        format!("operator {}", token)
+    } else {
+        format!("operator{}", token.replace(' ', ""))
+    }
+}
+
+// Tells a conversion operator, whose token is a type, from the other operators.
+fn operator_kind(token: &str) -> FunctionKind {
+    let token = token.trim_start();
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+    let is_keyword = ["new", "delete", "co_await"].iter().any(|keyword| {
+        token
+            .strip_prefix(keyword)
+            .is_some_and(|rest| !rest.starts_with(is_word_char))
+    });
+    if token.starts_with(is_word_char) && !is_keyword {
+        FunctionKind::Conversion
+    } else {
+        FunctionKind::Operator
+    }
+}
+
+// Reads a `= default` or `= delete` suffix from the text after a parameter list.
+fn parse_special_definition(trailing: &str) -> Option<SpecialDefinition> {
+    let (_, rest) = split_top_level_char(trailing, '=')?;
+    match rest.trim() {
+        "default" => Some(SpecialDefinition::Default),
+        "delete" => Some(SpecialDefinition::Delete),
+        _ => None,
+    }
+}
+
+// Collects the qualifiers that follow a parameter list. A trailing return type
+// (`-> T`) is skipped so that a `&` inside it is not taken as a ref-qualifier.
+fn parse_trailing_qualifiers(text: &str) -> Vec<String> {
+    let bytes = text.as_bytes();
+    let mut qualifiers = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'&' if bytes.get(i + 1) == Some(&b'&') => {
+                qualifiers.push("&&".to_owned());
+                i += 2;
+            }
+            b'&' => {
+                qualifiers.push("&".to_owned());
+                i += 1;
+            }
+            b'=' => {
+                if text[i + 1..].trim() == "0" {
+                    qualifiers.push("= 0".to_owned());
+                }
+                break;
+            }
+            b'-' if bytes.get(i + 1) == Some(&b'>') => i = trailing_return_end(text, i + 2),
+            byte if byte.is_ascii_alphabetic() || byte == b'_' => {
+                let end = text[i..]
+                    .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                    .map_or(text.len(), |pos| i + pos);
+                let word = &text[i..end];
+                i = end;
+                match word {
+                    // `throw(...)` is the exception specification from before C++17.
+                    "noexcept" | "throw" if text[i..].trim_start().starts_with('(') => {
+                        let open = i + text[i..].find('(').unwrap_or(0) + 1;
+                        let expr = param_list(text, open);
+                        qualifiers.push(format!(
+                            "{}({})",
+                            word,
+                            collapse_whitespace(&text[expr.clone()])
+                        ));
+                        i = (expr.end + 1).min(text.len());
+                    }
+                    "const" | "volatile" | "noexcept" | "override" | "final" => {
+                        qualifiers.push(word.to_owned());
+                    }
+                    _ => {}
+                }
+            }
+            _ => i += 1,
+        }
+    }
+    qualifiers
+}
+
+// Reads the `T` of a trailing `-> T` return type from the text after a parameter list.
+fn parse_trailing_return(text: &str) -> Option<String> {
+    let bytes = text.as_bytes();
+    let mut depth = 0usize;
+    for i in 0..bytes.len() {
+        match bytes[i] {
+            b'(' | b'[' => depth += 1,
+            b')' | b']' => depth = depth.saturating_sub(1),
+            b'=' if depth == 0 => return None,
+            b'-' if depth == 0 && bytes.get(i + 1) == Some(&b'>') => {
+                let end = trailing_return_end(text, i + 2);
+                return Some(normalize_type(&text[i + 2..end]));
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+// Finds where a trailing return type starting at `start` ends: at a top-level `=`,
+// `override` or `final`, or the end of the text.
+fn trailing_return_end(text: &str, start: usize) -> usize {
+    let bytes = text.as_bytes();
+    let mut depth = 0usize;
+    let mut i = start;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'<' | b'(' | b'[' => depth += 1,
+            b'>' | b')' | b']' => depth = depth.saturating_sub(1),
+            b'=' if depth == 0 => break,
+            _ if depth == 0
+                && (keyword_at(bytes, i, b"override") || keyword_at(bytes, i, b"final")) =>
+            {
+                break;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    i
+}
+
+// Returns true if `keyword` is a whole word at `i` in `bytes`, not part of a longer
+// identifier. Bytes outside ASCII count as identifier characters.
+fn keyword_at(bytes: &[u8], i: usize, keyword: &[u8]) -> bool {
+    let is_word = |byte: &u8| byte.is_ascii_alphanumeric() || *byte == b'_' || !byte.is_ascii();
+    bytes[i..].starts_with(keyword)
+        && !i
+            .checked_sub(1)
+            .and_then(|j| bytes.get(j))
+            .is_some_and(is_word)
+        && !bytes.get(i + keyword.len()).is_some_and(is_word)
+}
+
+// Returns the comment directly above the declaration starting at `start`, if any.
+fn doc_comment(src: &str, start: usize) -> Option<String> {
+    let before = &src[..start];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    if !before[line_start..].trim().is_empty() || line_start == 0 {
+        return None;
+    }
+    let mut lines: Vec<String> = Vec::new();
+    let mut rest = before[..line_start - 1].split('\n').rev().map(str::trim);
+    while let Some(line) = rest.next() {
+        if let Some(text) = line.strip_prefix("//") {
+            lines.push(text.trim_start_matches(['/', '!']).to_owned());
+        } else if let Some(text) = line.strip_suffix("*/") {
+            // Walk back to the line opening the block comment.
+            let mut block = vec![text.to_owned()];
+            let mut line = text.to_owned();
+            while !line.contains("/*") {
+                line = rest.next()?.to_owned();
+                block.push(line.clone());
+            }
+            for line in block {
+                let line = match line.find("/*") {
+                    Some(i) => line[i + 2..].trim_start_matches(['*', '!']).to_owned(),
+                    None => line,
+                };
+                let trimmed = line.trim_start();
+                lines.push(match trimmed.strip_prefix('*') {
+                    Some(text) => text.to_owned(),
+                    None => trimmed.to_owned(),
+                });
+            }
+            break;
+        } else {
+            break;
+        }
+    }
+    lines.reverse();
+    while lines.first().is_some_and(|line| line.trim().is_empty()) {
+        lines.remove(0);
+    }
+    while lines.last().is_some_and(|line| line.trim().is_empty()) {
+        lines.pop();
+    }
+    let indent = lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()?;
+    let lines: Vec<&str> = lines
+        .iter()
+        .map(|line| line.get(indent..).unwrap_or("").trim_end())
+        .collect();
+    Some(lines.join("\n"))
+}
+
+// Returns the index just past the `}` matching the `{` before `start`, or the text
+// length if it is never closed.
+fn block_end(mask: &str, start: usize) -> usize {
+    block_close(mask, start).map_or(mask.len(), |close| close + 1)
+}
+
+// Returns the index of the `}` matching the `{` before `start`, or `None` if it is never
+// closed.
+fn block_close(mask: &str, start: usize) -> Option<usize> {
+    let mut depth = 1usize;
+    for (i, byte) in mask.bytes().enumerate().skip(start) {
+        match byte {
+            b'{' => depth += 1,
+            b'}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+// Splits masked source into statements ending at `;`, `{` or `}` outside parentheses,
+// so a declaration is matched the same however it is wrapped across lines.
+fn statements(mask: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, byte) in mask.bytes().enumerate() {
+        match byte {
+            b'(' => depth += 1,
+            b')' => depth = depth.saturating_sub(1),
+            b';' | b'{' | b'}' if depth == 0 => {
+                ranges.push(start..i);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    ranges.push(start..mask.len());
+    ranges
+}
+
+// Returns the length of the `template <...>` clauses at the start of `text`, matching
+// each `<` with its `>` so nested arguments like `std::enable_if_t<X<T>::value>` stay
+// inside the clause. Returns 0 if `text` has no template clause.
+fn template_clause_end(text: &str) -> usize {
+    let mut end = 0;
+    while let Some(cap) = TEMPLATE_KEYWORD_REGEX.captures(&text[end..]) {
+        let open = end + cap.get_match().end();
+        let mut depth = Nesting {
+            brackets: 0,
+            angles: 1,
+        };
+        let close = text[open..].char_indices().find_map(|(i, c)| {
+            depth.step(c, &text[..open + i]);
+            depth.is_top_level().then_some(open + i + 1)
+        });
+        match close {
+            Some(close) => end = close,
+            None => break,
+        }
+    }
+    end
+}
+
+// Replaces the punctuation inside template argument lists, such as parentheses, commas
+// and the `!` of `!std::is_same<T, U>::value`, with spaces, up to the first parenthesis
+// outside them, so the head regexes see the function's own parameter list first. `<`,
+// `>`, `::`, `*` and `&` are kept. `<` and `>` inside those parentheses do not nest.
+// The operand of a `decltype`, as in `decltype(auto) f()`, is blanked the same way.
+fn blank_template_arguments(text: &str) -> String {
+    let mut head = text.as_bytes().to_vec();
+    let (mut angles, mut parens, mut decltype) = (0usize, 0usize, 0usize);
+    for (i, byte) in text.bytes().enumerate() {
+        if decltype > 0 || (byte == b'(' && angles == 0 && DECLTYPE_END_REGEX.is_match(&text[..i]))
+        {
+            match byte {
+                b'(' => decltype += 1,
+                b')' => decltype -= 1,
+                _ => {}
+            }
+            if byte.is_ascii_punctuation() && byte != b'_' {
+                head[i] = b' ';
+            }
+            continue;
+        }
+        match byte {
+            b'<' if parens == 0 => angles += 1,
+            b'>' if parens == 0 => angles = angles.saturating_sub(1),
+            b'(' | b')' if angles == 0 => break,
+            b'(' => parens += 1,
+            b')' => parens = parens.saturating_sub(1),
+            b'o' if angles == 0 && OPERATOR_WORD_REGEX.is_match(&text[i..]) => {
+                // A conversion to a type may itself have template arguments.
+                match CONVERSION_WORD_REGEX.captures(&text[i..]) {
+                    Some(cap) if !matches!(&cap[1], "new" | "delete" | "co_await") => continue,
+                    _ => break,
+                }
+            }
+            _ => {}
+        }
+        if angles > 0
+            && byte.is_ascii_punctuation()
+            && !matches!(byte, b'<' | b'>' | b':' | b'*' | b'&' | b'_')
+        {
+            head[i] = b' ';
+        }
+    }
+    String::from_utf8(head).unwrap_or_else(|_| text.to_owned())
+}
+
+// Separates function specifiers from the words of a return type.
+fn split_specifiers(text: &str) -> (Vec<String>, String) {
+    let (specifiers, rest): (Vec<&str>, Vec<&str>) = text
+        .split_whitespace()
+        .partition(|word| FUNCTION_SPECIFIERS.contains(word));
+    (
+        specifiers.into_iter().map(str::to_owned).collect(),
+        normalize_type(&rest.join(" ")),
+    )
+}
+
+// Takes the first word of `text` found in `conventions` out of it.
+fn split_calling_convention(text: &str, conventions: &[String]) -> (Option<String>, String) {
+    let mut convention = None;
+    let rest: Vec<&str> = text
+        .split_whitespace()
+        .filter(|word| {
+            let is_convention = convention.is_none() && conventions.iter().any(|c| c == word);
+            if is_convention {
+                convention = Some((*word).to_owned());
+            }
+            !is_convention
+        })
+        .collect();
+    (convention, rest.join(" "))
+}
+
+// Collapses each run of whitespace, newlines included, to one space and trims.
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Spells a C++ type canonically: whitespace collapsed to single spaces and trimmed, and
+/// dropped just inside angle brackets and before `&`, `*`, `>` and `,`. So a type wrapped
+/// as `const\n    std::vector< int > &` reads `const std::vector<int>&`. Spacing inside
+/// parentheses, which hold expressions such as `(N > 2)`, is kept.
+pub fn normalize_type(text: &str) -> String {
+    let collapsed = collapse_whitespace(text);
+    let mut out = String::with_capacity(collapsed.len());
+    let mut depth = Nesting::default();
+    for (i, c) in collapsed.char_indices() {
+        let next = &collapsed[i + c.len_utf8()..];
+        let dropped = c == ' '
+            && depth.brackets == 0
+            && (out.ends_with('<') || next.starts_with(['>', '&', '*', ',']));
+        if !dropped {
+            out.push(c);
+        }
+        depth.step(c, &collapsed[..i]);
+    }
+    out
+}
+
+// A brace-delimited scope opened before the current scan position.
+enum Scope {
+    Namespace(String),
+    InlineNamespace(String),
+    // A class and the access level in effect at the current position.
+    Class(String, Access),
+    Linkage(Linkage),
+    Block,
+}
+
+// How deeply scopes and template clauses may nest before the parser stops descending.
+// Real headers stay far below this; past it, each nested name would grow with the depth
+// and recursion could exhaust the stack, so those regions are skipped instead.
+const MAX_NESTING: usize = 256;
+
+// Tracks which scopes are open while moving forward through masked source. The
+// unmasked `code` is kept alongside to read `extern "C"` strings.
+struct ScopeTracker {
+    code: Rc<str>,
+    src: Rc<str>,
+    pos: usize,
+    // Start of the text that will introduce the next '{'.
+    head: usize,
+    stack: Vec<Scope>,
+    // The '{' a namespace macro was expanded to, by position, with the namespace name.
+    inline_namespaces: HashMap<usize, String>,
+    // Whether `namespace` reports inline namespaces.
+    keep_inline: bool,
+}
+
+impl ScopeTracker {
+    fn new(code: &str, src: &str) -> Self {
+        Self::shared(code.into(), src.into())
+    }
+
+    // Tracks views that the caller also holds.
+    fn shared(code: Rc<str>, src: Rc<str>) -> Self {
+        ScopeTracker {
+            code,
+            src,
+            pos: 0,
+            head: 0,
+            stack: Vec::new(),
+            inline_namespaces: HashMap::new(),
+            keep_inline: false,
+        }
+    }
+
+    // Opens an inline namespace at each expanded namespace macro, as found by
+    // `expand_namespace_macros`, reporting them only with `keep`.
+    fn with_inline_namespaces(mut self, opened: Vec<(usize, String)>, keep: bool) -> Self {
+        self.inline_namespaces = opened.into_iter().collect();
+        self.keep_inline = keep;
+        self
+    }
+
+    // Consumes the source up to `target`, opening and closing scopes on the way.
+    fn advance_to(&mut self, target: usize) {
+        let bytes = self.src.as_bytes();
+        while self.pos < target.min(bytes.len()) {
+            match bytes[self.pos] {
+                b'{' => {
+                    let head = &self.src[self.head..self.pos];
+                    let scope = match NAMESPACE_REGEX.captures(head) {
+                        _ if self.inline_namespaces.contains_key(&self.pos) => {
+                            Scope::InlineNamespace(self.inline_namespaces[&self.pos].clone())
+                        }
+                        Some(cap) if cap[2].is_empty() => {
+                            Scope::Namespace(ANONYMOUS_NAMESPACE.to_owned())
+                        }
+                        Some(cap) if cap.get(1).is_some() => {
+                            Scope::InlineNamespace(cap[2].to_owned())
+                        }
+                        Some(cap) => Scope::Namespace(cap[2].to_owned()),
+                        None => match CLASS_REGEX.captures(head) {
+                            Some(cap) if cap.get(1).is_none() => Scope::Class(
+                                cap[3].to_owned(),
+                                ClassKind::from_keyword(&cap[2]).default_access(),
+                            ),
+                            _ => match LINKAGE_REGEX.captures(&self.code[self.head..self.pos]) {
+                                Some(cap) if head[cap.get_match().end()..].trim().is_empty() => {
+                                    Scope::Linkage(Linkage::from_name(&cap[1]))
+                                }
+                                _ => Scope::Block,
+                            },
+                        },
+                    };
+                    self.stack.push(scope);
+                    self.head = self.pos + 1;
+                }
+                b'}' => {
+                    self.stack.pop();
+                    self.head = self.pos + 1;
+                }
+                b';' => self.head = self.pos + 1,
+                _ => {}
+            }
+            self.pos += 1;
+        }
+    }
+
+    // Applies an access label to the innermost class.
+    fn set_access(&mut self, access: Access) {
+        if let Some(Scope::Class(_, current)) = self.stack.last_mut() {
+            *current = access;
+        }
+    }
+
+    // Returns the access level in effect when the innermost scope is a class.
+    fn access(&self) -> Option<Access> {
+        match self.stack.last() {
+            Some(Scope::Class(_, access)) => Some(*access),
+            _ => None,
+        }
+    }
+
+    // Returns true when more than `MAX_NESTING` scopes are open.
+    fn too_deep(&self) -> bool {
+        self.stack.len() > MAX_NESTING
+    }
+
+    // Returns true inside a function body or other unnamed block.
+    fn in_block(&self) -> bool {
+        matches!(self.stack.last(), Some(Scope::Block))
+    }
+
+    // Returns the linkage of the innermost open `extern "..."` block.
+    fn linkage(&self) -> Linkage {
+        self.stack
+            .iter()
+            .rev()
+            .find_map(|scope| match scope {
+                Scope::Linkage(linkage) => Some(*linkage),
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
+    // Returns the namespaces open at the current position, joined with `::`.
+    fn namespace(&self) -> Option<String> {
+        let names: Vec<&str> = self
+            .stack
+            .iter()
+            .filter_map(|scope| match scope {
+                Scope::Namespace(name) => Some(name.as_str()),
+                Scope::InlineNamespace(name) if self.keep_inline => Some(name.as_str()),
+                _ => None,
+            })
+            .collect();
+        (!names.is_empty()).then(|| names.join("::"))
+    }
+
+    // Returns the classes open at the current position, joined with `::`.
+    fn enclosing_type(&self) -> Option<String> {
+        let names: Vec<&str> = self
+            .stack
+            .iter()
+            .filter_map(|scope| match scope {
+                Scope::Class(name, _) => Some(name.as_str()),
+                _ => None,
+            })
+            .collect();
+        (!names.is_empty()).then(|| names.join("::"))
+    }
+}
+
+/// The functions sharing one name in one scope, as grouped by [`group_overloads`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OverloadSet {
+    /// The enclosing namespaces joined with `::`, or `None` at global scope.
+    pub namespace: Option<String>,
+    /// The enclosing classes joined with `::`, or `None` for free functions.
+    pub enclosing_type: Option<String>,
+    /// The unqualified name shared by the members.
+    pub name: String,
+    /// The distinct overloads, in declaration order.
+    pub members: Vec<FunctionDetail>,
+}
+
+impl OverloadSet {
+    /// Returns true if the set has more than one member.
+    pub fn is_overloaded(&self) -> bool {
+        self.members.len() > 1
+    }
+}
+
+/// Groups functions by namespace, enclosing class and name, in order of first
+/// declaration. A redeclaration of a signature already in its set, such as the same
+/// function declared in two headers, is dropped in favour of the first.
+pub fn group_overloads(details: &[FunctionDetail]) -> Vec<OverloadSet> {
+    let mut sets: Vec<OverloadSet> = Vec::new();
+    let mut index: HashMap<(Option<&str>, Option<&str>, &str), usize> = HashMap::new();
+    for detail in details {
+        let key = (
+            detail.namespace.as_deref(),
+            detail.enclosing_type.as_deref(),
+            detail.name.as_str(),
+        );
+        let set = *index.entry(key).or_insert_with(|| {
+            sets.push(OverloadSet {
+                namespace: detail.namespace.clone(),
+                enclosing_type: detail.enclosing_type.clone(),
+                name: detail.name.clone(),
+                members: Vec::new(),
+            });
+            sets.len() - 1
+        });
+        let members = &mut sets[set].members;
+        if !members.iter().any(|member| member.same_signature(detail)) {
+            members.push(detail.clone());
+        }
+    }
+    sets
+}
+
+/// Include and exclude rules deciding which functions [`filter_details`] keeps, each a
+/// regex searched for in the qualified name (`absl::Cord::Append`), the header path
+/// (with `/` separators) or the namespace of a function. A function is dropped if any
+/// exclude rule matches it. Otherwise, for each of the three kinds with at least one
+/// include rule, one of them must match; a function without a path or namespace matches
+/// no rule of that kind. Templates can be dropped as well with
+/// [`FunctionFilter::skip_templated`]. A filter with no rules keeps everything.
+#[derive(Debug, Clone, Default)]
+pub struct FunctionFilter {
+    include_names: Vec<Regex>,
+    exclude_names: Vec<Regex>,
+    include_paths: Vec<Regex>,
+    exclude_paths: Vec<Regex>,
+    include_namespaces: Vec<Regex>,
+    exclude_namespaces: Vec<Regex>,
+    skip_templated: bool,
+}
+
+impl FunctionFilter {
+    /// A filter with no rules.
+    pub fn new() -> Self {
+        FunctionFilter::default()
+    }
+
+    /// Keeps functions whose qualified name matches `pattern`.
+    pub fn include_name(mut self, pattern: Regex) -> Self {
+        self.include_names.push(pattern);
+        self
+    }
+
+    /// Drops functions whose qualified name matches `pattern`.
+    pub fn exclude_name(mut self, pattern: Regex) -> Self {
+        self.exclude_names.push(pattern);
+        self
+    }
+
+    /// Keeps functions declared in a header whose path matches `pattern`.
+    pub fn include_path(mut self, pattern: Regex) -> Self {
+        self.include_paths.push(pattern);
+        self
+    }
+
+    /// Drops functions declared in a header whose path matches `pattern`.
+    pub fn exclude_path(mut self, pattern: Regex) -> Self {
+        self.exclude_paths.push(pattern);
+        self
+    }
+
+    /// Keeps functions whose namespace matches `pattern`.
+    pub fn include_namespace(mut self, pattern: Regex) -> Self {
+        self.include_namespaces.push(pattern);
+        self
+    }
+
+    /// Drops functions whose namespace matches `pattern`.
+    pub fn exclude_namespace(mut self, pattern: Regex) -> Self {
+        self.exclude_namespaces.push(pattern);
+        self
+    }
+
+    /// Drops functions with their own `template <...>` clause when `skip` is true.
+    pub fn skip_templated(mut self, skip: bool) -> Self {
+        self.skip_templated = skip;
+        self
+    }
+
+    /// The filter given by ASTD_INCLUDE_NAMES, ASTD_EXCLUDE_NAMES, ASTD_INCLUDE_HEADERS,
+    /// ASTD_EXCLUDE_HEADERS, ASTD_INCLUDE_NAMESPACES and ASTD_EXCLUDE_NAMESPACES, each
+    /// holding one regex, skipping templates when ASTD_SKIP_TEMPLATED=1. Panics if one
+    /// is not a valid regex.
+    pub fn from_env() -> Self {
+        let rules = [
+            (
+                "ASTD_INCLUDE_NAMES",
+                FunctionFilter::include_name as fn(Self, Regex) -> Self,
+            ),
+            ("ASTD_EXCLUDE_NAMES", FunctionFilter::exclude_name),
+            ("ASTD_INCLUDE_HEADERS", FunctionFilter::include_path),
+            ("ASTD_EXCLUDE_HEADERS", FunctionFilter::exclude_path),
+            ("ASTD_INCLUDE_NAMESPACES", FunctionFilter::include_namespace),
+            ("ASTD_EXCLUDE_NAMESPACES", FunctionFilter::exclude_namespace),
+        ];
+        let mut filter = FunctionFilter::new();
+        for (var, add) in rules {
+            println!("cargo:rerun-if-env-changed={}", var);
+            let Ok(pattern) = env::var(var) else {
+                continue;
+            };
+            if pattern.trim().is_empty() {
+                continue;
+            }
+            let regex = Regex::new(&pattern)
+                .unwrap_or_else(|err| panic!("{} is not a valid regex: {}", var, err));
+            filter = add(filter, regex);
+        }
+        println!("cargo:rerun-if-env-changed=ASTD_SKIP_TEMPLATED");
+        filter.skip_templated(env::var("ASTD_SKIP_TEMPLATED").is_ok_and(|value| value == "1"))
+    }
+
+    /// Returns true if the filter keeps `detail`.
+    pub fn matches(&self, detail: &FunctionDetail) -> bool {
+        if self.skip_templated && detail.is_templated() {
+            return false;
+        }
+        let name = qualified_name(detail);
+        let path = detail
+            .path
+            .as_ref()
+            .map(|path| path.to_string_lossy().replace("\\", "/"));
+        let fields = [
+            (
+                &self.include_names,
+                &self.exclude_names,
+                Some(name.as_str()),
+            ),
+            (&self.include_paths, &self.exclude_paths, path.as_deref()),
+            (
+                &self.include_namespaces,
+                &self.exclude_namespaces,
+                detail.namespace.as_deref(),
+            ),
+        ];
+        let matching = |rules: &[Regex], text: Option<&str>| {
+            text.is_some_and(|text| rules.iter().any(|rule| rule.is_match(text)))
+        };
+        fields
+            .iter()
+            .all(|(_, exclude, text)| !matching(exclude, *text))
+            && fields
+                .iter()
+                .all(|(include, _, text)| include.is_empty() || matching(include, *text))
+    }
+}
+
+/// Keeps the functions in `details` that `filter` matches, in order.
+pub fn filter_details(
+    mut details: Vec<FunctionDetail>,
+    filter: &FunctionFilter,
+) -> Vec<FunctionDetail> {
+    details.retain(|detail| filter.matches(detail));
+    details
+}
+
+/// Extracts the classes, structs and unions defined in C++ source, each with its public
+/// member functions. Forward declarations are ignored.
+pub fn extract_class_details(src: &str) -> Vec<ClassDetail> {
+    let functions = extract_function_details(src);
+    let src = &*normalize_source(src);
+    let (code, mask) = prepare_source(src);
+    let (code, mask, _) = strip_attribute_macros(&code, &mask, ATTRIBUTE_MACROS);
+    let (code, mask, inline) = expand_namespace_macros(&code, &mask, NAMESPACE_MACROS);
+    let mut scopes = ScopeTracker::new(&code, &mask).with_inline_namespaces(inline, false);
+    let lines = LineIndex::new(src);
+    let mut classes = Vec::new();
+    for statement in statements(&mask) {
+        scopes.advance_to(statement.start);
+        if mask.as_bytes().get(statement.end) != Some(&b'{')
+            || scopes.in_block()
+            || scopes.too_deep()
+        {
+            continue;
+        }
+        let mut head_start = statement.start;
+        if let Some(label) = ACCESS_LABEL_REGEX.find(&mask[statement.clone()]) {
+            head_start += label.end();
+        }
+        let text = &mask[head_start..statement.end];
+        let head_start = head_start + text.len() - text.trim_start().len();
+        let Some(cap) = CLASS_HEAD_REGEX.captures(text) else {
+            continue;
+        };
+        let namespace = scopes.namespace();
+        let enclosing_type = scopes.enclosing_type();
+        let name = cap[3].to_owned();
+        let owner = match &enclosing_type {
+            Some(outer) => format!("{}::{}", outer, name),
+            None => name.clone(),
+        };
+        let members: Vec<&FunctionDetail> = functions
+            .iter()
+            .filter(|f| {
+                f.namespace == namespace
+                    && f.enclosing_type.as_deref() == Some(&owner)
+                    && !f.is_friend()
+            })
+            .collect();
+        let (is_copyable, is_movable) = copy_and_move(&name, &members);
+        let bases = cap.get(5).map_or(Vec::new(), |clause| {
+            split_top_level(clause.as_str())
+                .into_iter()
+                .map(|range| {
+                    let words: Vec<&str> = clause.as_str()[range]
+                        .split_whitespace()
+                        .filter(|word| {
+                            !matches!(*word, "public" | "protected" | "private" | "virtual")
+                        })
+                        .collect();
+                    normalize_type(&words.join(" "))
+                })
+                .filter(|base| !base.is_empty())
+                .collect()
+        });
+        classes.push(ClassDetail {
+            template_prefix: collapse_whitespace(cap.get(1).map_or("", |m| m.as_str())),
+            kind: ClassKind::from_keyword(&cap[2]),
+            name,
+            namespace,
+            enclosing_type,
+            bases,
+            is_final: cap.get(4).is_some(),
+            is_copyable,
+            is_movable,
+            methods: members
+                .into_iter()
+                .filter(|f| f.access == Some(Access::Public))
+                .cloned()
+                .collect(),
+            line: lines.line(head_start),
+        });
+    }
+    classes
+}
+
+/// Extracts the enumerations defined in C++ source. Opaque declarations such as
+/// `enum class E : int;` are ignored.
+pub fn extract_enum_details(src: &str) -> Vec<EnumDetail> {
+    extract_enum_details_checked(src).0
+}
+
+/// Like [`extract_enum_details`], but also reports the enumerations whose body is never
+/// closed, which are skipped.
+pub fn extract_enum_details_checked(src: &str) -> (Vec<EnumDetail>, Vec<ParseDiagnostic>) {
+    let src = &*normalize_source(src);
+    let (code, mask) = prepare_source(src);
+    let (code, mask, _) = strip_attribute_macros(&code, &mask, ATTRIBUTE_MACROS);
+    let (code, mask, inline) = expand_namespace_macros(&code, &mask, NAMESPACE_MACROS);
+    let mut scopes = ScopeTracker::new(&code, &mask).with_inline_namespaces(inline, false);
+    let lines = LineIndex::new(src);
+    let mut enums = Vec::new();
+    let mut diagnostics = Vec::new();
+    for statement in statements(&mask) {
+        scopes.advance_to(statement.start);
+        if mask.as_bytes().get(statement.end) != Some(&b'{')
+            || scopes.in_block()
+            || scopes.too_deep()
+        {
+            continue;
+        }
+        let mut head_start = statement.start;
+        if let Some(label) = ACCESS_LABEL_REGEX.find(&mask[statement.clone()]) {
+            head_start += label.end();
+        }
+        let text = &mask[head_start..statement.end];
+        let Some(cap) = ENUM_HEAD_REGEX.captures(text) else {
+            continue;
+        };
+        let head_start = head_start + text.len() - text.trim_start().len();
+        let Some(close) = block_close(&mask, statement.end + 1) else {
+            diagnostics.push(parse_diagnostic(
+                src,
+                &lines,
+                head_start,
+                &mask[head_start..statement.end],
+                "unclosed enum body",
+            ));
+            continue;
+        };
+        let body = statement.end + 1..close;
+        let enumerators = split_expression_list(&mask[body.clone()])
+            .into_iter()
+            .filter_map(|part| {
+                let part = trimmed(&mask[body.clone()], part);
+                let text = &code[body.start + part.start..body.start + part.end];
+                let (name, value) = match text.split_once('=') {
+                    Some((name, value)) => (name.trim(), Some(collapse_whitespace(value))),
+                    None => (text, None),
+                };
+                is_identifier(name).then(|| Enumerator {
+                    name: name.to_owned(),
+                    value,
+                })
+            })
+            .collect();
+        enums.push(EnumDetail {
+            name: cap.get(2).map(|m| m.as_str().to_owned()),
+            is_scoped: cap.get(1).is_some(),
+            underlying_type: cap.get(3).map(|m| normalize_type(m.as_str())),
+            enumerators,
+            namespace: scopes.namespace(),
+            enclosing_type: scopes.enclosing_type(),
+            line: lines.line(head_start),
+        });
+    }
+    (enums, diagnostics)
+}
+
+/// Another name for [`extract_enum_details`].
+pub fn extract_enums(src: &str) -> Vec<EnumDef> {
+    extract_enum_details(src)
+}
+
+/// Extracts the `const` and `constexpr` variables declared at namespace or class scope.
+/// A declarator with a parameter list is a function, not a variable, so
+/// `constexpr Duration InfiniteDuration();` is ignored.
+pub fn extract_constants(src: &str) -> Vec<ConstantDetail> {
+    let src = &*normalize_source(src);
+    let (code, mask) = prepare_source(src);
+    let (code, mask, _) = strip_attribute_macros(&code, &mask, ATTRIBUTE_MACROS);
+    let (code, mask, inline) = expand_namespace_macros(&code, &mask, NAMESPACE_MACROS);
+    let mut scopes = ScopeTracker::new(&code, &mask).with_inline_namespaces(inline, false);
+    let lines = LineIndex::new(src);
+    let mut constants = Vec::new();
+    for statement in statements(&mask) {
+        scopes.advance_to(statement.start);
+        if scopes.in_block() || scopes.too_deep() {
+            continue;
+        }
+        let mut head_start = statement.start;
+        if let Some(label) = ACCESS_LABEL_REGEX.find(&mask[statement.clone()]) {
+            head_start += label.end();
+        }
+        let text = &code[head_start..statement.end];
+        let head_start = head_start + text.len() - text.trim_start().len();
+        let head = &code[head_start..statement.end];
+        // A braced initializer opens a block; the declaration ends at the `;` after it.
+        let end = match mask.as_bytes().get(statement.end) {
+            Some(b';') => statement.end,
+            Some(b'{') => {
+                let close = block_end(&mask, statement.end + 1);
+                match mask[close..].find(';') {
+                    Some(semi) if mask[close..close + semi].trim().is_empty() => close + semi,
+                    _ => continue,
+                }
+            }
+            _ => continue,
+        };
+        let (declarator, initializer) = match head.find('=') {
+            Some(eq) => (&head[..eq], Some(&code[head_start + eq + 1..end])),
+            None if end > statement.end => (head, Some(&code[statement.end..end])),
+            None => (head, None),
+        };
+        let Some((specifiers, type_name, name)) = constant_declarator(declarator) else {
+            continue;
+        };
+        constants.push(ConstantDetail {
+            specifiers,
+            type_name,
+            name,
+            initializer: initializer.map(collapse_whitespace),
+            namespace: scopes.namespace(),
+            enclosing_type: scopes.enclosing_type(),
+            line: lines.line(head_start),
+        });
+    }
+    constants
+}
+
+// Splits the declarator of a constant into its storage specifiers, type and name.
+// Returns `None` unless it declares one `const` or `constexpr` variable.
+fn constant_declarator(text: &str) -> Option<(Vec<String>, String, String)> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    if text.contains(['(', ')', '{', '}', ';'])
+        || split_top_level(text).len() != 1
+        || words
+            .first()
+            .is_none_or(|word| NON_CONSTANT_KEYWORDS.contains(word))
+    {
+        return None;
+    }
+    let cap = TYPEDEF_NAME_REGEX.captures(text.trim_end())?;
+    let name = cap.get(1)?;
+    let (specifiers, type_words): (Vec<&str>, Vec<&str>) = text[..name.start()]
+        .split_whitespace()
+        .partition(|word| STORAGE_SPECIFIERS.contains(word));
+    let is_const =
+        specifiers.contains(&"constexpr") || TYPE_CONST_REGEX.is_match(&text[..name.start()]);
+    let type_name =
+        normalize_type(&format!("{}{}", type_words.join(" "), &cap[2])).replace(" [", "[");
+    (is_const && !type_name.is_empty() && is_identifier(name.as_str())).then(|| {
+        (
+            specifiers.into_iter().map(str::to_owned).collect(),
+            type_name,
+            name.as_str().to_owned(),
+        )
+    })
+}
+
+/// Extracts the `typedef` and `using` type aliases declared in C++ source. Using
+/// declarations and directives such as `using std::swap;` are not aliases and are
+/// ignored, as are typedefs declaring more than one name.
+pub fn extract_type_aliases(src: &str) -> Vec<TypeAlias> {
+    let src = &*normalize_source(src);
+    let (code, mask) = prepare_source(src);
+    let (code, mask, _) = strip_attribute_macros(&code, &mask, ATTRIBUTE_MACROS);
+    let (code, mask, inline) = expand_namespace_macros(&code, &mask, NAMESPACE_MACROS);
+    let mut scopes = ScopeTracker::new(&code, &mask).with_inline_namespaces(inline, false);
+    let lines = LineIndex::new(src);
+    let mut aliases = Vec::new();
+    for statement in statements(&mask) {
+        scopes.advance_to(statement.start);
+        if mask.as_bytes().get(statement.end) != Some(&b';')
+            || scopes.in_block()
+            || scopes.too_deep()
+        {
+            continue;
+        }
+        let mut head_start = statement.start;
+        if let Some(label) = ACCESS_LABEL_REGEX.find(&mask[statement.clone()]) {
+            head_start += label.end();
+        }
+        let text = &code[head_start..statement.end];
+        let head_start = head_start + text.len() - text.trim_start().len();
+        let alias = if let Some(cap) = USING_ALIAS_REGEX.captures(text) {
+            Some((
+                cap.get(1).map_or("", |m| m.as_str().trim()),
+                cap[2].to_owned(),
+                normalize_type(&cap[3]),
+            ))
+        } else {
+            TYPEDEF_REGEX
+                .captures(text)
+                .and_then(|cap| typedef_declarator(cap.get(1)?.as_str()))
+                .map(|(name, aliased_type)| ("", name, aliased_type))
+        };
+        let Some((template_prefix, name, aliased_type)) = alias else {
+            continue;
+        };
+        aliases.push(TypeAlias {
+            template_prefix: collapse_whitespace(template_prefix),
+            name,
+            aliased_type,
+            namespace: scopes.namespace(),
+            enclosing_type: scopes.enclosing_type(),
+            line: lines.line(head_start),
+        });
+    }
+    aliases
+}
+
+// Splits the text after `typedef` into the declared name and the aliased type. A
+// pointer declarator keeps its parentheses, so `void (*Fn)(int)` aliases
+// `void (*)(int)`; a function type such as `void Fn(int)` aliases `void(int)`.
+fn typedef_declarator(text: &str) -> Option<(String, String)> {
+    if split_top_level(text).len() != 1 || text.contains('{') {
+        return None;
+    }
+    let (name, aliased) = if let Some(cap) = TYPEDEF_DECLARATOR_REGEX.captures(text) {
+        let name = cap.get(1)?;
+        (
+            name.as_str(),
+            format!("{}{}", &text[..name.start()], &text[name.end()..]),
+        )
+    } else if let Some(open) = text.find('(') {
+        let cap = TYPEDEF_NAME_REGEX.captures(text[..open].trim_end())?;
+        let name = cap.get(1)?;
+        (
+            name.as_str(),
+            format!("{}{}", &text[..name.start()].trim_end(), &text[open..]),
+        )
+    } else {
+        let cap = TYPEDEF_NAME_REGEX.captures(text)?;
+        let name = cap.get(1)?;
+        (
+            name.as_str(),
+            format!("{}{}", &text[..name.start()], &cap[2]),
+        )
+    };
+    let aliased = normalize_type(&aliased).replace(" [", "[");
+    (is_identifier(name) && !aliased.is_empty()).then(|| (name.to_owned(), aliased))
+}
+
+// Splits an expression list on the commas that are not nested in (), [] or {}. Unlike
+// `split_top_level`, angle brackets are not tracked, since `<` may be a shift or a
+// comparison here.
+fn split_expression_list(list: &str) -> Vec<Range<usize>> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in list.char_indices() {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                parts.push(start..i);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(start..list.len());
+    parts
+}
+
+// Works out whether a class is copyable and movable from its declared special members.
+fn copy_and_move(class_name: &str, members: &[&FunctionDetail]) -> (bool, bool) {
+    let takes = |f: &FunctionDetail, suffix: &str| {
+        f.parameters.len() == 1
+            && f.parameters[0]
+                .type_name
+                .strip_suffix(suffix)
+                .is_some_and(|base| base.trim_start_matches("const ").trim() == class_name)
+    };
+    let special = |f: &FunctionDetail| {
+        f.kind == FunctionKind::Constructor
+            || (f.kind == FunctionKind::Operator && f.name == "operator=")
+    };
+    let unusable = |f: &FunctionDetail| {
+        f.special_definition == Some(SpecialDefinition::Delete) || f.access != Some(Access::Public)
+    };
+    let copies: Vec<_> = members
+        .iter()
+        .filter(|f| special(f) && takes(f, "&") && !takes(f, "&&"))
+        .collect();
+    let moves: Vec<_> = members
+        .iter()
+        .filter(|f| special(f) && takes(f, "&&"))
+        .collect();
+    let copy_blocked = copies.iter().any(|f| unusable(f));
+    let copy_ctor_declared = copies.iter().any(|f| f.kind == FunctionKind::Constructor);
+    let is_copyable = !copy_blocked && (moves.is_empty() || copy_ctor_declared);
+    let move_blocked = moves.iter().any(|f| unusable(f));
+    let is_movable = !move_blocked && (!moves.is_empty() || is_copyable);
+    (is_copyable, is_movable)
+}
+
+// Extracts function details in the old tuple shape: (template, return type, name).
+#[deprecated(note = "use `extract_function_details`, which returns `FunctionDetail`")]
+pub fn extract_function_details_tuples(src: &str) -> Vec<(String, String, String)> {
+    extract_function_details(src)
+        .into_iter()
+        .map(|detail| (detail.template_prefix, detail.return_type, detail.name))
+        .collect()
+}
+
+// Blanks each attribute-like macro named in `macros`, with its parenthesized arguments,
+// and each `[[...]]` attribute sequence in both views. Returns the new views and the
+// offset and spelling of each macro and of each attribute in a sequence.
+fn strip_attribute_macros(
+    code: &str,
+    mask: &str,
+    macros: &[&str],
+) -> (String, String, Vec<(usize, String)>) {
+    let mut code_bytes = code.as_bytes().to_vec();
+    let mut mask_bytes = mask.as_bytes().to_vec();
+    let mut found = Vec::new();
+    let bytes = mask.as_bytes();
+    let is_word = |b: u8| b.is_ascii_alphanumeric() || b == b'_';
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i..].starts_with(b"[[") {
+            let close = attribute_sequence_close(mask, i + 2);
+            let inner = i + 2..close.unwrap_or(bytes.len());
+            let end = close.map_or(bytes.len(), |close| close + 2);
+            for part in split_top_level(&mask[inner.clone()]) {
+                let part = trimmed(&mask[inner.clone()], part);
+                let text = &code[inner.start + part.start..inner.start + part.end];
+                if !text.is_empty() {
+                    found.push((i, collapse_whitespace(text)));
+                }
+            }
+            blank(&mut code_bytes, i..end);
+            blank(&mut mask_bytes, i..end);
+            i = end;
+            continue;
+        }
+        if !is_word(bytes[i]) || (i > 0 && is_word(bytes[i - 1])) {
+            i += 1;
+            continue;
+        }
+        let mut end = i;
+        while end < bytes.len() && is_word(bytes[end]) {
+            end += 1;
+        }
+        if macros.contains(&&mask[i..end]) {
+            let after = end + mask[end..].len() - mask[end..].trim_start().len();
+            if bytes.get(after) == Some(&b'(') {
+                end = (param_list(mask, after + 1).end + 1).min(bytes.len());
+            }
+            found.push((i, collapse_whitespace(&code[i..end])));
+            blank(&mut code_bytes, i..end);
+            blank(&mut mask_bytes, i..end);
+        }
+        i = end;
+    }
+    (
+        String::from_utf8_lossy(&code_bytes).into_owned(),
+        String::from_utf8_lossy(&mask_bytes).into_owned(),
+        found,
+    )
+}
+
+// Replaces each namespace macro with the brace it stands for, so `ABSL_NAMESPACE_BEGIN`
+// opens a scope like `inline namespace lts_20250127 {` and `ABSL_NAMESPACE_END` closes it.
+// Also returns the position of each opening brace with the namespace name, for
+// `ScopeTracker::with_inline_namespaces`.
+fn expand_namespace_macros(
+    code: &str,
+    mask: &str,
+    macros: &[NamespaceMacro],
+) -> (String, String, Vec<(usize, String)>) {
+    let mut code_bytes = code.as_bytes().to_vec();
+    let mut mask_bytes = mask.as_bytes().to_vec();
+    let mut opened = Vec::new();
+    for word in WORD_REGEX.find_iter(mask) {
+        let brace = if let Some(found) = macros.iter().find(|m| m.begin == word.as_str()) {
+            opened.push((word.start(), found.name.to_owned()));
+            b'{'
+        } else if macros.iter().any(|m| m.end == word.as_str()) {
+            b'}'
+        } else {
+            continue;
+        };
+        for bytes in [&mut code_bytes, &mut mask_bytes] {
+            blank(bytes, word.range());
+            bytes[word.start()] = brace;
+        }
+    }
+    (
+        String::from_utf8_lossy(&code_bytes).into_owned(),
+        String::from_utf8_lossy(&mask_bytes).into_owned(),
+        opened,
+    )
+}
+
+// Returns the index of the `]]` closing an attribute sequence whose contents start at
+// `start`, or `None` if it is never closed.
+fn attribute_sequence_close(mask: &str, start: usize) -> Option<usize> {
+    let bytes = mask.as_bytes();
+    let mut depth = 0usize;
+    let mut i = start;
+    while i < bytes.len() {
+        match bytes[i] {
+            b']' if depth == 0 && bytes.get(i + 1) == Some(&b']') => return Some(i),
+            b'(' | b'[' | b'{' => depth += 1,
+            b')' | b']' | b'}' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+// Returns two views of `src` with comments and preprocessor directives blanked: the
+// first keeps string and char literals (for default arguments), the second blanks
+// their contents too and is what the parser matches against.
+fn prepare_source(src: &str) -> (String, String) {
+    let (code, mask, _) = prepare_source_with_defines(src, None);
+    (code, mask)
+}
+
+// Like `prepare_source`, but with `defines` also blanks the conditional groups they turn
+// off, and returns the directives whose conditions could not be evaluated.
+fn prepare_source_with_defines(
+    src: &str,
+    defines: Option<&HashMap<String, Option<String>>>,
+) -> (String, String, Vec<Range<usize>>) {
+    let src = &*normalize_source(src);
+    let tokens = tokenize(src);
+    let mut code = blank_tokens(src, &tokens, false).into_bytes();
+    let mut mask = blank_tokens(src, &tokens, true).into_bytes();
+    let directive_mask = String::from_utf8_lossy(&mask).into_owned();
+    let mut ranges = preprocessor_ranges(&directive_mask);
+    let mut unknown = Vec::new();
+    if let Some(defines) = defines {
+        let (inactive, unevaluated) = inactive_ranges(&directive_mask, defines);
+        ranges.extend(inactive);
+        unknown = unevaluated;
+    }
+    for range in ranges {
+        blank(&mut code, range.clone());
+        blank(&mut mask, range);
+    }
+    (
+        String::from_utf8_lossy(&code).into_owned(),
+        String::from_utf8_lossy(&mask).into_owned(),
+        unknown,
+    )
+}
+
+/// Blanks every preprocessor directive in `src`, including backslash-continued
+/// lines, along with the body of each `#if 0` group. Other conditional groups are
+/// kept. Directives become spaces rather than being removed, so line numbers and
+/// byte offsets in the result match `src`.
+pub fn strip_preprocessor(src: &str) -> String {
+    let mut out = src.as_bytes().to_vec();
+    for range in preprocessor_ranges(&blank_comments_and_literals(src, true)) {
+        blank(&mut out, range);
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+// Finds the byte ranges of preprocessor directives and `#if 0` groups in text whose
+// comments and literals are already blanked.
+fn preprocessor_ranges(mask: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    // Start of an `#if 0` group being skipped, and how many `#if`s are open inside it.
+    let mut skipping: Option<(usize, usize)> = None;
+    for directive in directives(mask) {
+        let Directive {
+            line,
+            name,
+            condition,
+            ..
+        } = directive;
+        match (name, skipping.as_mut()) {
+            ("if" | "ifdef" | "ifndef", Some((_, depth))) => *depth += 1,
+            ("endif", Some((_, depth))) if *depth > 0 => *depth -= 1,
+            ("else" | "elif" | "endif", Some((start, 0))) => {
+                ranges.push(*start..line.end);
+                skipping = None;
+            }
+            (_, Some(_)) => {}
+            ("if", None) if matches!(condition.trim(), "0" | "false") => {
+                skipping = Some((line.start, 0));
+            }
+            (_, None) => ranges.push(line),
+        }
+    }
+    if let Some((start, _)) = skipping {
+        ranges.push(start..mask.len());
+    }
+    ranges
+}
+
+// A preprocessor directive line, such as `#ifdef _WIN32`.
+struct Directive<'a> {
+    // The whole line, without its newline, including any backslash-continued lines.
+    line: Range<usize>,
+    // Where the `#` is.
+    hash: usize,
+    // The directive name, such as `ifdef`; empty for a null directive.
+    name: &'a str,
+    // The rest of the line after the name.
+    condition: &'a str,
+}
+
+// Lists the directives in text whose comments and literals are already blanked.
+fn directives(mask: &str) -> Vec<Directive<'_>> {
+    let bytes = mask.as_bytes();
+    let mut directives = Vec::new();
+    let mut line_start = 0;
+    while line_start < bytes.len() {
+        let mut i = line_start;
+        while i < bytes.len() && matches!(bytes[i], b' ' | b'\t' | b'\r') {
+            i += 1;
+        }
+        // A directive runs to the first newline not escaped by a backslash.
+        let mut end = i;
+        while end < bytes.len() && (bytes[end] != b'\n' || continues_line(bytes, end)) {
+            end += 1;
+        }
+        if bytes.get(i) == Some(&b'#') {
+            let directive = mask[i + 1..end].trim_start();
+            let name_len = directive
+                .find(|c: char| !c.is_ascii_alphabetic())
+                .unwrap_or(directive.len());
+            let (name, condition) = directive.split_at(name_len);
+            directives.push(Directive {
+                line: line_start..end,
+                hash: i,
+                name,
+                condition,
+            });
+        }
+        line_start = end + 1;
+    }
+    directives
+}
+
+// Finds the conditional groups in `mask` that `defines` turn off, and the directives
+// whose conditions could not be evaluated. An unevaluated condition keeps its group
+// without settling the chain, so a later `#elif` or `#else` is still considered.
+fn inactive_ranges(
+    mask: &str,
+    defines: &HashMap<String, Option<String>>,
+) -> (Vec<Range<usize>>, Vec<Range<usize>>) {
+    // One `#if` chain: whether its enclosing group is kept, whether one of its
+    // conditions has held, and whether the current group is kept.
+    struct Chain {
+        outer: bool,
+        taken: bool,
+        active: bool,
+    }
+    let mut chains: Vec<Chain> = Vec::new();
+    let mut ranges = Vec::new();
+    let mut unknown = Vec::new();
+    // Where the group being skipped starts.
+    let mut skip_start = None;
+    for directive in directives(mask) {
+        let was_active = chains.last().is_none_or(|chain| chain.active);
+        let mut test = |outer: bool| {
+            let value = if outer {
+                evaluate_directive(directive.name, directive.condition, defines)
+            } else {
+                Some(false)
+            };
+            if value.is_none() {
+                unknown.push(directive.hash..directive.line.end);
+            }
+            value
+        };
+        match directive.name {
+            "if" | "ifdef" | "ifndef" => {
+                let value = test(was_active);
+                chains.push(Chain {
+                    outer: was_active,
+                    taken: value == Some(true),
+                    active: value != Some(false),
+                });
+            }
+            "elif" => {
+                if let Some(chain) = chains.last_mut() {
+                    let value = test(chain.outer && !chain.taken);
+                    chain.active = value != Some(false);
+                    chain.taken |= value == Some(true);
+                }
+            }
+            "else" => {
+                if let Some(chain) = chains.last_mut() {
+                    chain.active = chain.outer && !chain.taken;
+                    chain.taken = true;
+                }
+            }
+            "endif" => {
+                chains.pop();
+            }
+            _ => {}
+        }
+        let is_active = chains.last().is_none_or(|chain| chain.active);
+        if was_active && !is_active {
+            skip_start = Some(directive.line.end);
+        } else if !was_active && is_active {
+            ranges.extend(skip_start.take().map(|start| start..directive.line.start));
+        }
+    }
+    ranges.extend(skip_start.map(|start| start..mask.len()));
+    (ranges, unknown)
+}
+
+// Evaluates the condition of an `#if`, `#ifdef`, `#ifndef` or `#elif`, or returns
+// `None` if it uses something the evaluator does not support.
+fn evaluate_directive(
+    name: &str,
+    condition: &str,
+    defines: &HashMap<String, Option<String>>,
+) -> Option<bool> {
+    match name {
+        "ifdef" | "ifndef" => {
+            let macro_name = condition.trim();
+            is_identifier(macro_name).then(|| defines.contains_key(macro_name) == (name == "ifdef"))
+        }
+        _ => ConditionParser::evaluate(condition, defines, 0).map(|value| value != 0),
+    }
+}
+
+// A recursive-descent evaluator for `#if` expressions over integers.
+struct ConditionParser<'a> {
+    tokens: Vec<&'a str>,
+    pos: usize,
+    defines: &'a HashMap<String, Option<String>>,
+    // How many macro replacements are being evaluated, to stop runaway recursion.
+    depth: usize,
+}
+
+impl<'a> ConditionParser<'a> {
+    fn evaluate(
+        text: &'a str,
+        defines: &'a HashMap<String, Option<String>>,
+        depth: usize,
+    ) -> Option<i64> {
+        if depth > 32 {
+            return None;
+        }
+        let mut parser = ConditionParser {
+            tokens: condition_tokens(text)?,
+            pos: 0,
+            defines,
+            depth,
+        };
+        let value = parser.or()?;
+        (parser.pos == parser.tokens.len()).then_some(value)
+    }
+
+    fn peek(&self) -> Option<&'a str> {
+        self.tokens.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        let found = self.peek() == Some(token);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn or(&mut self) -> Option<i64> {
+        let mut value = self.and()?;
+        while self.eat("||") {
+            let rhs = self.and()?;
+            value = i64::from(value != 0 || rhs != 0);
+        }
+        Some(value)
+    }
+
+    fn and(&mut self) -> Option<i64> {
+        let mut value = self.comparison()?;
+        while self.eat("&&") {
+            let rhs = self.comparison()?;
+            value = i64::from(value != 0 && rhs != 0);
+        }
+        Some(value)
+    }
+
+    fn comparison(&mut self) -> Option<i64> {
+        let mut value = self.unary()?;
+        while let Some(op @ ("==" | "!=" | "<" | "<=" | ">" | ">=")) = self.peek() {
+            self.pos += 1;
+            let rhs = self.unary()?;
+            value = i64::from(match op {
+                "==" => value == rhs,
+                "!=" => value != rhs,
+                "<" => value < rhs,
+                "<=" => value <= rhs,
+                ">" => value > rhs,
+                _ => value >= rhs,
+            });
+        }
+        Some(value)
+    }
+
+    fn unary(&mut self) -> Option<i64> {
+        if self.eat("!") {
+            Some(i64::from(self.unary()? == 0))
+        } else if self.eat("-") {
+            self.unary()?.checked_neg()
+        } else {
+            self.primary()
+        }
+    }
+
+    fn primary(&mut self) -> Option<i64> {
+        let token = self.peek()?;
+        self.pos += 1;
+        match token {
+            "(" => {
+                let value = self.or()?;
+                self.eat(")").then_some(value)
+            }
+            "defined" => {
+                let parenthesized = self.eat("(");
+                let name = self.peek().filter(|name| is_identifier(name))?;
+                self.pos += 1;
+                if parenthesized && !self.eat(")") {
+                    return None;
+                }
+                Some(i64::from(self.defines.contains_key(name)))
+            }
+            _ if token.starts_with(|c: char| c.is_ascii_digit()) => parse_integer_literal(token),
+            // A function-like macro call cannot be evaluated.
+            _ if self.peek() == Some("(") => None,
+            _ => match self.defines.get(token) {
+                Some(Some(value)) => ConditionParser::evaluate(value, self.defines, self.depth + 1),
+                Some(None) => Some(1),
+                None => Some(i64::from(token == "true")),
+            },
+        }
+    }
+}
+
+// Splits an `#if` expression into words, numbers and operators, or returns `None` if it
+// has a character the evaluator does not know.
+fn condition_tokens(text: &str) -> Option<Vec<&str>> {
+    let bytes = text.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i];
+        let len = if c.is_ascii_whitespace() || c == b'\\' {
+            i += 1;
+            continue;
+        } else if c.is_ascii_alphanumeric() || c == b'_' {
+            bytes[i..]
+                .iter()
+                .take_while(|b| b.is_ascii_alphanumeric() || **b == b'_')
+                .count()
+        } else if matches!(
+            &bytes[i..(i + 2).min(bytes.len())],
+            b"&&" | b"||" | b"==" | b"!=" | b"<=" | b">="
+        ) {
+            2
+        } else if matches!(c, b'!' | b'<' | b'>' | b'(' | b')' | b'-') {
+            1
+        } else {
+            return None;
+        };
+        tokens.push(&text[i..i + len]);
+        i += len;
+    }
+    Some(tokens)
+}
+
+// Parses a decimal, hex or octal integer literal such as `201703L` or `0x10`, ignoring
+// `u` and `l` suffixes.
+fn parse_integer_literal(token: &str) -> Option<i64> {
+    let digits = token.trim_end_matches(['u', 'U', 'l', 'L']);
+    if let Some(hex) = digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        i64::from_str_radix(hex, 16).ok()
+    } else if digits.len() > 1 && digits.starts_with('0') {
+        i64::from_str_radix(&digits[1..], 8).ok()
+    } else {
+        digits.parse().ok()
+    }
+}
+
+/// Strips a leading UTF-8 byte order mark and turns `\r\n` and lone `\r` line endings
+/// into `\n`, so sources checked out on Windows parse like any other. Every parser
+/// runs its input through this first; line numbers and columns refer to the result,
+/// while [`FunctionDetail::byte_span`] is mapped back to the input.
+pub fn normalize_source(src: &str) -> Cow<'_, str> {
+    let src = src.strip_prefix('\u{feff}').unwrap_or(src);
+    if src.contains('\r') {
+        Cow::Owned(src.replace("\r\n", "\n").replace('\r', "\n"))
+    } else {
+        Cow::Borrowed(src)
+    }
+}
+
+// Maps offsets in the output of `normalize_source` back to the source it was given.
+struct SourceMap {
+    // The length of the byte order mark stripped from the start, if any.
+    bom: usize,
+    // The offsets in the output of the `\n`s that were `\r\n` in the source, in order.
+    crlf: Vec<usize>,
+}
+
+impl SourceMap {
+    fn new(src: &str) -> Self {
+        let bom = if src.starts_with('\u{feff}') {
+            '\u{feff}'.len_utf8()
+        } else {
+            0
+        };
+        let crlf = src[bom..]
+            .match_indices("\r\n")
+            .enumerate()
+            .map(|(removed, (i, _))| i - removed)
+            .collect();
+        SourceMap { bom, crlf }
+    }
+
+    // Maps `span` of the normalized source to the same text in the source. A span
+    // ending at a line break leaves out the whole `\r\n`.
+    fn span(&self, span: Range<usize>) -> Range<usize> {
+        let start = self.crlf.partition_point(|&i| i <= span.start);
+        let end = self.crlf.partition_point(|&i| i < span.end);
+        self.bom + span.start + start..self.bom + span.end + end
+    }
+}
+
+// Reads a header as lossy UTF-8, leaving its line endings for the parsers to normalize.
+fn read_source(path: &Path) -> Result<String> {
+    Ok(String::from_utf8_lossy(&fs::read(path)?).into_owned())
+}
+
+/// The kind of a [`Token`] found by [`tokenize`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    /// An identifier or keyword, such as `absl` or `const`.
+    Identifier,
+    /// A numeric literal with its digit separators and suffix, such as `1'000u`.
+    Number,
+    /// A single punctuation character; `::` is two `:` tokens.
+    Punct,
+    /// A string literal from its opening quote, raw strings included. An encoding
+    /// prefix such as `u8` or `R` is the identifier before it.
+    String,
+    /// A character literal.
+    Char,
+    /// A `//` or `/* */` comment.
+    Comment,
+    /// A preprocessor directive, from its `#` to the end of its line including any
+    /// backslash-continued lines. Only the comments and literals inside it are
+    /// tokenized, and they follow it as tokens of their own.
+    Directive,
+}
+
+/// A token of C++ source, as found by [`tokenize`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Token {
+    /// What the token is.
+    pub kind: TokenKind,
+    /// Where the token is in the source, in bytes.
+    pub span: Range<usize>,
+}
+
+/// Splits C++ source into tokens in a single pass, skipping whitespace. An unterminated
+/// comment or literal runs to the end of the input, or for a literal to the end of its
+/// line, so every input tokenizes.
+pub fn tokenize(src: &str) -> Vec<Token> {
+    let bytes = src.as_bytes();
+    let is_word = |b: u8| b.is_ascii_alphanumeric() || b == b'_' || b >= 0x80;
+    let mut tokens: Vec<Token> = Vec::new();
+    // The open directive's token, if any; it ends at the next unescaped newline.
+    let mut directive: Option<usize> = None;
+    // Only whitespace and comments precede `i` on its line.
+    let mut line_start = true;
+    let mut i = 0;
+    while i < bytes.len() {
+        let start = i;
+        let kind = match bytes[i] {
+            b'\n' => {
+                if let Some(open) = directive.filter(|_| !continues_line(bytes, i)) {
+                    tokens[open].span.end = i;
+                    directive = None;
+                }
+                line_start = true;
+                i += 1;
+                continue;
+            }
+            b' ' | b'\t' | b'\r' | b'\x0b' | b'\x0c' => {
+                i += 1;
+                continue;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                // A backslash before the newline continues the comment.
+                while i < bytes.len() && (bytes[i] != b'\n' || continues_line(bytes, i)) {
+                    i += 1;
+                }
+                TokenKind::Comment
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = find_bytes(bytes, i + 2, b"*/").map_or(bytes.len(), |end| end + 2);
+                TokenKind::Comment
+            }
+            b'"' => {
+                i = if is_raw_string_prefix(bytes, i) {
+                    raw_string_end(bytes, i)
+                } else {
+                    quoted_end(bytes, i, b'"')
+                };
+                TokenKind::String
+            }
+            b'\'' => {
+                i = quoted_end(bytes, i, b'\'');
+                TokenKind::Char
+            }
+            b'#' if line_start && directive.is_none() => {
+                directive = Some(tokens.len());
+                i += 1;
+                TokenKind::Directive
+            }
+            b'0'..=b'9' => {
+                i = number_end(bytes, i);
+                TokenKind::Number
+            }
+            b'.' if bytes.get(i + 1).is_some_and(u8::is_ascii_digit) => {
+                i = number_end(bytes, i);
+                TokenKind::Number
+            }
+            byte if is_word(byte) => {
+                while i < bytes.len() && is_word(bytes[i]) {
+                    i += 1;
+                }
+                TokenKind::Identifier
+            }
+            _ => {
+                // One whole character, so a stray non-ASCII byte never splits it.
+                i += src[i..].chars().next().map_or(1, char::len_utf8);
+                TokenKind::Punct
+            }
+        };
+        let newline = bytes[start..i].iter().position(|&b| b == b'\n');
+        // A comment counts as whitespace, so one ending on a later line leaves `i` at
+        // that line's start.
+        line_start = kind == TokenKind::Comment && (line_start || newline.is_some());
+        if let Some(open) = directive.filter(|&open| open != tokens.len()) {
+            if !matches!(
+                kind,
+                TokenKind::Comment | TokenKind::String | TokenKind::Char
+            ) {
+                continue;
+            }
+            // A newline inside a comment or literal ends the directive, as the
+            // backslash before it is part of that token.
+            if let Some(newline) = newline {
+                tokens[open].span.end = start + newline;
+                directive = None;
+            }
+        }
+        tokens.push(Token {
+            kind,
+            span: start..i,
+        });
+    }
+    if let Some(open) = directive {
+        tokens[open].span.end = bytes.len();
+    }
+    tokens
+}
+
+// Returns the offset just past the number starting at `start`: its digits, letters,
+// `.`s, digit separators and exponent signs, as in `0x1p-3f` or `1'000'000ull`.
+fn number_end(bytes: &[u8], start: usize) -> usize {
+    let mut i = start + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' | b'-' if matches!(bytes[i - 1], b'e' | b'E' | b'p' | b'P') => i += 1,
+            byte if byte.is_ascii_alphanumeric() || matches!(byte, b'_' | b'.' | b'\'') => i += 1,
+            _ => break,
+        }
+    }
+    i
+}
+
+// Replaces comments with spaces, and with `literals` also the contents of string and
+// char literals. Newlines and byte offsets are preserved so positions map back to `src`.
+fn blank_comments_and_literals(src: &str, literals: bool) -> String {
+    blank_tokens(src, &tokenize(src), literals)
+}
+
+// Like `blank_comments_and_literals`, for `tokens` already found in `src`.
+fn blank_tokens(src: &str, tokens: &[Token], literals: bool) -> String {
+    let bytes = src.as_bytes();
+    let mut out = bytes.to_vec();
+    for token in tokens {
+        match token.kind {
+            TokenKind::Comment => blank(&mut out, token.span.clone()),
+            TokenKind::String | TokenKind::Char if literals => {
+                blank_literal(&mut out, bytes, token.span.clone())
+            }
+            _ => {}
+        }
+    }
+    // Only whole ASCII-delimited regions were replaced, so this never loses data.
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+// Overwrites a byte range with spaces, keeping newlines.
+fn blank(out: &mut [u8], range: Range<usize>) {
+    for byte in &mut out[range] {
+        if *byte != b'\n' {
+            *byte = b' ';
+        }
+    }
+}
+
+// Blanks the contents of a literal spanning `range`, keeping its delimiting quotes.
+fn blank_literal(out: &mut [u8], bytes: &[u8], range: Range<usize>) {
+    let quote = bytes[range.start];
+    let closed = range.end > range.start + 1 && bytes[range.end - 1] == quote;
+    blank(out, range.start + 1..range.end - usize::from(closed));
+}
+
+// Returns true if the newline at `i` is escaped by a backslash (optionally before `\r`).
+fn continues_line(bytes: &[u8], i: usize) -> bool {
+    match i.checked_sub(1).map(|j| bytes[j]) {
+        Some(b'\\') => true,
+        Some(b'\r') => i >= 2 && bytes[i - 2] == b'\\',
+        _ => false,
+    }
+}
+
+// Finds `needle` in `bytes` at or after `from`.
+fn find_bytes(bytes: &[u8], from: usize, needle: &[u8]) -> Option<usize> {
+    bytes
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|pos| from + pos)
+}
+
+// Returns the offset just past the closing `quote` of a literal opened at `open`,
+// stopping at an unescaped newline for unterminated literals.
+fn quoted_end(bytes: &[u8], open: usize, quote: u8) -> usize {
+    let mut i = open + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'\n' => return i,
+            byte if byte == quote => return i + 1,
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+// Returns true if the quote at `quote` opens a raw string (`R"`, `u8R"`, `LR"`, ...).
+fn is_raw_string_prefix(bytes: &[u8], quote: usize) -> bool {
+    let start = bytes[..quote]
+        .iter()
+        .rposition(|&b| !(b.is_ascii_alphanumeric() || b == b'_'))
+        .map_or(0, |i| i + 1);
+    matches!(&bytes[start..quote], b"R" | b"u8R" | b"uR" | b"UR" | b"LR")
+}
+
+// Returns the offset just past the raw string opened at `quote`.
+fn raw_string_end(bytes: &[u8], quote: usize) -> usize {
+    let Some(paren) = bytes[quote + 1..]
+        .iter()
+        .take(17)
+        .position(|&b| b == b'(')
+        .map(|pos| quote + 1 + pos)
+    else {
+        return quoted_end(bytes, quote, b'"');
+    };
+    let mut close = Vec::with_capacity(paren - quote + 1);
+    close.push(b')');
+    close.extend_from_slice(&bytes[quote + 1..paren]);
+    close.push(b'"');
+    find_bytes(bytes, paren + 1, &close).map_or(bytes.len(), |end| end + close.len())
+}
+
+// Returns the range between the '(' ending at `open` and its matching ')'.
+// Runs to the end of the input if the list is never closed.
+fn param_list(src: &str, open: usize) -> Range<usize> {
+    let mut depth = 0usize;
+    for (i, c) in src[open..].char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return open..open + i,
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+    open..src.len()
+}
+
+// Splits a parameter list on the commas that are not nested in <>, (), [] or {}.
+fn split_top_level(list: &str) -> Vec<Range<usize>> {
+    let mut parts = Vec::new();
+    let mut depth = Nesting::default();
+    let mut start = 0;
+    for (i, c) in list.char_indices() {
+        match c {
+            ',' if depth.is_top_level() => {
+                parts.push(start..i);
+                start = i + 1;
+            }
+            c => depth.step(c, &list[..i]),
+        }
+    }
+    parts.push(start..list.len());
+    parts
+}
+
+/// Splits a parameter list such as `std::map<K, V> m, int n = {1, 2}` into its
+/// parameters. Commas inside template arguments, brackets, braces and literals do not
+/// split, and each parameter is returned trimmed. An empty list gives no parameters.
+pub fn split_parameters(list: &str) -> Vec<String> {
+    if list.trim().is_empty() {
+        return Vec::new();
+    }
+    let mask = blank_comments_and_literals(list, true);
+    split_top_level(&mask)
+        .into_iter()
+        .map(|part| list[trimmed(&mask, part)].to_owned())
+        .collect()
+}
+
+// Narrows a range of `text` so it excludes surrounding whitespace.
+fn trimmed(text: &str, range: Range<usize>) -> Range<usize> {
+    let slice = &text[range.clone()];
+    let start = range.start + (slice.len() - slice.trim_start().len());
+    let end = range.end - (slice.len() - slice.trim_end().len());
+    start..end.max(start)
+}
+
+// Keywords that can end a parameter type, so they are never taken as its name.
+const TYPE_KEYWORDS: &[&str] = &[
+    "auto", "bool", "char", "char8_t", "char16_t", "char32_t", "class", "const", "double", "enum",
+    "float", "int", "long", "short", "signed", "struct", "typename", "union", "unsigned", "void",
+    "volatile", "wchar_t",
+];
+
+// Parses a parameter list into its parameters. `mask` has literals blanked and drives
+// the parse; `code` is the same text with literals intact, used for default arguments.
+fn parse_parameters(mask: &str, code: &str) -> Vec<Parameter> {
+    let list = mask.trim();
+    if list.is_empty() || list == "void" {
+        return Vec::new();
+    }
+    split_top_level(mask)
+        .into_iter()
+        .map(|part| {
+            let part = trimmed(mask, part);
+            parse_parameter(&mask[part.clone()], &code[part])
+        })
+        .collect()
+}
+
+// Parses a single parameter into its type, optional name and optional default.
+fn parse_parameter(param: &str, code: &str) -> Parameter {
+    let (decl, default) = match split_top_level_char(param, '=') {
+        Some((decl, _)) => (
+            decl.trim_end(),
+            Some(code[decl.len() + 1..].trim().to_owned()),
+        ),
+        None => (param, None),
+    };
+    let (decl, is_pack) = strip_pack_expansion(decl);
+    Parameter {
+        is_pack,
+        ..parse_declarator(&decl, default)
+    }
+}
+
+// Removes the `...` of a pack expansion such as `const Args&... args`, reporting whether
+// one was present. A lone `...` is a C variadic and is left alone.
+fn strip_pack_expansion(decl: &str) -> (String, bool) {
+    if decl.trim() == "..." {
+        return (decl.to_owned(), false);
+    }
+    match decl.rfind("...") {
+        Some(pos) if decl[pos + 3..].trim().is_empty() || is_identifier(decl[pos + 3..].trim()) => {
+            (format!("{} {}", &decl[..pos], &decl[pos + 3..]), true)
+        }
+        _ => (decl.to_owned(), false),
+    }
+}
+
+// Parses a parameter declaration without its default into a type and optional name.
+fn parse_declarator(decl: &str, default: Option<String>) -> Parameter {
+    let decl = decl.trim();
+    // Function pointer or reference to array: `int (*cmp)(int)`, `int (&arr)[4]`.
+    if let Some(open) = find_top_level(decl, '(') {
+        let list = param_list(decl, open + 1);
+        let inner = decl[list.clone()].trim_end();
+        let name = inner.trim_start_matches(['*', '&', ' ']);
+        if inner.trim_start().starts_with(['*', '&']) && is_identifier(name) {
+            let type_name = format!(
+                "{}({}){}",
+                &decl[..open],
+                inner[..inner.len() - name.len()].trim(),
+                decl.get(list.end + 1..).unwrap_or("")
+            );
+            return Parameter {
+                type_name: normalize_type(&type_name),
+                name: Some(name.to_owned()),
+                default,
+                is_pack: false,
+            };
+        }
+        return Parameter {
+            type_name: normalize_type(decl),
+            name: None,
+            default,
+            is_pack: false,
+        };
+    }
+    // Array parameter: the name sits before the brackets.
+    let (base, array) = match decl.find('[') {
+        Some(i) => (decl[..i].trim_end(), &decl[i..]),
+        None => (decl, ""),
+    };
+    let ident_start = base
+        .char_indices()
+        .rfind(|&(_, c)| !(c.is_alphanumeric() || c == '_'))
+        .map_or(0, |(i, c)| i + c.len_utf8());
+    let (ty, ident) = base.split_at(ident_start);
+    let ty = ty.trim_end();
+    let named = !ident.is_empty()
+        && !ty.is_empty()
+        && !ty.ends_with("::")
+        && !TYPE_KEYWORDS.contains(&ident)
+        && !ty
+            .split_whitespace()
+            .all(|word| matches!(word, "class" | "enum" | "struct" | "typename" | "union"));
+    if named {
+        Parameter {
+            type_name: normalize_type(&format!("{}{}", ty, array)),
+            name: Some(ident.to_owned()),
+            default,
+            is_pack: false,
+        }
+    } else {
+        Parameter {
+            type_name: normalize_type(decl),
+            name: None,
+            default,
+            is_pack: false,
+        }
+    }
+}
+
+// Bracket depth while scanning a list. Angle brackets only nest outside parentheses,
+// so the `>` of `(N > 2)` does not close a template argument list, and the `>` of
+// `->` is never a bracket.
+#[derive(Default)]
+struct Nesting {
+    brackets: usize,
+    angles: usize,
+}
+
+impl Nesting {
+    fn step(&mut self, c: char, before: &str) {
+        match c {
+            '(' | '[' | '{' => self.brackets += 1,
+            ')' | ']' | '}' => self.brackets = self.brackets.saturating_sub(1),
+            '<' if self.brackets == 0 => self.angles += 1,
+            '>' if self.brackets == 0 && !before.ends_with('-') => {
+                self.angles = self.angles.saturating_sub(1)
+            }
+            _ => {}
+        }
+    }
+
+    fn is_top_level(&self) -> bool {
+        self.brackets == 0 && self.angles == 0
+    }
+}
+
+// Returns the index of the first `c` outside template argument lists and brackets.
+fn find_top_level(text: &str, c: char) -> Option<usize> {
+    let mut depth = Nesting::default();
+    for (i, ch) in text.char_indices() {
+        if ch == c && depth.is_top_level() {
+            return Some(i);
+        }
+        depth.step(ch, &text[..i]);
+    }
+    None
+}
+
+// Returns the index of the last `c` outside template argument lists and brackets.
+fn find_last_top_level(text: &str, c: char) -> Option<usize> {
+    let mut depth = Nesting::default();
+    let mut last = None;
+    for (i, ch) in text.char_indices() {
+        if ch == c && depth.is_top_level() {
+            last = Some(i);
+        }
+        depth.step(ch, &text[..i]);
+    }
+    last
+}
+
+// Splits at the first `sep` that is not nested in <>, (), [] or {}.
+fn split_top_level_char(text: &str, sep: char) -> Option<(&str, &str)> {
+    find_top_level(text, sep).map(|i| (&text[..i], &text[i + 1..]))
+}
+
+// Returns true for a plain C++ identifier.
+fn is_identifier(text: &str) -> bool {
+    let mut chars = text.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+static CONFIG_FLAGS: LazyLock<Mutex<Vec<String>>> = LazyLock::new(|| Mutex::new(Vec::new()));
+static COMPILE_FLAGS: LazyLock<Mutex<Vec<String>>> = LazyLock::new(|| Mutex::new(Vec::new()));
+
+macro_rules! define_lazy_path {
+    ($name:ident, $path:expr) => {
+        static $name: LazyLock<PathBuf> = LazyLock::new(|| PathBuf::from($path));
+    };
+    ($name:ident, $base:expr, $path:expr) => {
+        static $name: LazyLock<PathBuf> = LazyLock::new(|| $base.join($path));
+    };
+}
+macro_rules! add_flag {
+    ($flags:expr, $flag:expr) => {{
+        $flags.lock().unwrap().push(String::from($flag));
+    }};
+}
+
+static MINIMUM_GIT_VERSION: [u8; 3] = [2, 40, 0];
+static MINIMUM_CMAKE_VERSION: [u8; 3] = [3, 31, 0];
+const ABSEIL_SRC: &str = "https://github.com/abseil/abseil-cpp.git";
+// Abseil release checked out when ASTD_ABSEIL_REF is unset.
+const DEFAULT_ABSEIL_REF: &str = "20250127.0";
+
+// Locations of the generated artifacts inside the external directory.
+const BIND_FILE_NAME: &str = "bindings.cpp";
+const SIGNATURES_FILE_NAME: &str = "signatures.json";
+const INCLUDE_SUBDIR: &str = "include/";
+const LIB_SUBDIR: &str = "lib/";
+// Locations of the CMake build tree and the build stamp inside the build directory.
+const ABSEIL_BUILD_SUBDIR: &str = "abseil-cpp/build/";
+const STAMP_FILE_NAME: &str = "astd.stamp";
+
+define_lazy_path!(BUILD_DIR, build_dir());
+define_lazy_path!(
+    ABSEIL_DIR,
+    vendored_abseil_dir().unwrap_or_else(|| BUILD_DIR.join("abseil-cpp/"))
+);
+define_lazy_path!(ABSEIL_BUILD_DIR, BUILD_DIR, ABSEIL_BUILD_SUBDIR);
+define_lazy_path!(SOURCE_DIR, ABSEIL_DIR, "absl/");
+define_lazy_path!(STAMP_FILE, BUILD_DIR, STAMP_FILE_NAME);
+define_lazy_path!(EXTERNAL_DIR, external_dir());
+define_lazy_path!(RUST_BIND_FILE, crate_dir(), "src/generated.rs");
+define_lazy_path!(INCLUDE_DIR, EXTERNAL_DIR, INCLUDE_SUBDIR);
+define_lazy_path!(
+    LIB_DIR,
+    gather_libs::target_lib_dir(&EXTERNAL_DIR.join(LIB_SUBDIR), &gather_libs::target())
+);
+
+// Returns the root of this crate, falling back to the working directory when the
+// build script is run outside Cargo.
+fn crate_dir() -> PathBuf {
+    env::var_os("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_default()
+}
+
+// Returns the directory for intermediate build artifacts: Cargo's OUT_DIR, or
+// `target/` under the crate when run outside Cargo.
+fn build_dir() -> PathBuf {
+    env::var_os("OUT_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| crate_dir().join("target/"))
+}
+
+/// Returns the directory receiving the gathered headers, libraries and
+/// `bindings.cpp`: `ASTD_EXTERNAL_DIR` when set, otherwise `external/` under the crate.
+pub fn external_dir() -> PathBuf {
+    println!("cargo:rerun-if-env-changed=ASTD_EXTERNAL_DIR");
+    env::var_os("ASTD_EXTERNAL_DIR")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| crate_dir().join("external/"))
+}
+
+// Sets build flags for building `kind` libraries in `config` for the target `layout`.
+fn build_flags(
+    config: gather_libs::BuildConfig,
+    layout: gather_libs::LibLayout,
+    kind: gather_libs::LibKind,
+) {
+    let arch = gather_libs::target_arch();
+    let generator = cmake_generator();
+    if let Some(generator) = &generator {
+        log_info(format_args!("Using CMake generator: {}", generator));
+    }
+    CONFIG_FLAGS.lock().unwrap().extend(cmake_config_flags(
+        config,
+        layout,
+        kind,
+        generator.as_deref(),
+        &arch,
+    ));
+    match cmake_toolchain() {
+        Some(toolchain) => {
+            log_info(format_args!(
+                "Using CMake toolchain file: {}",
+                toolchain.display()
+            ));
+            add_flag!(
+                CONFIG_FLAGS,
+                format!("-DCMAKE_TOOLCHAIN_FILE={}", toolchain.display())
+            );
+        }
+        None if env::var("TARGET").ok() != env::var("HOST").ok() => log_warn(format_args!(
+            "Building for {} without ASTD_CMAKE_TOOLCHAIN; Abseil will be built for the host",
+            gather_libs::target()
+        )),
+        None => {}
+    }
+    COMPILE_FLAGS.lock().unwrap().extend(cmake_build_flags(
+        layout,
+        generator.as_deref(),
+        &arch,
+        build_jobs(),
+    ));
+}
+
+// The Visual Studio platform name for the target architecture `arch`, or `None` to
+// leave the generator's default.
+fn msbuild_platform(arch: &str) -> Option<&'static str> {
+    match arch {
+        "x86_64" => Some("x64"),
+        "x86" => Some("Win32"),
+        "aarch64" | "arm64ec" => Some("ARM64"),
+        "arm" => Some("ARM"),
+        _ => None,
+    }
+}
+
+/// Returns the CMake configure flags for building `kind` libraries in `config` for the
+/// target `layout` and architecture `arch`, with `generator`, or the platform default
+/// generator when it is `None`.
+pub fn cmake_config_flags(
+    config: gather_libs::BuildConfig,
+    layout: gather_libs::LibLayout,
+    kind: gather_libs::LibKind,
+    generator: Option<&str>,
+    arch: &str,
+) -> Vec<String> {
+    let mut flags = Vec::new();
+    let msvc = matches!(layout, gather_libs::LibLayout::Msvc(_));
+    if let Some(generator) = generator {
+        flags.push("-G".to_owned());
+        flags.push(generator.to_owned());
+    } else if msvc && let Some(platform) = msbuild_platform(arch) {
+        // The default Visual Studio generator builds for the host unless told otherwise.
+        flags.push("-A".to_owned());
+        flags.push(platform.to_owned());
+    }
+    flags.push("-DABSL_USE_GOOGLETEST_HEAD=ON".to_owned());
+    flags.push("-DCMAKE_CXX_STANDARD_REQUIRED=ON".to_owned());
+    flags.push("-DCMAKE_CXX_STANDARD=20".to_owned());
+    flags.push(format!("-DCMAKE_BUILD_TYPE={}", config.name()));
+    match kind {
+        // Set off explicitly, as the cache keeps the value of an earlier shared build.
+        gather_libs::LibKind::Static => {
+            flags.push("-DBUILD_SHARED_LIBS=OFF".to_owned());
+            // A static runtime cannot be shared safely across DLL boundaries.
+            if msvc {
+                flags.push("-DABSL_MSVC_STATIC_RUNTIME=ON".to_owned());
+            }
+        }
+        gather_libs::LibKind::Shared => flags.push("-DBUILD_SHARED_LIBS=ON".to_owned()),
+    }
+    flags
+}
+
+/// Returns the `cmake --build` flags for the target `layout` and architecture `arch`
+/// with `generator`, running `jobs` jobs in parallel. The MSBuild flags are used for an
+/// MSVC target built with the default Visual Studio generator.
+pub fn cmake_build_flags(
+    layout: gather_libs::LibLayout,
+    generator: Option<&str>,
+    arch: &str,
+    jobs: usize,
+) -> Vec<String> {
+    let mut flags = vec!["--build".to_owned(), ".".to_owned()];
+    match layout {
+        gather_libs::LibLayout::Msvc(config) if generator.is_none() => {
+            flags.push("--".to_owned());
+            if let Some(platform) = msbuild_platform(arch) {
+                flags.push(format!("/p:Platform={}", platform));
+            }
+            flags.push(format!("/p:Configuration={}", config.name()));
+            flags.push(format!("/m:{}", jobs));
+        }
+        _ => {
+            flags.push("--parallel".to_owned());
+            flags.push(jobs.to_string());
+        }
+    }
+    flags
+}
+
+/// Returns the arguments that configure the CMake build tree `build_dir` for the source
+/// tree `source_dir` with `flags`, as from [`cmake_config_flags`].
+pub fn cmake_configure_args(source_dir: &Path, build_dir: &Path, flags: &[String]) -> Vec<String> {
+    let mut args = vec![
+        "-S".to_owned(),
+        source_dir.display().to_string(),
+        "-B".to_owned(),
+        build_dir.display().to_string(),
+    ];
+    args.extend(flags.iter().cloned());
+    args
+}
+
+/// Returns true if `cache`, the text of a `CMakeCache.txt`, records a different
+/// generator or platform than the `-G` and `-A` in `flags` ask for, or a different
+/// toolchain file than its `-DCMAKE_TOOLCHAIN_FILE`. CMake refuses to switch the
+/// generator or platform of a build tree and reads the toolchain file only when first
+/// configuring it, so it must then be configured afresh.
+pub fn cmake_cache_is_stale(cache: &str, flags: &[String]) -> bool {
+    let option = |name: &str| {
+        flags
+            .iter()
+            .position(|flag| flag == name)
+            .and_then(|i| flags.get(i + 1))
+    };
+    let toolchain = flags
+        .iter()
+        .find_map(|flag| flag.strip_prefix("-DCMAKE_TOOLCHAIN_FILE="));
+    option("-G").is_some_and(|generator| {
+        cmake_cache_value(cache, "CMAKE_GENERATOR") != Some(generator.as_str())
+    }) || option("-A").is_some_and(|platform| {
+        cmake_cache_value(cache, "CMAKE_GENERATOR_PLATFORM") != Some(platform.as_str())
+    }) || cmake_cache_value(cache, "CMAKE_TOOLCHAIN_FILE") != toolchain
+}
+
+// Returns the value of the entry `name` in the text of a `CMakeCache.txt`, whose entries
+// read `NAME:TYPE=VALUE`.
+fn cmake_cache_value<'a>(cache: &'a str, name: &str) -> Option<&'a str> {
+    cache.lines().find_map(|line| {
+        let (key, value) = line.split_once('=')?;
+        (key.split_once(':')?.0 == name).then_some(value)
+    })
+}
+
+// Configures the CMake build tree with CONFIG_FLAGS, afresh if its cache is stale, then
+// builds Abseil in it with COMPILE_FLAGS.
+fn build_abseil() -> Result<()> {
+    let flags = CONFIG_FLAGS.lock().unwrap().clone();
+    let mut configure = cmake_configure_args(&ABSEIL_DIR, &ABSEIL_BUILD_DIR, &flags);
+    let cache = ABSEIL_BUILD_DIR.join("CMakeCache.txt");
+    if fs::read_to_string(&cache).is_ok_and(|cache| cmake_cache_is_stale(&cache, &flags)) {
+        log_info(format_args!(
+            "CMake configuration of {:?} changed, configuring afresh",
+            *ABSEIL_BUILD_DIR
+        ));
+        configure.insert(0, "--fresh".to_owned());
+    }
+    log_info(format_args!(
+        "Configuring Abseil in {:?}",
+        *ABSEIL_BUILD_DIR
+    ));
+    let configure: Vec<&str> = configure.iter().map(String::as_str).collect();
+    run_command("cmake", &configure, &ABSEIL_BUILD_DIR)?;
+    let compile = COMPILE_FLAGS.lock().unwrap().clone();
+    log_info(format_args!(
+        "Building Abseil with `cmake {}`",
+        compile.join(" ")
+    ));
+    let compile: Vec<&str> = compile.iter().map(String::as_str).collect();
+    run_command("cmake", &compile, &ABSEIL_BUILD_DIR)?;
+    Ok(())
+}
+
+// Returns the CMake toolchain file named by ASTD_CMAKE_TOOLCHAIN, relative to the crate
+// if not absolute, or `None` when unset. Panics if the file does not exist.
+fn cmake_toolchain() -> Option<PathBuf> {
+    println!("cargo:rerun-if-env-changed=ASTD_CMAKE_TOOLCHAIN");
+    let value = env::var("ASTD_CMAKE_TOOLCHAIN").ok()?;
+    if value.trim().is_empty() {
+        return None;
+    }
+    let toolchain = crate_dir().join(value.trim());
+    if !toolchain.is_file() {
+        panic!("ASTD_CMAKE_TOOLCHAIN file {:?} does not exist", toolchain);
+    }
+    println!("cargo:rerun-if-changed={}", toolchain.display());
+    Some(toolchain)
+}
+
+// Returns the CMake generator named by ASTD_CMAKE_GENERATOR, or `None` for the
+// platform default. Panics if CMake does not know the generator, or if its build
+// tool is missing.
+fn cmake_generator() -> Option<String> {
+    println!("cargo:rerun-if-env-changed=ASTD_CMAKE_GENERATOR");
+    let generator = env::var("ASTD_CMAKE_GENERATOR").ok()?.trim().to_owned();
+    if generator.is_empty() {
+        return None;
+    }
+    let help = run_command("cmake", &["--help"], Path::new("."))
+        .unwrap_or_else(|err| panic!("Failed to list the CMake generators: {}", err));
+    let known = help.lines().any(|line| {
+        line.trim_start()
+            .trim_start_matches("* ")
+            .strip_prefix(generator.as_str())
+            .is_some_and(|rest| rest.starts_with([' ', '=']) || rest.is_empty())
+    });
+    if !known {
+        panic!("CMake does not support the generator {:?}", generator);
+    }
+    let tool = match generator.as_str() {
+        "Ninja" | "Ninja Multi-Config" => Some("ninja"),
+        "Unix Makefiles" => Some("make"),
+        _ => None,
+    };
+    if let Some(tool) = tool
+        && run_command(tool, &["--version"], Path::new(".")).is_err()
+    {
+        panic!(
+            "Generator {:?} needs `{}`, which was not found",
+            generator, tool
+        );
+    }
+    Some(generator)
+}
+
+// Returns the number of parallel compile jobs: ASTD_BUILD_JOBS if it is a positive
+// number, otherwise the available parallelism.
+fn build_jobs() -> usize {
+    println!("cargo:rerun-if-env-changed=ASTD_BUILD_JOBS");
+    let default = std::thread::available_parallelism().map_or(1, |n| n.get());
+    match env::var("ASTD_BUILD_JOBS") {
+        Ok(value) => match value.trim().parse::<usize>() {
+            Ok(jobs) if jobs > 0 => jobs,
+            _ => {
+                log_warn(format_args!(
+                    "Ignoring invalid ASTD_BUILD_JOBS={:?}, using {} jobs",
+                    value, default
+                ));
+                default
+            }
+        },
+        Err(_) => default,
+    }
+}
+
+// Creates a directory if it doesn't exist.
+// Logs any error and continues.
+fn create_path(path: &Path) {
+    if !path.exists()
+        && let Err(err) = fs::create_dir_all(path)
+    {
+        log_error(format_args!("Failed to create path {:?}: {}", path, err));
+    }
+}
+
+/// Removes every file under `dest_dir` that is not a `.h` file under `src_dir` at the
+/// same relative path, then any directory left empty, so headers dropped upstream do
+/// not linger. Returns the number of files removed. A missing `dest_dir` has nothing to
+/// prune.
+pub fn prune_stale_headers(src_dir: &Path, dest_dir: &Path) -> Result<usize> {
+    if !dest_dir.exists() {
+        return Ok(0);
+    }
+    let mut headers = Vec::new();
+    collect_headers(src_dir, &["h"], &mut headers)?;
+    let keep: HashSet<PathBuf> = headers
+        .iter()
+        .filter_map(|header| header.strip_prefix(src_dir).ok())
+        .map(|relative| dest_dir.join(relative))
+        .collect();
+    let mut removed = 0;
+    remove_stale(dest_dir, &keep, &mut removed)?;
+    Ok(removed)
+}
+
+// Removes the files under `dir` not in `keep`, and the subdirectories this empties.
+fn remove_stale(dir: &Path, keep: &HashSet<PathBuf>, removed: &mut usize) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            remove_stale(&path, keep, removed)?;
+            if fs::read_dir(&path)?.next().is_none() {
+                fs::remove_dir(&path)?;
+            }
+        } else if !keep.contains(&path) {
+            log_debug(format_args!("Pruning stale header {:?}", path));
+            fs::remove_file(&path)?;
+            *removed += 1;
+        }
+    }
+    Ok(())
+}
+
+/// Recursively copies the `.h` files under `src_dir` into `dest_dir`, keeping their
+/// paths relative to `base`. Stops at the first error, which names the path involved.
+pub fn visit_dirs(src_dir: &Path, dest_dir: &Path, base: &Path) -> Result<()> {
+    let context = |err: std::io::Error, what: String| {
+        std::io::Error::new(err.kind(), format!("{}: {}", what, err))
+    };
+    let entries = fs::read_dir(src_dir)
+        .map_err(|err| context(err, format!("Failed to read directory {:?}", src_dir)))?;
+    for entry in entries {
+        let path = entry
+            .map_err(|err| context(err, format!("Failed to read an entry of {:?}", src_dir)))?
+            .path();
+        if path.is_dir() {
+            visit_dirs(&path, dest_dir, base)?;
+        } else if path.extension().and_then(|s| s.to_str()) == Some("h") {
+            let dest_file_path = dest_dir.join(path.strip_prefix(base).unwrap_or(&path));
+            if let Some(parent) = dest_file_path.parent() {
+                fs::create_dir_all(parent).map_err(|err| {
+                    context(err, format!("Failed to create directory {:?}", parent))
+                })?;
+            }
+            log_debug(format_args!("Copying {:?} -> {:?}", path, dest_file_path));
+            fs::copy(&path, &dest_file_path).map_err(|err| {
+                context(
+                    err,
+                    format!("Failed to copy file {:?} to {:?}", path, dest_file_path),
+                )
+            })?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes `bindings.cpp` into `external_dir` from the headers gathered under its
+/// `include/` directory, returning the path written.
+pub fn generate_bindings(external_dir: &Path) -> Result<PathBuf> {
+    let headers_dir = &external_dir.join(INCLUDE_SUBDIR);
+    let bindings_path = external_dir.join(BIND_FILE_NAME);
+    let mut writer = BufWriter::new(File::create(&bindings_path)?);
+    writeln!(writer, "// language: C++")?;
+    writeln!(
+        writer,
+        "// Auto-generated: includes from the external folder"
+    )?;
+    // A failure to list the headers is critical. They are included before `extern "C"`
+    // opens, as the templates and overloads they declare cannot have C linkage.
+    for include in bind_includes(headers_dir)? {
+        writeln!(writer, "#include \"{}\"", include)?;
+    }
+    writeln!(writer)?;
+    writeln!(writer, "#ifdef __cplusplus")?;
+    writeln!(writer, "extern \"C\" {{")?;
+    writeln!(writer, "#endif")?;
+    writeln!(writer)?;
+    generate_bind_wrappers(headers_dir, &mut writer)?;
+    writeln!(writer)?;
+    writeln!(writer, "#ifdef __cplusplus")?;
+    writeln!(writer, "}}")?;
+    writeln!(writer, "#endif")?;
+    writer.flush()?;
+    log_info(format_args!("Generated bindings at: {:?}", bindings_path));
+    Ok(bindings_path)
+}
+
+/// Lists the `.h` files under `headers_dir` as include paths relative to it, with `/`
+/// separators, in sorted order. A header reachable under several paths, through a
+/// symlink or a case-insensitive filesystem, is listed once, under the first of them.
+pub fn bind_includes(headers_dir: &Path) -> Result<BTreeSet<String>> {
+    let mut headers = Vec::new();
+    collect_headers(headers_dir, &["h"], &mut headers)?;
+    let by_include: BTreeMap<String, PathBuf> = headers
+        .into_iter()
+        .map(|path| {
+            let include = path
+                .strip_prefix(headers_dir)
+                .unwrap_or(&path)
+                .to_string_lossy()
+                .replace("\\", "/");
+            (include, path)
+        })
+        .collect();
+    let mut seen = HashSet::new();
+    let mut includes = BTreeSet::new();
+    for (include, path) in by_include {
+        if seen.insert(fs::canonicalize(&path)?) {
+            includes.insert(include);
+        }
+    }
+    Ok(includes)
+}
+
+// Generates extern "C" wrappers for the free functions declared in the headers.
+// Skipped functions are logged with their location.
+fn generate_bind_wrappers(headers_dir: &Path, writer: &mut BufWriter<File>) -> Result<()> {
+    let details = header_details(headers_dir)?;
+    let skip_deprecated = skip_deprecated();
+    for (detail, _, planned) in plan_wrappers(&details, skip_deprecated) {
+        if let Err(reason) = planned {
+            log_debug(format_args!("{}: skipped: {}", detail.diagnostic(), reason));
+        }
+    }
+    emit_wrappers(&details, skip_deprecated, writer)
+}
+
+// Extracts the functions of every header under `headers_dir`, in sorted path order,
+// parsing a header reachable under several paths once.
+fn header_details(headers_dir: &Path) -> Result<Vec<FunctionDetail>> {
+    let headers: Vec<PathBuf> = bind_includes(headers_dir)?
+        .iter()
+        .map(|include| headers_dir.join(include))
+        .collect();
+    let macros = strip_macros();
+    let macros: Vec<&str> = macros.iter().map(String::as_str).collect();
+    let mut details = Vec::new();
+    for (header, parsed) in headers.iter().zip(parse_headers(&headers, &macros)) {
+        let (parsed, diagnostics) = parsed?;
+        if !diagnostics.is_empty() {
+            log_warn(format_args!(
+                "{} declarations skipped in {}",
+                diagnostics.len(),
+                header.display()
+            ));
+        }
+        details.extend(parsed);
+    }
+    let total = details.len();
+    let filter = FunctionFilter::from_env();
+    for detail in &details {
+        let decision = if filter.matches(detail) {
+            "kept"
+        } else {
+            "left out"
+        };
+        log_debug(format_args!(
+            "{}: {} by the function filters",
+            detail.diagnostic(),
+            decision
+        ));
+    }
+    let details = filter_details(details, &filter);
+    if details.len() < total {
+        log_info(format_args!(
+            "{} functions left out by the function filters",
+            total - details.len()
+        ));
+    }
+    Ok(details)
+}
+
+/// Extracts the functions of every `.h` and `.inc` file under `dir`, parsing the files
+/// in parallel. Results are sorted by path. A file that cannot be read is left out of
+/// the results and its error returned alongside them; only a failure to walk the tree
+/// is an error.
+pub fn extract_from_dir(dir: &Path) -> Result<DirDetails> {
+    let mut headers = Vec::new();
+    collect_headers(dir, &["h", "inc"], &mut headers)?;
+    headers.sort();
+    let parsed = parse_headers(&headers, &[]);
+    let mut extracted = DirDetails::default();
+    for (header, parsed) in headers.into_iter().zip(parsed) {
+        match parsed {
+            Ok((details, _)) => extracted.headers.push((header, details)),
+            Err(err) => extracted.errors.push((header, err)),
+        }
+    }
+    Ok(extracted)
+}
+
+/// Reads the file at `path` and extracts its functions, each tagged with `path`. Bytes
+/// that are not valid UTF-8, such as Latin-1 in comments, are replaced rather than
+/// failing the read.
+pub fn parse_header_file<P: AsRef<Path>>(path: P) -> Result<Vec<FunctionDetail>> {
+    let path = path.as_ref();
+    let src = read_source(path)?;
+    Ok(extract_function_details(&src)
+        .into_iter()
+        .map(|detail| FunctionDetail {
+            path: Some(path.to_owned()),
+            ..detail
+        })
+        .collect())
+}
+
+/// Reads the header at `path` like [`parse_header_file`], and also lists its `#include`
+/// directives.
+pub fn extract_from_file(path: &Path) -> Result<FileDetails> {
+    let src = read_source(path)?;
+    Ok(FileDetails {
+        path: path.to_owned(),
+        includes: extract_includes(&src),
+        functions: extract_function_details(&src)
+            .into_iter()
+            .map(|detail| FunctionDetail {
+                path: Some(path.to_owned()),
+                ..detail
+            })
+            .collect(),
+    })
+}
+
+/// Finds the `#include` directives in C++ source, in source order. Directives inside
+/// comments are ignored; conditional groups are not evaluated, so both branches of an
+/// `#ifdef` are listed.
+pub fn extract_includes(src: &str) -> Vec<IncludeDirective> {
+    let code = blank_comments_and_literals(src, false);
+    let lines = LineIndex::new(&code);
+    INCLUDE_REGEX
+        .captures_iter(&code)
+        .filter_map(|cap| {
+            let (path, kind) = match cap.get(1) {
+                Some(path) => (path, IncludeKind::System),
+                None => (cap.get(2)?, IncludeKind::Quoted),
+            };
+            Some(IncludeDirective {
+                path: path.as_str().trim().to_owned(),
+                kind,
+                line: lines.line(path.start()),
+            })
+        })
+        .collect()
+}
+
+/// Parses every `.h` file under `dir` with [`parse_header_file`], sorted by path. Unlike
+/// [`extract_from_dir`], the first file that cannot be read is an error.
+pub fn parse_directory(dir: &Path) -> Result<Vec<(PathBuf, Vec<FunctionDetail>)>> {
+    let mut headers = Vec::new();
+    collect_headers(dir, &["h"], &mut headers)?;
+    headers.sort();
+    headers
+        .into_iter()
+        .map(|header| {
+            let details = parse_header_file(&header)?;
+            Ok((header, details))
+        })
+        .collect()
+}
+
+/// Bumped whenever the parser or the cache format changes what a header yields, so
+/// [`ParseCache`] entries written by an older parser are not reused.
+pub const PARSE_CACHE_VERSION: u32 = 7;
+
+/// Parse results for header files, kept in a directory and reused while a file's
+/// content and the parser version are unchanged.
+#[derive(Debug, Clone)]
+pub struct ParseCache {
+    dir: PathBuf,
+}
+
+impl ParseCache {
+    /// A cache keeping its entries in `dir`, which is created when the first entry is
+    /// written.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        ParseCache { dir: dir.into() }
+    }
+
+    /// Returns the file the entry for the header at `path` is kept in.
+    pub fn entry_path(&self, path: &Path) -> PathBuf {
+        let mut hasher = DefaultHasher::new();
+        path.hash(&mut hasher);
+        self.dir.join(format!("{:016x}.cache", hasher.finish()))
+    }
+
+    /// Returns the functions of the header at `path` like [`parse_header_file`], from
+    /// the cache if its entry matches the file's content. Otherwise the header is parsed
+    /// and the entry rewritten; an entry that is corrupt or from another parser version
+    /// is replaced the same way. Failing to write the entry is only logged.
+    pub fn get_or_parse(&self, path: &Path) -> Result<Vec<FunctionDetail>> {
+        let src = read_source(path)?;
+        let mut hasher = DefaultHasher::new();
+        src.hash(&mut hasher);
+        let header = format!(
+            "astd-parse-cache\t{}\t{}\t{:016x}",
+            PARSE_CACHE_VERSION,
+            env!("CARGO_PKG_VERSION"),
+            hasher.finish()
+        );
+        let entry = self.entry_path(path);
+        let cached = fs::read_to_string(&entry).ok().and_then(|cached| {
+            let mut lines = cached.lines();
+            if lines.next()? != header {
+                return None;
+            }
+            lines
+                .map(|line| decode_detail(line, path))
+                .collect::<Option<Vec<_>>>()
+        });
+        if let Some(details) = cached {
+            return Ok(details);
+        }
+        let details: Vec<FunctionDetail> = extract_function_details(&src)
+            .into_iter()
+            .map(|detail| FunctionDetail {
+                path: Some(path.to_owned()),
+                ..detail
+            })
+            .collect();
+        let mut contents = header;
+        for detail in &details {
+            contents.push('\n');
+            contents.push_str(&encode_detail(detail));
+        }
+        if let Err(err) = fs::create_dir_all(&self.dir).and_then(|()| fs::write(&entry, contents)) {
+            log_warn(format_args!(
+                "Failed to write parse cache entry {:?}: {}",
+                entry, err
+            ));
+        }
+        Ok(details)
+    }
+}
+
+// Writes a FunctionDetail as one line of tab-separated fields, leaving out its path.
+fn encode_detail(detail: &FunctionDetail) -> String {
+    let mut fields = FieldWriter::default();
+    fields.string(&detail.template_prefix);
+    fields.list(&detail.specifiers);
+    fields.string(&detail.return_type);
+    fields.flag(detail.has_trailing_return);
+    fields.flag(detail.is_deduced);
+    fields.string(&detail.name);
+    fields.string(&format!("{:?}", detail.kind));
+    fields.number(detail.parameters.len());
+    for parameter in &detail.parameters {
+        fields.string(&parameter.type_name);
+        fields.option(parameter.name.as_deref());
+        fields.option(parameter.default.as_deref());
+        fields.flag(parameter.is_pack);
+    }
+    fields.flag(detail.is_variadic);
+    fields.list(&detail.trailing_qualifiers);
+    fields.option(
+        detail
+            .special_definition
+            .map(|special| format!("{:?}", special))
+            .as_deref(),
+    );
+    fields.option(detail.namespace.as_deref());
+    fields.option(detail.enclosing_type.as_deref());
+    fields.string(&format!("{:?}", detail.linkage));
+    fields.option(detail.calling_convention.as_deref());
+    fields.number(detail.line);
+    fields.number(detail.column);
+    fields.option(
+        detail
+            .access
+            .map(|access| format!("{:?}", access))
+            .as_deref(),
+    );
+    fields.number(detail.byte_span.start);
+    fields.number(detail.byte_span.end);
+    fields.list(&detail.attributes);
+    fields.option(detail.deprecated.as_deref());
+    fields.flag(detail.is_definition);
+    fields.option(detail.doc.as_deref());
+    fields.0.join("\t")
+}
+
+// Reads a line written by `encode_detail`, setting its path to `path`. Returns `None` if
+// the line is malformed.
+fn decode_detail(line: &str, path: &Path) -> Option<FunctionDetail> {
+    let mut fields = FieldReader(line.split('\t'));
+    let template_prefix = fields.string()?;
+    let specifiers = fields.list()?;
+    let return_type = fields.string()?;
+    let has_trailing_return = fields.flag()?;
+    let is_deduced = fields.flag()?;
+    let name = fields.string()?;
+    let kind = match fields.string()?.as_str() {
+        "Regular" => FunctionKind::Regular,
+        "Constructor" => FunctionKind::Constructor,
+        "Destructor" => FunctionKind::Destructor,
+        "Operator" => FunctionKind::Operator,
+        "Conversion" => FunctionKind::Conversion,
+        _ => return None,
+    };
+    let parameters = (0..fields.number()?)
+        .map(|_| {
+            Some(Parameter {
+                type_name: fields.string()?,
+                name: fields.option()?,
+                default: fields.option()?,
+                is_pack: fields.flag()?,
+            })
+        })
+        .collect::<Option<_>>()?;
+    let is_variadic = fields.flag()?;
+    let trailing_qualifiers = fields.list()?;
+    let special_definition = match fields.option()?.as_deref() {
+        None => None,
+        Some("Default") => Some(SpecialDefinition::Default),
+        Some("Delete") => Some(SpecialDefinition::Delete),
+        Some(_) => return None,
+    };
+    let namespace = fields.option()?;
+    let enclosing_type = fields.option()?;
+    let linkage = match fields.string()?.as_str() {
+        "Cpp" => Linkage::Cpp,
+        "C" => Linkage::C,
+        _ => return None,
+    };
+    let calling_convention = fields.option()?;
+    let line = fields.number()?;
+    let column = fields.number()?;
+    let access = match fields.option()?.as_deref() {
+        None => None,
+        Some("Public") => Some(Access::Public),
+        Some("Protected") => Some(Access::Protected),
+        Some("Private") => Some(Access::Private),
+        Some(_) => return None,
+    };
+    let byte_span = fields.number()?..fields.number()?;
+    let attributes = fields.list()?;
+    let deprecated = fields.option()?;
+    let is_definition = fields.flag()?;
+    let doc = fields.option()?;
+    if fields.0.next().is_some() {
+        return None;
+    }
+    Some(FunctionDetail {
+        template_prefix,
+        specifiers,
+        return_type,
+        has_trailing_return,
+        is_deduced,
+        name,
+        kind,
+        parameters,
+        is_variadic,
+        trailing_qualifiers,
+        special_definition,
+        namespace,
+        enclosing_type,
+        linkage,
+        calling_convention,
+        line,
+        column,
+        path: Some(path.to_owned()),
+        access,
+        byte_span,
+        attributes,
+        deprecated,
+        is_definition,
+        doc,
+    })
+}
+
+// Collects the fields of a cache line. Tabs, newlines and backslashes in strings are
+// escaped, and `\0`, which escaping never produces, stands for `None`.
+#[derive(Default)]
+struct FieldWriter(Vec<String>);
+
+impl FieldWriter {
+    fn string(&mut self, value: &str) {
+        let mut escaped = String::with_capacity(value.len());
+        for c in value.chars() {
+            match c {
+                '\\' => escaped.push_str("\\\\"),
+                '\t' => escaped.push_str("\\t"),
+                '\n' => escaped.push_str("\\n"),
+                '\r' => escaped.push_str("\\r"),
+                c => escaped.push(c),
+            }
+        }
+        self.0.push(escaped);
+    }
+
+    fn option(&mut self, value: Option<&str>) {
+        match value {
+            Some(value) => self.string(value),
+            None => self.0.push("\\0".to_owned()),
+        }
+    }
+
+    fn list(&mut self, values: &[String]) {
+        self.number(values.len());
+        for value in values {
+            self.string(value);
+        }
+    }
+
+    fn number(&mut self, value: usize) {
+        self.0.push(value.to_string());
+    }
+
+    fn flag(&mut self, value: bool) {
+        self.0.push(if value { "1" } else { "0" }.to_owned());
+    }
+}
+
+// Reads back the fields written by a FieldWriter, returning `None` for a missing or
+// malformed field.
+struct FieldReader<'a>(std::str::Split<'a, char>);
+
+impl FieldReader<'_> {
+    fn string(&mut self) -> Option<String> {
+        let field = self.0.next()?;
+        let mut value = String::with_capacity(field.len());
+        let mut chars = field.chars();
+        while let Some(c) = chars.next() {
+            value.push(match c {
+                '\\' => match chars.next()? {
+                    '\\' => '\\',
+                    't' => '\t',
+                    'n' => '\n',
+                    'r' => '\r',
+                    _ => return None,
+                },
+                c => c,
+            });
+        }
+        Some(value)
+    }
+
+    fn option(&mut self) -> Option<Option<String>> {
+        if self.0.clone().next()? == "\\0" {
+            self.0.next();
+            return Some(None);
+        }
+        self.string().map(Some)
+    }
+
+    fn list(&mut self) -> Option<Vec<String>> {
+        (0..self.number()?).map(|_| self.string()).collect()
+    }
+
+    fn number(&mut self) -> Option<usize> {
+        self.0.next()?.parse().ok()
+    }
+
+    fn flag(&mut self) -> Option<bool> {
+        match self.0.next()? {
+            "1" => Some(true),
+            "0" => Some(false),
+            _ => None,
+        }
+    }
+}
+
+/// Writes `details` as a pretty-printed JSON document grouped by source file:
+/// `{"files": [{"path": ..., "functions": [...]}]}`. Files are sorted by path, with
+/// functions parsed from a string (`path` of `None`) first under a `null` path, and
+/// functions keep their order within a file. Each function is an object with one key
+/// per [`FunctionDetail`] field in declaration order, enums written as their variant
+/// name, so the output is stable and matches what the `serde` feature produces.
+pub fn to_json_report(details: &[FunctionDetail]) -> String {
+    let mut files: BTreeMap<Option<&Path>, Vec<Json>> = BTreeMap::new();
+    for detail in details {
+        files
+            .entry(detail.path.as_deref())
+            .or_default()
+            .push(function_json(detail));
+    }
+    let files = files
+        .into_iter()
+        .map(|(path, functions)| {
+            Json::Object(vec![
+                ("path", Json::path(path)),
+                ("functions", Json::Array(functions)),
+            ])
+        })
+        .collect();
+    let mut out = String::new();
+    Json::Object(vec![("files", Json::Array(files))]).write(&mut out, 0);
+    out.push('\n');
+    out
+}
+
+/// Writes `signatures` as a pretty-printed JSON array, each function laid out as in
+/// [`to_json_report`].
+pub fn to_json(signatures: &[FunctionSignature]) -> String {
+    let mut out = String::new();
+    Json::Array(signatures.iter().map(function_json).collect()).write(&mut out, 0);
+    out.push('\n');
+    out
+}
+
+// Lays out a FunctionDetail the way its serde derive does.
+fn function_json(detail: &FunctionDetail) -> Json {
+    let parameters = detail
+        .parameters
+        .iter()
+        .map(|parameter| {
+            Json::Object(vec![
+                ("type_name", Json::string(&parameter.type_name)),
+                ("name", Json::option(parameter.name.as_deref())),
+                ("default", Json::option(parameter.default.as_deref())),
+                ("is_pack", Json::Bool(parameter.is_pack)),
+            ])
+        })
+        .collect();
+    Json::Object(vec![
+        ("template_prefix", Json::string(&detail.template_prefix)),
+        ("specifiers", Json::strings(&detail.specifiers)),
+        ("return_type", Json::string(&detail.return_type)),
+        (
+            "has_trailing_return",
+            Json::Bool(detail.has_trailing_return),
+        ),
+        ("is_deduced", Json::Bool(detail.is_deduced)),
+        ("name", Json::string(&detail.name)),
+        ("kind", Json::variant(detail.kind)),
+        ("parameters", Json::Array(parameters)),
+        ("is_variadic", Json::Bool(detail.is_variadic)),
+        (
+            "trailing_qualifiers",
+            Json::strings(&detail.trailing_qualifiers),
+        ),
+        (
+            "special_definition",
+            detail.special_definition.map_or(Json::Null, Json::variant),
+        ),
+        ("namespace", Json::option(detail.namespace.as_deref())),
+        (
+            "enclosing_type",
+            Json::option(detail.enclosing_type.as_deref()),
+        ),
+        ("linkage", Json::variant(detail.linkage)),
+        (
+            "calling_convention",
+            Json::option(detail.calling_convention.as_deref()),
+        ),
+        ("line", Json::Number(detail.line)),
+        ("column", Json::Number(detail.column)),
+        ("path", Json::path(detail.path.as_deref())),
+        ("access", detail.access.map_or(Json::Null, Json::variant)),
+        (
+            "byte_span",
+            Json::Object(vec![
+                ("start", Json::Number(detail.byte_span.start)),
+                ("end", Json::Number(detail.byte_span.end)),
+            ]),
+        ),
+        ("attributes", Json::strings(&detail.attributes)),
+        ("deprecated", Json::option(detail.deprecated.as_deref())),
+        ("is_definition", Json::Bool(detail.is_definition)),
+        ("doc", Json::option(detail.doc.as_deref())),
+    ])
+}
+
+// A JSON value, just enough to write reports without a serializer dependency.
+enum Json {
+    Null,
+    Bool(bool),
+    Number(usize),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(&'static str, Json)>),
+}
+
+impl Json {
+    fn string(value: &str) -> Self {
+        Json::String(value.to_owned())
+    }
+
+    fn option(value: Option<&str>) -> Self {
+        value.map_or(Json::Null, Json::string)
+    }
+
+    fn strings(values: &[String]) -> Self {
+        Json::Array(values.iter().map(|value| Json::string(value)).collect())
+    }
+
+    fn path(path: Option<&Path>) -> Self {
+        path.map_or(Json::Null, |path| Json::string(&path.to_string_lossy()))
+    }
+
+    // A unit enum variant, written as its name.
+    fn variant(value: impl Debug) -> Self {
+        Json::String(format!("{:?}", value))
+    }
+
+    // Writes the value with two-space indentation, `indent` levels deep. Empty arrays
+    // and objects stay on one line.
+    fn write(&self, out: &mut String, indent: usize) {
+        match self {
+            Json::Null => out.push_str("null"),
+            Json::Bool(value) => out.push_str(if *value { "true" } else { "false" }),
+            Json::Number(value) => out.push_str(&value.to_string()),
+            Json::String(value) => write_json_string(out, value),
+            Json::Array(items) if items.is_empty() => out.push_str("[]"),
+            Json::Object(fields) if fields.is_empty() => out.push_str("{}"),
+            Json::Array(items) => {
+                out.push('[');
+                for (i, item) in items.iter().enumerate() {
+                    out.push_str(if i == 0 { "\n" } else { ",\n" });
+                    push_indent(out, indent + 1);
+                    item.write(out, indent + 1);
+                }
+                out.push('\n');
+                push_indent(out, indent);
+                out.push(']');
+            }
+            Json::Object(fields) => {
+                out.push('{');
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    out.push_str(if i == 0 { "\n" } else { ",\n" });
+                    push_indent(out, indent + 1);
+                    write_json_string(out, key);
+                    out.push_str(": ");
+                    value.write(out, indent + 1);
+                }
+                out.push('\n');
+                push_indent(out, indent);
+                out.push('}');
+            }
+        }
+    }
+}
+
+// Indents a line of JSON output by `indent` levels.
+fn push_indent(out: &mut String, indent: usize) {
+    out.extend(std::iter::repeat_n("  ", indent));
+}
+
+// Writes `value` as a quoted JSON string, escaping quotes, backslashes and control
+// characters.
+fn write_json_string(out: &mut String, value: &str) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{c}' => out.push_str("\\f"),
+            c if c < ' ' => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+// The functions of one header and the declarations skipped in it.
+type ParsedHeader = (Vec<FunctionDetail>, Vec<ParseDiagnostic>);
+
+// Reads and parses each header on a pool of scoped threads, one per available CPU,
+// treating `extra_macros` as attribute macros. The results are in the order of
+// `headers`, with each function's path set.
+fn parse_headers(headers: &[PathBuf], extra_macros: &[&str]) -> Vec<Result<ParsedHeader>> {
+    let next = AtomicUsize::new(0);
+    let workers = thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(headers.len())
+        .max(1);
+    let mut results: Vec<Option<Result<ParsedHeader>>> = headers.iter().map(|_| None).collect();
+    thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut parsed = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(header) = headers.get(index) else {
+                            break;
+                        };
+                        let details = read_source(header).map(|src| {
+                            let (details, diagnostics) = scan_checked(&src, extra_macros);
+                            let details = details
+                                .into_iter()
+                                .map(|detail| FunctionDetail {
+                                    path: Some(header.clone()),
+                                    ..detail
+                                })
+                                .collect();
+                            (details, diagnostics)
+                        });
+                        parsed.push((index, details));
+                    }
+                    parsed
+                })
+            })
+            .collect();
+        for handle in handles {
+            for (index, details) in handle.join().expect("header parser panicked") {
+                results[index] = Some(details);
+            }
+        }
+    });
+    results.into_iter().flatten().collect()
+}
+
+// Writes the Rust declarations for the generated wrappers to RUST_BIND_FILE.
+fn generate_rust_bindings() -> Result<()> {
+    let rust_path = &*RUST_BIND_FILE;
+    let mut writer = BufWriter::new(File::create(rust_path)?);
+    emit_rust_bindings(
+        &header_details(&INCLUDE_DIR)?,
+        skip_deprecated(),
+        &mut writer,
+    )?;
+    log_info(format_args!("Generated Rust bindings at: {:?}", rust_path));
+    Ok(())
+}
+
+// Writes every function declared in the gathered headers to SIGNATURES_FILE_NAME in
+// the external directory, as JSON for tools outside Rust.
+fn generate_signatures_json() -> Result<()> {
+    let json_path = EXTERNAL_DIR.join(SIGNATURES_FILE_NAME);
+    fs::write(&json_path, to_json(&header_details(&INCLUDE_DIR)?))?;
+    log_info(format_args!("Generated signatures at: {:?}", json_path));
+    Ok(())
+}
+
+// Returns true when ASTD_EMIT_JSON=1 asks for SIGNATURES_FILE_NAME to be generated.
+fn emit_json() -> bool {
+    println!("cargo:rerun-if-env-changed=ASTD_EMIT_JSON");
+    env::var("ASTD_EMIT_JSON").is_ok_and(|value| value == "1")
+}
+
+// Returns true when ASTD_EMIT_RUST=1 asks for RUST_BIND_FILE to be generated.
+fn emit_rust() -> bool {
+    println!("cargo:rerun-if-env-changed=ASTD_EMIT_RUST");
+    env::var("ASTD_EMIT_RUST").is_ok_and(|value| value == "1")
+}
+
+// Returns the macros named in the comma-separated ASTD_STRIP_MACROS, to be stripped
+// from declarations along with ATTRIBUTE_MACROS.
+fn strip_macros() -> Vec<String> {
+    println!("cargo:rerun-if-env-changed=ASTD_STRIP_MACROS");
+    env::var("ASTD_STRIP_MACROS")
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+// Returns true when ASTD_SKIP_DEPRECATED=1 asks for deprecated functions to get no
+// wrapper.
+fn skip_deprecated() -> bool {
+    println!("cargo:rerun-if-env-changed=ASTD_SKIP_DEPRECATED");
+    env::var("ASTD_SKIP_DEPRECATED").is_ok_and(|value| value == "1")
+}
+
+// Collects the paths of the files under `dir` with one of the given extensions.
+fn collect_headers(dir: &Path, extensions: &[&str], headers: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_headers(&path, extensions, headers)?;
+        } else if path
+            .extension()
+            .and_then(|s| s.to_str())
+            .is_some_and(|ext| extensions.iter().any(|e| ext.eq_ignore_ascii_case(e)))
+        {
+            headers.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Writes an `extern "C"` wrapper for every function in `details` that can be called
+/// through a C signature, and a comment explaining why each other function was skipped.
+/// Wrapper names are derived from the qualified name; overloads are told apart with
+/// [`mangle_overload_name`], and a numeric suffix is added when a name is still taken,
+/// so the output is deterministic for the same input. With `skip_deprecated`, deprecated
+/// functions are skipped too.
+pub fn emit_wrappers(
+    details: &[FunctionDetail],
+    skip_deprecated: bool,
+    writer: &mut impl Write,
+) -> Result<()> {
+    for (_, qualified, planned) in plan_wrappers(details, skip_deprecated) {
+        let (wrapper, signature) = match planned {
+            Ok(planned) => planned,
+            Err(reason) if reason == "deprecated" => {
+                writeln!(writer, "// skipped (deprecated): {}", qualified)?;
+                continue;
+            }
+            Err(reason) => {
+                writeln!(writer, "// skipped {}: {}", qualified, reason)?;
+                continue;
+            }
+        };
+        let mut params: Vec<String> = signature
+            .params
+            .iter()
+            .map(|param| {
+                if param.c_type.needs_length {
+                    format!(
+                        "{} {}, size_t {}_len",
+                        param.c_type.spelling, param.name, param.name
+                    )
+                } else {
+                    format!("{} {}", param.c_type.spelling, param.name)
+                }
+            })
+            .collect();
+        let call = format!("{}({})", qualified, signature.args.join(", "));
+        let (return_type, body) = match signature.ret {
+            None => ("void".to_owned(), format!("{};", call)),
+            Some(ret) if ret.needs_length => {
+                params.push("size_t* out_len".to_owned());
+                (
+                    ret.spelling,
+                    format!(
+                        "auto result = {}; *out_len = result.size(); return result.data();",
+                        call
+                    ),
+                )
+            }
+            Some(ret) => (ret.spelling, format!("return {};", call)),
+        };
+        writeln!(
+            writer,
+            "{} {}({}) {{ {} }}",
+            return_type,
+            wrapper,
+            params.join(", "),
+            body
+        )?;
+    }
+    Ok(())
+}
 
-// Extracts function details: (template, return type, name)
-pub fn extract_function_details(src: &str) -> Vec<(String, String, String)> {
-    let mut results = Vec::new();
-    for cap in FUNC_REGEX.captures_iter(src) {
-        results.push((
-            cap.get(1).map_or("", |m| m.as_str()).trim().to_owned(),
-            cap.get(2).unwrap().as_str().trim().to_owned(),
-            cap.get(3).unwrap().as_str().trim().to_owned(),
-        ));
+/// Writes the Rust side of the wrappers [`emit_wrappers`] produces for `details`: an
+/// `extern "C"` block declaring each wrapper, followed by a safe function for each
+/// wrapper that takes and returns only scalars and strings, documented with the
+/// declaration's doc comment. Deprecated functions keep their note as `#[deprecated]`.
+pub fn emit_rust_bindings(
+    details: &[FunctionDetail],
+    skip_deprecated: bool,
+    writer: &mut impl Write,
+) -> Result<()> {
+    let wrappers: Vec<_> = plan_wrappers(details, skip_deprecated)
+        .into_iter()
+        .filter_map(|(detail, _, planned)| Some((detail, planned.ok()?)))
+        .collect();
+    writeln!(
+        writer,
+        "// Auto-generated: Rust declarations for bindings.cpp"
+    )?;
+    writeln!(writer, "#![allow(non_snake_case, dead_code)]")?;
+    writeln!(writer)?;
+    writeln!(writer, "unsafe extern \"C\" {{")?;
+    for (detail, (wrapper, signature)) in &wrappers {
+        let mut params = Vec::new();
+        for param in &signature.params {
+            let name = rust_identifier(&param.name);
+            params.push(format!("{}: {}", name, map_cpp_type_to_rust(&param.c_type)));
+            if param.c_type.needs_length {
+                params.push(format!("{}_len: usize", param.name));
+            }
+        }
+        let ret = match &signature.ret {
+            None => String::new(),
+            Some(ret) => {
+                if ret.needs_length {
+                    params.push("out_len: *mut usize".to_owned());
+                }
+                format!(" -> {}", map_cpp_type_to_rust(ret))
+            }
+        };
+        if let Some(attribute) = deprecated_attribute(detail) {
+            writeln!(writer, "    {}", attribute)?;
+        }
+        writeln!(
+            writer,
+            "    pub fn {}({}){};",
+            wrapper,
+            params.join(", "),
+            ret
+        )?;
+    }
+    writeln!(writer, "}}")?;
+    for (detail, (wrapper, signature)) in &wrappers {
+        // Only values that need no pointer handling get a safe wrapper.
+        let is_pointer = |c_type: &CType| !c_type.needs_length && c_type.spelling.ends_with('*');
+        if signature
+            .params
+            .iter()
+            .any(|param| is_pointer(&param.c_type))
+            || signature
+                .ret
+                .as_ref()
+                .is_some_and(|ret| ret.needs_length || is_pointer(ret))
+        {
+            continue;
+        }
+        let mut params = Vec::new();
+        let mut args = Vec::new();
+        for param in &signature.params {
+            let name = rust_identifier(&param.name);
+            if param.c_type.needs_length {
+                params.push(format!("{}: &str", name));
+                args.push(format!("{}.as_ptr().cast(), {}.len()", name, name));
+            } else {
+                params.push(format!("{}: {}", name, map_cpp_type_to_rust(&param.c_type)));
+                args.push(name);
+            }
+        }
+        let ret = signature.ret.as_ref().map_or(String::new(), |ret| {
+            format!(" -> {}", map_cpp_type_to_rust(ret))
+        });
+        writeln!(writer)?;
+        for line in detail.doc.iter().flat_map(|doc| doc.lines()) {
+            writeln!(
+                writer,
+                "///{}{}",
+                if line.is_empty() { "" } else { " " },
+                line
+            )?;
+        }
+        if let Some(attribute) = deprecated_attribute(detail) {
+            writeln!(writer, "{}", attribute)?;
+        }
+        writeln!(
+            writer,
+            "pub fn {}({}){} {{",
+            wrapper.strip_suffix("_wrapper").unwrap_or(wrapper),
+            params.join(", "),
+            ret
+        )?;
+        writeln!(writer, "    unsafe {{ {}({}) }}", wrapper, args.join(", "))?;
+        writeln!(writer, "}}")?;
     }
-    results
+    Ok(())
 }
 
-static CONFIG_FLAGS: LazyLock<Mutex<Vec<&'static str>>> = LazyLock::new(|| Mutex::new(Vec::new()));
-static COMPILE_FLAGS: LazyLock<Mutex<Vec<&'static str>>> = LazyLock::new(|| Mutex::new(Vec::new()));
+// Spells the Rust `#[deprecated]` attribute of a deprecated function, escaping the note
+// as a Rust string literal.
+fn deprecated_attribute(detail: &FunctionDetail) -> Option<String> {
+    detail.deprecated.as_deref().map(|note| {
+        if note.is_empty() {
+            "#[deprecated]".to_owned()
+        } else {
+            format!("#[deprecated = {:?}]", note)
+        }
+    })
+}
 
-macro_rules! define_lazy_path {
-    ($name:ident, $path:expr) => {
-        static $name: LazyLock<PathBuf> = LazyLock::new(|| PathBuf::from($path));
-    };
+// Decides the wrapper for each function, in order: the function, its qualified name and
+// either the wrapper name and C signature, or the reason it has no wrapper.
+#[allow(clippy::type_complexity)]
+fn plan_wrappers(
+    details: &[FunctionDetail],
+    skip_deprecated: bool,
+) -> Vec<(
+    &FunctionDetail,
+    String,
+    std::result::Result<(String, CSignature), String>,
+)> {
+    let signatures: Vec<_> = details
+        .iter()
+        .enumerate()
+        .map(|(i, detail)| {
+            let signature = if details[..i].iter().any(|d| d.same_signature(detail)) {
+                Err("duplicate declaration".to_owned())
+            } else if skip_deprecated && detail.is_deprecated() {
+                Err("deprecated".to_owned())
+            } else {
+                c_signature(detail)
+            };
+            (detail, qualified_name(detail), signature)
+        })
+        .collect();
+    let mut overloads: HashMap<String, usize> = HashMap::new();
+    for (_, qualified, signature) in &signatures {
+        if signature.is_ok() {
+            *overloads.entry(qualified.clone()).or_default() += 1;
+        }
+    }
+    let mut used = HashSet::new();
+    let mut planned = Vec::new();
+    for (detail, qualified, signature) in signatures {
+        let signature = match signature {
+            Ok(signature) => signature,
+            Err(reason) => {
+                planned.push((detail, qualified, Err(reason)));
+                continue;
+            }
+        };
+        let base = if overloads[&qualified] > 1 {
+            mangle_overload_name(detail)
+        } else {
+            format!("{}_wrapper", qualified.replace("::", "_"))
+        };
+        let mut wrapper = base.clone();
+        let mut suffix = 2;
+        while !used.insert(wrapper.clone()) {
+            wrapper = format!("{}_{}", base, suffix);
+            suffix += 1;
+        }
+        planned.push((detail, qualified, Ok((wrapper, signature))));
+    }
+    planned
 }
-macro_rules! add_flag {
-    ($flags:expr, $flag:expr) => {{
-        $flags.lock().unwrap().push($flag);
-    }};
+
+// Escapes a C++ parameter name that is a Rust keyword.
+fn rust_identifier(name: &str) -> String {
+    const KEYWORDS: &[&str] = &[
+        "as", "box", "crate", "dyn", "fn", "impl", "in", "let", "loop", "match", "mod", "move",
+        "mut", "pub", "ref", "self", "Self", "super", "trait", "type", "unsafe", "use", "where",
+        "async", "await", "gen", "yield",
+    ];
+    if KEYWORDS.contains(&name) {
+        format!("{}_", name)
+    } else {
+        name.to_owned()
+    }
 }
 
-static MINIMUM_GIT_VERSION: [u8; 3] = [2, 40, 0];
-static MINIMUM_CMAKE_VERSION: [u8; 3] = [3, 31, 0];
-const ABSEIL_SRC: &str = "https://github.com/abseil/abseil-cpp.git";
+/// Returns the wrapper name for one overload of a function: the qualified name followed
+/// by its parameter types, e.g. `absl_Append_int_float_wrapper` for
+/// `absl::Append(int, float)`. Functions without parameters get `_void`. Member
+/// functions differing only in qualifiers are told apart by a `const`, `lref` or `rref`
+/// suffix.
+pub fn mangle_overload_name(detail: &FunctionSignature) -> String {
+    let mut parts = vec![qualified_name(detail).replace("::", "_")];
+    if detail.parameters.is_empty() {
+        parts.push("void".to_owned());
+    }
+    for param in &detail.parameters {
+        let spelled = param
+            .type_name
+            .replace("&&", " rref ")
+            .replace('&', " ref ")
+            .replace('*', " ptr ");
+        let words: Vec<&str> = spelled
+            .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .filter(|word| !word.is_empty())
+            .collect();
+        parts.push(words.join("_"));
+    }
+    if detail.is_const() {
+        parts.push("const".to_owned());
+    }
+    match detail.ref_qualifier() {
+        Some(RefQualifier::LValue) => parts.push("lref".to_owned()),
+        Some(RefQualifier::RValue) => parts.push("rref".to_owned()),
+        None => {}
+    }
+    parts.push("wrapper".to_owned());
+    parts.join("_")
+}
+
+// The C side of a wrapper: its parameters, the C++ argument expression for each
+// original parameter, and the C return type (`None` for `void`).
+struct CSignature {
+    params: Vec<CParam>,
+    args: Vec<String>,
+    ret: Option<CType>,
+}
+
+// One wrapper parameter. A string (`needs_length`) is followed by a `{name}_len` length.
+struct CParam {
+    c_type: CType,
+    name: String,
+}
+
+// Returns the fully qualified name of a function.
+fn qualified_name(detail: &FunctionDetail) -> String {
+    let scopes = [&detail.namespace, &detail.enclosing_type];
+    let mut parts: Vec<&str> = scopes.into_iter().flatten().map(String::as_str).collect();
+    parts.push(&detail.name);
+    parts.join("::")
+}
 
-define_lazy_path!(BUILD_DIR, "target/");
-define_lazy_path!(ABSEIL_BUILD_DIR, "target/abseil-cpp/build/");
-define_lazy_path!(SOURCE_DIR, "target/abseil-cpp/absl/");
-define_lazy_path!(BIND_FILE, "external/bindings.cpp");
-define_lazy_path!(INCLUDE_DIR, "external/include/");
-define_lazy_path!(LIB_DIR, "external/lib/");
-
-// Sets build flags.
-fn build_flags() {
-    add_flag!(CONFIG_FLAGS, "-DABSL_USE_GOOGLETEST_HEAD=ON");
-    add_flag!(CONFIG_FLAGS, "-DCMAKE_CXX_STANDARD_REQUIRED=ON");
-    add_flag!(CONFIG_FLAGS, "-DCMAKE_CXX_STANDARD=20");
-    #[cfg(debug_assertions)]
-    add_flag!(CONFIG_FLAGS, "-DCMAKE_BUILD_TYPE=Debug");
-    #[cfg(not(debug_assertions))]
-    add_flag!(CONFIG_FLAGS, "-DCMAKE_BUILD_TYPE=Release");
-    #[cfg(all(target_os = "windows", target_env = "msvc"))]
+// Builds the C signature of a wrapper, or explains why the function cannot have one.
+fn c_signature(detail: &FunctionDetail) -> std::result::Result<CSignature, String> {
+    if detail.special_definition == Some(SpecialDefinition::Delete) {
+        return Err("deleted function".to_owned());
+    }
+    if detail.is_friend() {
+        return Err("friend declaration".to_owned());
+    }
+    if !detail.is_public() {
+        return Err("non-public member function".to_owned());
+    }
+    // A wrapper only ever has an lvalue to call a member function on.
+    if detail.ref_qualifier() == Some(RefQualifier::RValue) {
+        return Err("rvalue ref-qualified member function".to_owned());
+    }
+    if detail.enclosing_type.is_some() {
+        return Err("member function".to_owned());
+    }
+    if detail.linkage == Linkage::C {
+        return Err("already has C linkage".to_owned());
+    }
+    if matches!(
+        detail.kind,
+        FunctionKind::Operator | FunctionKind::Conversion
+    ) {
+        return Err("operator".to_owned());
+    }
+    if detail.is_templated() {
+        return Err("function template".to_owned());
+    }
+    if detail.is_deduced {
+        return Err("deduced return type".to_owned());
+    }
+    if detail.is_variadic {
+        return Err("C variadic arguments cannot be forwarded".to_owned());
+    }
+    if detail.parameters.iter().any(|param| param.is_pack) {
+        return Err("parameter pack".to_owned());
+    }
+    if detail
+        .namespace
+        .as_deref()
+        .is_some_and(|ns| ns.split("::").any(|part| part == ANONYMOUS_NAMESPACE))
     {
-        add_flag!(CONFIG_FLAGS, "-DABSL_MSVC_STATIC_RUNTIME=ON");
-        add_flag!(COMPILE_FLAGS, "--build");
-        add_flag!(COMPILE_FLAGS, ".");
-        add_flag!(COMPILE_FLAGS, "--");
-        add_flag!(COMPILE_FLAGS, "/p:Platform=x64");
-        #[cfg(debug_assertions)]
-        add_flag!(COMPILE_FLAGS, "/p:Configuration=Debug");
-        #[cfg(not(debug_assertions))]
-        add_flag!(COMPILE_FLAGS, "/p:Configuration=Release");
+        return Err("internal linkage".to_owned());
     }
+    let ret = match detail.return_type.as_str() {
+        "void" => None,
+        ty => match TypeShape::parse(ty) {
+            Some(shape) if shape.reference => {
+                return Err(format!("returns a reference `{}`", ty));
+            }
+            Some(shape)
+                if shape.pointers == 0
+                    && !is_view_type(&shape.base)
+                    && is_string_type(&shape.base) =>
+            {
+                return Err(format!("returns an owning string `{}`", ty));
+            }
+            _ => Some(map_cpp_type_to_c(ty).ok_or_else(|| format!("returns non-C type `{}`", ty))?),
+        },
+    };
+    let mut params = Vec::new();
+    let mut args = Vec::new();
+    for (i, param) in detail.parameters.iter().enumerate() {
+        let name = param.name_or_placeholder(i);
+        let ty = &param.type_name;
+        let (Some(c_type), Some(shape)) = (map_cpp_type_to_c(ty), TypeShape::parse(ty)) else {
+            return Err(format!("takes non-C type `{}`", ty));
+        };
+        if c_type.spelling == "void" {
+            return Err(format!("takes non-C type `{}`", ty));
+        }
+        if c_type.needs_length {
+            args.push(format!("{}({}, {}_len)", shape.base, name, name));
+        } else if shape.reference && !shape.is_const {
+            // A mutable reference becomes a pointer the wrapper dereferences.
+            args.push(format!("*{}", name));
+        } else {
+            args.push(name.clone());
+        }
+        params.push(CParam { c_type, name });
+    }
+    Ok(CSignature { params, args, ret })
 }
 
-// Creates a directory if it doesn't exist.
-// Logs any error and continues.
-fn create_path(path: &Path) {
-    if !path.exists() {
-        if let Err(err) = fs::create_dir_all(path) {
-            eprintln!("Failed to create path {:?}: {}", path, err);
+/// A C-ABI-safe spelling for a C++ type, as chosen by [`map_cpp_type_to_c`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CType {
+    /// How the type is written in C, e.g. `const char*`.
+    pub spelling: String,
+    /// Whether the value is passed as a pointer plus a companion `size_t` length,
+    /// as strings and string views are.
+    pub needs_length: bool,
+}
+
+impl CType {
+    fn new(spelling: impl Into<String>) -> Self {
+        CType {
+            spelling: spelling.into(),
+            needs_length: false,
         }
     }
 }
 
-// Recursively copies header files; logs errors and continues.
-fn visit_dirs(src_dir: &Path, dest_dir: &Path, base: &Path) {
-    let entries = fs::read_dir(src_dir).unwrap_or_else(|err| {
-        eprintln!("Failed to read directory {:?}: {}", src_dir, err);
-        // Return an empty iterator on error.
-        fs::read_dir("/dev/null").unwrap()
-    });
-    for entry in entries {
-        match entry {
-            Ok(entry) => {
-                let path = entry.path();
-                if path.is_dir() {
-                    visit_dirs(&path, dest_dir, base);
-                } else if path.extension().and_then(|s| s.to_str()) == Some("h") {
-                    let dest_file_path = dest_dir.join(path.strip_prefix(base).unwrap());
-                    if let Some(parent) = dest_file_path.parent() {
-                        if let Err(err) = fs::create_dir_all(parent) {
-                            eprintln!("Failed to create directory {:?}: {}", parent, err);
-                            continue;
-                        }
-                    }
-                    if let Err(err) = fs::copy(&path, &dest_file_path) {
-                        eprintln!(
-                            "Failed to copy file {:?} to {:?}: {}",
-                            path, dest_file_path, err
-                        );
-                    }
-                }
+// Scalar types that have the same meaning in C and C++.
+const C_SCALAR_TYPES: &[&str] = &[
+    "void",
+    "bool",
+    "char",
+    "signed char",
+    "unsigned char",
+    "short",
+    "unsigned short",
+    "int",
+    "unsigned",
+    "unsigned int",
+    "long",
+    "unsigned long",
+    "long long",
+    "unsigned long long",
+    "float",
+    "double",
+    "size_t",
+    "ptrdiff_t",
+    "intptr_t",
+    "uintptr_t",
+    "int8_t",
+    "int16_t",
+    "int32_t",
+    "int64_t",
+    "uint8_t",
+    "uint16_t",
+    "uint32_t",
+    "uint64_t",
+];
+
+// String types passed to C as a pointer and a length.
+const STRING_TYPES: &[&str] = &[
+    "std::string",
+    "absl::string_view",
+    "std::string_view",
+    "string_view",
+];
+
+// Returns true if `base` is one of the string types in `STRING_TYPES`.
+fn is_string_type(base: &str) -> bool {
+    STRING_TYPES.contains(&base)
+}
+
+// Returns true if `base` is a non-owning string view.
+fn is_view_type(base: &str) -> bool {
+    base.ends_with("string_view")
+}
+
+/// A type split by [`parse_type`] into its base and its outermost qualifiers.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TypeInfo {
+    /// The type with the reference, the outermost `*` and their cv-qualifiers removed:
+    /// `std::vector<int>` for `const std::vector<int>&`, `const char*` for
+    /// `const char**`.
+    pub base: String,
+    /// Whether the value, or what the pointer or reference refers to, is `const`,
+    /// whichever side of the base it is written on.
+    pub is_const: bool,
+    /// Whether the type is a pointer to `base`.
+    pub is_pointer: bool,
+    /// Whether the pointer itself is `const`, as in `T* const`.
+    pub is_const_pointer: bool,
+    /// Whether the type is an lvalue reference, `T&`.
+    pub is_reference: bool,
+    /// Whether the type is an rvalue reference, `T&&`.
+    pub is_rvalue_ref: bool,
+}
+
+/// Splits a type such as `const T&`, `T const&` or `char* const` into a [`TypeInfo`].
+/// The reference and one level of pointer are peeled off the outside; anything inside
+/// template arguments, and deeper pointer levels, stay in `base` as written, with
+/// whitespace collapsed.
+pub fn parse_type(type_name: &str) -> TypeInfo {
+    let text = normalize_type(type_name);
+    let mut rest = text.trim();
+    let is_rvalue_ref = rest.ends_with("&&");
+    let is_reference = !is_rvalue_ref && rest.ends_with('&');
+    rest = rest.trim_end_matches('&').trim_end();
+    let mut is_pointer = false;
+    let mut is_const_pointer = false;
+    if let Some(star) = find_last_top_level(rest, '*') {
+        let (is_const, tail) = strip_cv(&rest[star + 1..], true);
+        if tail.is_empty() {
+            is_pointer = true;
+            is_const_pointer = is_const;
+            rest = rest[..star].trim_end();
+        }
+    }
+    // Past a further `*`, a leading `const` belongs to the type pointed to.
+    let leading = find_last_top_level(rest, '*').is_none();
+    let (is_const, base) = strip_cv(rest, leading);
+    TypeInfo {
+        base: base.to_owned(),
+        is_const,
+        is_pointer,
+        is_const_pointer,
+        is_reference,
+        is_rvalue_ref,
+    }
+}
+
+// Strips the `const` and `volatile` written after `text`, and with `leading` also those
+// before it. Returns whether one was `const`, and what is left.
+fn strip_cv(text: &str, leading: bool) -> (bool, &str) {
+    let mut rest = text.trim();
+    let mut is_const = false;
+    loop {
+        let before = rest;
+        for cv in ["const", "volatile"] {
+            if let Some(head) = rest.strip_suffix(cv)
+                && (head.is_empty() || !head.ends_with(|c: char| c.is_alphanumeric() || c == '_'))
+            {
+                is_const |= cv == "const";
+                rest = head.trim_end();
             }
-            Err(err) => eprintln!("Failed to process directory entry: {}", err),
+            if let Some(tail) = rest.strip_prefix(cv).filter(|_| leading)
+                && (tail.is_empty() || tail.starts_with(' '))
+            {
+                is_const |= cv == "const";
+                rest = tail.trim_start();
+            }
+        }
+        if rest == before {
+            return (is_const, rest);
         }
     }
 }
 
-// Generates C++ bindings; a failure here is critical.
-fn generate_bindings() -> Result<()> {
-    let headers_dir = &*INCLUDE_DIR;
-    let bindings_path = &*BIND_FILE;
-    let mut writer = BufWriter::new(File::create(bindings_path)?);
-    writeln!(writer, "// language: C++")?;
-    writeln!(
-        writer,
-        "// Auto-generated: includes from the external folder"
-    )?;
-    writeln!(writer, "#ifdef __cplusplus")?;
-    writeln!(writer, "extern \"C\" {{")?;
-    writeln!(writer, "#endif")?;
-    writeln!(writer)?;
-    generate_bind_includes(headers_dir, headers_dir, &mut writer)?;
-    writeln!(writer)?;
-    generate_bind_wrappers(headers_dir, &mut writer)?;
-    writeln!(writer)?;
-    writeln!(writer, "#ifdef __cplusplus")?;
-    writeln!(writer, "}}")?;
-    writeln!(writer, "#endif")?;
-    println!("Generated bindings at: {:?}", bindings_path);
-    Ok(())
+// A type split into its base name, cv-qualification, pointer depth and reference.
+struct TypeShape {
+    base: String,
+    is_const: bool,
+    pointers: usize,
+    reference: bool,
 }
 
-// Generates include directives; a failure here is critical.
-fn generate_bind_includes(
-    base_dir: &Path,
-    current_dir: &Path,
-    writer: &mut BufWriter<File>,
-) -> Result<()> {
-    for entry in fs::read_dir(current_dir)? {
-        let path = entry?.path();
-        if path.is_dir() {
-            generate_bind_includes(base_dir, &path, writer)?;
-        } else if path
-            .extension()
-            .and_then(|s| s.to_str())
-            .map_or(false, |ext| ext.eq_ignore_ascii_case("h"))
-        {
-            let include_path = path
-                .strip_prefix(base_dir)
-                .unwrap()
-                .to_string_lossy()
-                .replace("\\", "/");
-            writeln!(writer, "#include \"{}\"", include_path)?;
+impl TypeShape {
+    // Parses `const T*&`-style spellings; returns `None` for rvalue references.
+    fn parse(type_name: &str) -> Option<Self> {
+        let mut rest = type_name.trim();
+        if rest.ends_with("&&") {
+            return None;
+        }
+        let reference = rest.ends_with('&');
+        rest = rest.trim_end_matches('&').trim_end();
+        let stripped = rest.trim_end_matches(|c: char| c == '*' || c.is_whitespace());
+        let pointers = rest[stripped.len()..].matches('*').count();
+        let mut is_const = false;
+        let words: Vec<&str> = stripped
+            .split_whitespace()
+            .filter(|word| match *word {
+                "const" => {
+                    is_const = true;
+                    false
+                }
+                "volatile" => false,
+                _ => true,
+            })
+            .collect();
+        Some(TypeShape {
+            base: words.join(" "),
+            is_const,
+            pointers,
+            reference,
+        })
+    }
+}
+
+/// Maps a C++ type to a type that can cross an `extern "C"` boundary, or returns
+/// `None` when there is no safe mapping.
+///
+/// Scalars map to themselves with any `std::` prefix and top-level `const` dropped.
+/// Pointers to scalars keep their constness. A `const` reference to a scalar decays to
+/// the value and a mutable one to a pointer. `std::string` and the string views become
+/// `const char*` with a companion length. Rvalue references, and class types other than
+/// strings, have no mapping.
+pub fn map_cpp_type_to_c(type_name: &str) -> Option<CType> {
+    let shape = TypeShape::parse(&normalize_type(type_name))?;
+    let base = shape.base.strip_prefix("std::").unwrap_or(&shape.base);
+    if C_SCALAR_TYPES.contains(&base) {
+        let constness = if shape.is_const { "const " } else { "" };
+        return match (shape.pointers, shape.reference) {
+            (0, false) => Some(CType::new(base)),
+            (0, true) if base == "void" => None,
+            (0, true) if shape.is_const => Some(CType::new(base)),
+            (0, true) => Some(CType::new(format!("{}*", base))),
+            (pointers, false) => Some(CType::new(format!(
+                "{}{}{}",
+                constness,
+                base,
+                "*".repeat(pointers)
+            ))),
+            (_, true) => None,
+        };
+    }
+    // A mutable string reference is an out-parameter, which has no C equivalent here.
+    if is_string_type(&shape.base) && shape.pointers == 0 && (shape.is_const || !shape.reference) {
+        return Some(CType {
+            spelling: "const char*".to_owned(),
+            needs_length: true,
+        });
+    }
+    None
+}
+
+// Rust spellings of the scalar types in `C_SCALAR_TYPES`.
+const RUST_SCALAR_TYPES: &[(&str, &str)] = &[
+    ("void", "core::ffi::c_void"),
+    ("bool", "bool"),
+    ("char", "core::ffi::c_char"),
+    ("signed char", "core::ffi::c_schar"),
+    ("unsigned char", "core::ffi::c_uchar"),
+    ("short", "core::ffi::c_short"),
+    ("unsigned short", "core::ffi::c_ushort"),
+    ("int", "core::ffi::c_int"),
+    ("unsigned", "core::ffi::c_uint"),
+    ("unsigned int", "core::ffi::c_uint"),
+    ("long", "core::ffi::c_long"),
+    ("unsigned long", "core::ffi::c_ulong"),
+    ("long long", "core::ffi::c_longlong"),
+    ("unsigned long long", "core::ffi::c_ulonglong"),
+    ("float", "f32"),
+    ("double", "f64"),
+    ("size_t", "usize"),
+    ("ptrdiff_t", "isize"),
+    ("intptr_t", "isize"),
+    ("uintptr_t", "usize"),
+    ("int8_t", "i8"),
+    ("int16_t", "i16"),
+    ("int32_t", "i32"),
+    ("int64_t", "i64"),
+    ("uint8_t", "u8"),
+    ("uint16_t", "u16"),
+    ("uint32_t", "u32"),
+    ("uint64_t", "u64"),
+];
+
+/// Spells a [`CType`] from [`map_cpp_type_to_c`] as a Rust FFI type, e.g.
+/// `*const core::ffi::c_char` for `const char*`. `void` is `()` unless it is the target of
+/// a pointer. A string's companion length is not included; it is passed as `usize`.
+pub fn map_cpp_type_to_rust(c_type: &CType) -> String {
+    let spelling = c_type.spelling.trim();
+    let pointers = spelling.len() - spelling.trim_end_matches('*').len();
+    let base = spelling.trim_end_matches('*').trim_end();
+    let (is_const, base) = match base.strip_prefix("const ") {
+        Some(base) => (true, base.trim()),
+        None => (false, base),
+    };
+    if pointers == 0 && base == "void" {
+        return "()".to_owned();
+    }
+    let mut rust = RUST_SCALAR_TYPES
+        .iter()
+        .find(|(c, _)| *c == base)
+        .map_or(base, |(_, rust)| rust)
+        .to_owned();
+    for level in 0..pointers {
+        let mutability = if level == 0 && is_const {
+            "const"
+        } else {
+            "mut"
+        };
+        rust = format!("*{} {}", mutability, rust);
+    }
+    rust
+}
+
+/// How much the build script prints, from least to most.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    /// Only failures.
+    Error,
+    /// Failures and problems the build works around.
+    Warn,
+    /// Progress through the build steps, the default.
+    Info,
+    /// Every command run and every file copied, too.
+    Debug,
+}
+
+impl LogLevel {
+    /// Parses `error`, `warn`, `info` or `debug`, in any case.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "error" => Some(LogLevel::Error),
+            "warn" => Some(LogLevel::Warn),
+            "info" => Some(LogLevel::Info),
+            "debug" => Some(LogLevel::Debug),
+            _ => None,
+        }
+    }
+
+    /// The level named by ASTD_LOG, defaulting to `Info`. Panics on any other value.
+    pub fn from_env() -> Self {
+        println!("cargo:rerun-if-env-changed=ASTD_LOG");
+        match env::var("ASTD_LOG") {
+            Ok(value) if !value.trim().is_empty() => LogLevel::parse(&value).unwrap_or_else(|| {
+                panic!(
+                    "ASTD_LOG must be error, warn, info or debug, not {:?}",
+                    value
+                )
+            }),
+            _ => LogLevel::Info,
+        }
+    }
+}
+
+static LOG_LEVEL: LazyLock<LogLevel> = LazyLock::new(LogLevel::from_env);
+
+// Prints a failure on stderr.
+fn log_error(message: impl Display) {
+    if *LOG_LEVEL >= LogLevel::Error {
+        eprintln!("error: {}", message);
+    }
+}
+
+// Prints a problem the build works around on stderr.
+fn log_warn(message: impl Display) {
+    if *LOG_LEVEL >= LogLevel::Warn {
+        eprintln!("warning: {}", message);
+    }
+}
+
+// Prints build progress on stdout.
+fn log_info(message: impl Display) {
+    if *LOG_LEVEL >= LogLevel::Info {
+        println!("{}", message);
+    }
+}
+
+// Prints detailed tracing on stdout when ASTD_LOG=debug.
+fn log_debug(message: impl Display) {
+    if *LOG_LEVEL >= LogLevel::Debug {
+        println!("{}", message);
+    }
+}
+
+/// Runs `command` with `args` in `path` and returns its stdout. A command that cannot
+/// be started, or that exits unsuccessfully, is an error naming the command line, and
+/// in the second case its exit status and stderr.
+pub fn run_command(command: &str, args: &[&str], path: &Path) -> Result<String> {
+    let line = || {
+        [command]
+            .iter()
+            .chain(args)
+            .copied()
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    log_debug(format_args!("Running `{}` in {:?}", line(), path));
+    let output = Command::new(command)
+        .args(args)
+        .current_dir(path)
+        .output()
+        .map_err(|err| std::io::Error::new(err.kind(), format!("`{}`: {}", line(), err)))?;
+    if !output.status.success() {
+        return Err(std::io::Error::other(format!(
+            "`{}` failed with {}: {}",
+            line(),
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+// Git subcommands that talk to a remote, and so can fail on a network blip.
+const NETWORK_GIT_COMMANDS: &[&str] = &["clone", "fetch", "ls-remote", "pull"];
+// Delay before the first retry of a network command; it doubles for each later one.
+const NETWORK_BACKOFF: Duration = Duration::from_secs(2);
+
+/// Returns true if `command` with `args` talks to the network, which for now means a git
+/// clone, fetch, ls-remote or pull. Options before the subcommand, such as `-c key=value`,
+/// are skipped.
+pub fn is_network_command(command: &str, args: &[&str]) -> bool {
+    if Path::new(command)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        != Some("git")
+    {
+        return false;
+    }
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match *arg {
+            "-c" | "-C" | "--git-dir" | "--work-tree" => {
+                args.next();
+            }
+            option if option.starts_with('-') => {}
+            subcommand => return NETWORK_GIT_COMMANDS.contains(&subcommand),
+        }
+    }
+    false
+}
+
+/// Like [`run_command`], but retries a network command (see [`is_network_command`]) up
+/// to `retries` times when it fails, sleeping `backoff` before the first retry and twice
+/// as long before each later one. Other commands, and programs that cannot be started,
+/// are run once.
+pub fn run_command_with_retry(
+    command: &str,
+    args: &[&str],
+    path: &Path,
+    retries: usize,
+    backoff: Duration,
+) -> Result<String> {
+    let retries = if is_network_command(command, args) {
+        retries
+    } else {
+        0
+    };
+    let mut delay = backoff;
+    for attempt in 1.. {
+        match run_command(command, args, path) {
+            Err(err) if attempt <= retries && err.kind() != std::io::ErrorKind::NotFound => {
+                log_warn(format_args!(
+                    "Attempt {} of {} failed, retrying in {:?}: {}",
+                    attempt,
+                    retries + 1,
+                    delay,
+                    err
+                ));
+                thread::sleep(delay);
+                delay = delay.saturating_mul(2);
+            }
+            result => return result,
         }
     }
+    unreachable!("the attempts never run out")
+}
+
+// Returns how many times a failed clone or fetch is retried: ASTD_NETWORK_RETRIES if it
+// is a number, otherwise 3.
+fn network_retries() -> usize {
+    println!("cargo:rerun-if-env-changed=ASTD_NETWORK_RETRIES");
+    let default = 3;
+    match env::var("ASTD_NETWORK_RETRIES") {
+        Ok(value) => value.trim().parse::<usize>().unwrap_or_else(|_| {
+            log_warn(format_args!(
+                "Ignoring invalid ASTD_NETWORK_RETRIES={:?}, using {}",
+                value, default
+            ));
+            default
+        }),
+        Err(_) => default,
+    }
+}
+
+// Resolves the Abseil tag, branch, or commit to build from ASTD_ABSEIL_REF.
+fn abseil_ref() -> String {
+    println!("cargo:rerun-if-env-changed=ASTD_ABSEIL_REF");
+    env::var("ASTD_ABSEIL_REF")
+        .ok()
+        .map(|reference| reference.trim().to_owned())
+        .filter(|reference| !reference.is_empty())
+        .unwrap_or_else(|| DEFAULT_ABSEIL_REF.to_owned())
+}
+
+// Returns the commit the Abseil checkout must be at, from ASTD_ABSEIL_VERIFY_SHA,
+// normalized by `normalize_sha`. Panics if it is not a commit SHA.
+fn expected_abseil_sha() -> Option<String> {
+    println!("cargo:rerun-if-env-changed=ASTD_ABSEIL_VERIFY_SHA");
+    let value = env::var("ASTD_ABSEIL_VERIFY_SHA").ok()?;
+    if value.trim().is_empty() {
+        return None;
+    }
+    let sha = normalize_sha(&value).unwrap_or_else(|| {
+        panic!(
+            "ASTD_ABSEIL_VERIFY_SHA must be a commit SHA of 7 to 64 hex digits, not {:?}",
+            value
+        )
+    });
+    Some(sha)
+}
+
+/// Trims and lowercases a commit SHA, full or abbreviated. Returns `None` unless it is
+/// 7 to 64 hex digits, long enough to name a commit and short enough for SHA-256.
+pub fn normalize_sha(sha: &str) -> Option<String> {
+    let sha = sha.trim().to_ascii_lowercase();
+    ((7..=64).contains(&sha.len()) && sha.bytes().all(|b| b.is_ascii_hexdigit())).then_some(sha)
+}
+
+/// Returns true if `head`, the full SHA a checkout is at, is the commit `expected`
+/// names. An abbreviated `expected` matches the commit it is a prefix of.
+pub fn sha_matches(expected: &str, head: &str) -> bool {
+    match (normalize_sha(expected), normalize_sha(head)) {
+        (Some(expected), Some(head)) => head.starts_with(&expected),
+        _ => false,
+    }
+}
+
+// Checks that the Abseil checkout is at `expected`, the commit from
+// ASTD_ABSEIL_VERIFY_SHA.
+fn verify_abseil_sha(expected: &str) -> Result<()> {
+    let head = run_command("git", &["rev-parse", "HEAD"], &ABSEIL_DIR)?;
+    let head = head.trim();
+    if !sha_matches(expected, head) {
+        return Err(std::io::Error::other(format!(
+            "Abseil checkout {:?} is at {}, but ASTD_ABSEIL_VERIFY_SHA expects {}",
+            *ABSEIL_DIR, head, expected
+        )));
+    }
+    log_info(format_args!("Verified Abseil commit: {}", head));
     Ok(())
 }
 
-// Placeholder for future wrapper generation.
-fn generate_bind_wrappers(_headers_dir: &Path, writer: &mut BufWriter<File>) -> Result<()> {
-    writeln!(writer, "// Wrappers go here")
+// Returns the pre-fetched Abseil checkout named by ASTD_ABSEIL_SOURCE_DIR, if any.
+// The directory is the root of an abseil-cpp checkout, holding `CMakeLists.txt`
+// and the `absl/` header tree.
+fn vendored_abseil_dir() -> Option<PathBuf> {
+    println!("cargo:rerun-if-env-changed=ASTD_ABSEIL_SOURCE_DIR");
+    env::var_os("ASTD_ABSEIL_SOURCE_DIR")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+}
+
+// Returns true when ASTD_OFFLINE=1 forbids network access.
+fn offline() -> bool {
+    println!("cargo:rerun-if-env-changed=ASTD_OFFLINE");
+    env::var("ASTD_OFFLINE").is_ok_and(|value| value == "1")
 }
 
-// Runs a command and returns its stdout; logs error and returns an empty string on failure.
-fn run_command(command: &str, args: &[&str], path: &Path) -> String {
-    let output = Command::new(command).args(args).current_dir(path).output();
-    match output {
-        Ok(output) if output.status.success() => {
-            String::from_utf8(output.stdout).unwrap_or_default()
+// Clones Abseil into ABSEIL_DIR if needed and checks out the requested ref,
+// fetching only when the ref is not already known locally. A vendored checkout
+// is used as-is. Offline builds fail instead of touching the network.
+fn fetch_abseil() -> Result<()> {
+    if vendored_abseil_dir().is_some() {
+        log_info(format_args!("Using vendored Abseil at: {:?}", *ABSEIL_DIR));
+        if !SOURCE_DIR.exists() {
+            return Err(std::io::Error::other(format!(
+                "ASTD_ABSEIL_SOURCE_DIR has no absl/ directory: {:?}",
+                *ABSEIL_DIR
+            )));
         }
-        Ok(output) => {
-            eprintln!(
-                "Command failed: {}",
-                String::from_utf8_lossy(&output.stderr)
-            );
-            String::new()
+        if let Some(expected) = expected_abseil_sha() {
+            verify_abseil_sha(&expected)?;
         }
-        Err(err) => {
-            eprintln!("Failed to run command {}: {}", command, err);
-            String::new()
+        return Ok(());
+    }
+    let reference = abseil_ref();
+    log_info(format_args!("Using Abseil ref: {}", reference));
+    let offline_error = |action: &str| {
+        Err(std::io::Error::other(format!(
+            "ASTD_OFFLINE=1 but Abseil needs a {}; set ASTD_ABSEIL_SOURCE_DIR instead",
+            action
+        )))
+    };
+    if !ABSEIL_DIR.join(".git").exists() {
+        if offline() {
+            return offline_error("clone");
+        }
+        run_command_with_retry(
+            "git",
+            &["clone", "--no-checkout", ABSEIL_SRC, "abseil-cpp"],
+            &BUILD_DIR,
+            network_retries(),
+            NETWORK_BACKOFF,
+        )?;
+    }
+    let commit = format!("{}^{{commit}}", reference);
+    // `rev-parse --verify` fails for a ref that is not known yet.
+    let known = run_command(
+        "git",
+        &["rev-parse", "--verify", "--quiet", &commit],
+        &ABSEIL_DIR,
+    )
+    .is_ok_and(|known| !known.trim().is_empty());
+    if !known {
+        if offline() {
+            return offline_error(&format!("fetch of {}", reference));
         }
+        run_command_with_retry(
+            "git",
+            &["fetch", "--tags", "origin", &reference],
+            &ABSEIL_DIR,
+            network_retries(),
+            NETWORK_BACKOFF,
+        )?;
+    }
+    run_command(
+        "git",
+        &["-c", "advice.detachedHead=false", "checkout", &reference],
+        &ABSEIL_DIR,
+    )?;
+    if let Some(expected) = expected_abseil_sha() {
+        verify_abseil_sha(&expected)?;
     }
+    Ok(())
 }
 
 // Gathers header files by copying them from SOURCE_DIR to INCLUDE_DIR.
-fn gather_includes() {
+fn gather_includes() -> Result<()> {
     let source = &*SOURCE_DIR;
     let destination = &*INCLUDE_DIR;
     if !source.exists() {
-        eprintln!("Source {:?} missing, skipping.", source);
-        return;
+        log_warn(format_args!("Source {:?} missing, skipping.", source));
+        return Ok(());
+    }
+    if prune_headers() {
+        let removed = prune_stale_headers(source, destination)?;
+        log_info(format_args!(
+            "Pruned {} stale headers from {:?}",
+            removed, destination
+        ));
     }
     create_path(destination);
-    visit_dirs(source, destination, source);
+    visit_dirs(source, destination, source)
+}
+
+// Returns true when ASTD_PRUNE_HEADERS=1 allows deleting stale headers from INCLUDE_DIR.
+fn prune_headers() -> bool {
+    println!("cargo:rerun-if-env-changed=ASTD_PRUNE_HEADERS");
+    env::var("ASTD_PRUNE_HEADERS").is_ok_and(|value| value == "1")
 }
 
 fn main() {
-    build_flags();
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=gather_libs.rs");
+    let config = gather_libs::BuildConfig::from_env();
+    let kind = gather_libs::LibKind::from_env();
+    let layout = gather_libs::LibLayout::target(config);
+    build_flags(config, layout, kind);
+    if clean_requested() {
+        clean();
+    }
     create_path(&BUILD_DIR);
+    let bindings_path = EXTERNAL_DIR.join(BIND_FILE_NAME);
+    println!("cargo:rerun-if-changed={}", STAMP_FILE.display());
+    println!("cargo:rerun-if-changed={}", bindings_path.display());
+    let stamp = build_stamp();
+    let mut succeeded = true;
+    if is_up_to_date(&stamp, &bindings_path) {
+        log_info(format_args!(
+            "Abseil is up to date with {:?}, skipping the rebuild",
+            *STAMP_FILE
+        ));
+    } else {
+        succeeded = rebuild(layout, &stamp);
+    }
+    match gather_libs::link_directives(&LIB_DIR, layout, kind) {
+        Ok(directives) => directives
+            .iter()
+            .for_each(|directive| println!("{}", directive)),
+        Err(err) => {
+            log_error(format_args!("Failed to list libraries to link: {}", err));
+            succeeded = false;
+        }
+    }
+    if emit_rust()
+        && let Err(err) = generate_rust_bindings()
+    {
+        log_error(format_args!("Failed to generate Rust bindings: {}", err));
+        succeeded = false;
+    }
+    if emit_json()
+        && let Err(err) = generate_signatures_json()
+    {
+        log_error(format_args!("Failed to generate signatures: {}", err));
+        succeeded = false;
+    }
+    if succeeded {
+        log_info("Build script completed successfully.");
+    } else {
+        log_error("Build script completed with errors; see the messages above.");
+    }
+}
+
+// Fetches Abseil, gathers its headers, builds it, gathers its libraries and generates
+// the bindings, writing `stamp` to STAMP_FILE if every step succeeded. Returns whether
+// they all did.
+fn rebuild(layout: gather_libs::LibLayout, stamp: &str) -> bool {
+    if let Err(err) = fetch_abseil() {
+        panic!("Failed to fetch Abseil: {}", err);
+    }
     create_path(&ABSEIL_BUILD_DIR);
-    gather_includes();
-    if let Err(err) = generate_bindings() {
-        eprintln!("Failed to generate bindings: {}", err);
+    if let Err(err) = gather_includes() {
+        panic!("Failed to gather headers: {}", err);
+    }
+    if let Err(err) = build_abseil() {
+        panic!("Failed to build Abseil: {}", err);
+    }
+    let dry_run = gather_libs::dry_run();
+    let mut succeeded = true;
+    match gather_libs::gather_libs(&ABSEIL_BUILD_DIR, &LIB_DIR, layout, dry_run) {
+        Ok(count) if dry_run => log_info(format_args!(
+            "Would gather {} libraries into {:?}",
+            count, *LIB_DIR
+        )),
+        Ok(count) => log_info(format_args!(
+            "Gathered {} libraries into {:?}",
+            count, *LIB_DIR
+        )),
+        Err(err) => {
+            log_error(format_args!("Failed to gather libraries: {}", err));
+            succeeded = false;
+        }
+    }
+    if let Err(err) = generate_bindings(&EXTERNAL_DIR) {
+        log_error(format_args!("Failed to generate bindings: {}", err));
+        succeeded = false;
+    }
+    // A dry run gathers nothing, so the next build must not be skipped.
+    if succeeded
+        && !dry_run
+        && let Err(err) = fs::write(&*STAMP_FILE, stamp)
+    {
+        log_error(format_args!(
+            "Failed to write build stamp {:?}: {}",
+            *STAMP_FILE, err
+        ));
+    }
+    succeeded
+}
+
+// Records the inputs of a rebuild: the Abseil ref and checkout, a hash of the CMake
+// configuration flags, and the options the bindings are generated with.
+fn build_stamp() -> String {
+    let mut hasher = DefaultHasher::new();
+    CONFIG_FLAGS.lock().unwrap().hash(&mut hasher);
+    format!(
+        "ref={}\nverify_sha={}\nsource={}\nflags={:016x}\nskip_deprecated={}\nstrip_macros={}\nfilter={:?}\n",
+        abseil_ref(),
+        expected_abseil_sha().unwrap_or_default(),
+        ABSEIL_DIR.display(),
+        hasher.finish(),
+        skip_deprecated(),
+        strip_macros().join(","),
+        FunctionFilter::from_env()
+    )
+}
+
+/// Lists what a clean build removes: the CMake build tree and the stamp under
+/// `build_dir`, the gathered headers, the generated `bindings.cpp` and `signatures.json`
+/// under `external_dir`, and the gathered libraries in `lib_dir`. A path inside
+/// `vendored`, the checkout named by ASTD_ABSEIL_SOURCE_DIR, or containing it, is
+/// left off so the user's source is never deleted.
+pub fn clean_paths(
+    build_dir: &Path,
+    external_dir: &Path,
+    lib_dir: &Path,
+    vendored: Option<&Path>,
+) -> Vec<PathBuf> {
+    let vendored = vendored.map(|dir| fs::canonicalize(dir).unwrap_or_else(|_| dir.to_owned()));
+    [
+        build_dir.join(ABSEIL_BUILD_SUBDIR),
+        external_dir.join(INCLUDE_SUBDIR),
+        lib_dir.to_owned(),
+        external_dir.join(BIND_FILE_NAME),
+        external_dir.join(SIGNATURES_FILE_NAME),
+        build_dir.join(STAMP_FILE_NAME),
+    ]
+    .into_iter()
+    .filter(|path| {
+        let Some(vendored) = &vendored else {
+            return true;
+        };
+        let path = fs::canonicalize(path).unwrap_or_else(|_| path.clone());
+        !path.starts_with(vendored) && !vendored.starts_with(&path)
+    })
+    .collect()
+}
+
+// Returns true when ASTD_CLEAN=1 asks for a build from scratch.
+fn clean_requested() -> bool {
+    println!("cargo:rerun-if-env-changed=ASTD_CLEAN");
+    env::var("ASTD_CLEAN").is_ok_and(|value| value == "1")
+}
+
+// Removes the artifacts listed by `clean_paths`, logging each one. Failures are only
+// logged, as the rebuild that follows overwrites what is left.
+fn clean() {
+    let vendored = vendored_abseil_dir();
+    let dry_run = gather_libs::dry_run();
+    for path in clean_paths(&BUILD_DIR, &EXTERNAL_DIR, &LIB_DIR, vendored.as_deref()) {
+        if !path.exists() {
+            continue;
+        }
+        if dry_run {
+            log_info(format_args!("Would remove {:?}", path));
+            continue;
+        }
+        let removed = if path.is_dir() {
+            fs::remove_dir_all(&path)
+        } else {
+            fs::remove_file(&path)
+        };
+        match removed {
+            Ok(()) => log_info(format_args!("Removed {:?}", path)),
+            Err(err) => log_warn(format_args!("Failed to remove {:?}: {}", path, err)),
+        }
     }
-    println!("Build script completed successfully.");
+}
+
+// Returns true when the last rebuild had the same inputs and its libraries and
+// bindings are still in place.
+fn is_up_to_date(stamp: &str, bindings_path: &Path) -> bool {
+    fs::read_to_string(&*STAMP_FILE).is_ok_and(|recorded| recorded == stamp)
+        && bindings_path.is_file()
+        && fs::read_dir(&*LIB_DIR).is_ok_and(|mut entries| entries.next().is_some())
 }
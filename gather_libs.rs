@@ -0,0 +1,389 @@
+// Copies the compiled Abseil libraries out of the CMake build tree.
+
+use std::{
+    env, fs,
+    io::Result,
+    path::{Component, Path, PathBuf},
+};
+
+/// The CMake configuration Abseil is built in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuildConfig {
+    /// Unoptimized, with debug information.
+    Debug,
+    /// Optimized.
+    Release,
+}
+
+impl BuildConfig {
+    /// The configuration named by ASTD_BUILD_CONFIG, `Debug` or `Release` in any case,
+    /// defaulting to `Debug` when debug assertions are on. Panics on any other value.
+    pub fn from_env() -> Self {
+        println!("cargo:rerun-if-env-changed=ASTD_BUILD_CONFIG");
+        match env::var("ASTD_BUILD_CONFIG") {
+            Ok(value) if value.trim().eq_ignore_ascii_case("debug") => BuildConfig::Debug,
+            Ok(value) if value.trim().eq_ignore_ascii_case("release") => BuildConfig::Release,
+            Ok(value) if !value.trim().is_empty() => {
+                panic!(
+                    "ASTD_BUILD_CONFIG must be Debug or Release, not {:?}",
+                    value
+                )
+            }
+            _ if cfg!(debug_assertions) => BuildConfig::Debug,
+            _ => BuildConfig::Release,
+        }
+    }
+
+    /// The configuration name as CMake and MSBuild spell it.
+    pub fn name(self) -> &'static str {
+        match self {
+            BuildConfig::Debug => "Debug",
+            BuildConfig::Release => "Release",
+        }
+    }
+}
+
+/// Whether Abseil is built as static or shared libraries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LibKind {
+    /// Static `.a` or `.lib` archives, the default.
+    Static,
+    /// Shared `.so`, `.dylib` or `.dll` libraries, requested by ASTD_SHARED=1.
+    Shared,
+}
+
+impl LibKind {
+    /// The kind requested by ASTD_SHARED.
+    pub fn from_env() -> Self {
+        println!("cargo:rerun-if-env-changed=ASTD_SHARED");
+        if env::var("ASTD_SHARED").is_ok_and(|value| value == "1") {
+            LibKind::Shared
+        } else {
+            LibKind::Static
+        }
+    }
+}
+
+/// Where a toolchain puts its libraries and what they are called.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LibLayout {
+    /// MSVC: `.lib`, `.pdb` and `.dll` files inside a per-configuration `Debug` or
+    /// `Release` folder. A shared build's `.lib` files are import libraries.
+    Msvc(BuildConfig),
+    /// Linux and other Unix-likes: `.a` and `.so` files, no configuration folder.
+    Unix,
+    /// macOS: `.a` and `.dylib` files, no configuration folder.
+    Apple,
+}
+
+impl LibLayout {
+    /// The layout of the toolchain this build script was compiled for, building in
+    /// `config`.
+    pub fn host(config: BuildConfig) -> Self {
+        if cfg!(all(target_os = "windows", target_env = "msvc")) {
+            LibLayout::Msvc(config)
+        } else if cfg!(target_os = "macos") {
+            LibLayout::Apple
+        } else {
+            LibLayout::Unix
+        }
+    }
+
+    /// The layout of the toolchain Cargo is building for, read from CARGO_CFG_TARGET_OS
+    /// and CARGO_CFG_TARGET_ENV, building in `config`. Falls back to the host layout
+    /// when those are unset.
+    pub fn target(config: BuildConfig) -> Self {
+        match (
+            env::var("CARGO_CFG_TARGET_OS"),
+            env::var("CARGO_CFG_TARGET_ENV"),
+        ) {
+            (Ok(os), Ok(target_env)) => LibLayout::for_target(config, &os, &target_env),
+            _ => LibLayout::host(config),
+        }
+    }
+
+    /// The layout for a target with the given `target_os` and `target_env` values, such
+    /// as `windows` and `msvc`, building in `config`.
+    pub fn for_target(config: BuildConfig, os: &str, target_env: &str) -> Self {
+        match (os, target_env) {
+            ("windows", "msvc") => LibLayout::Msvc(config),
+            ("macos" | "ios" | "tvos" | "watchos" | "visionos", _) => LibLayout::Apple,
+            _ => LibLayout::Unix,
+        }
+    }
+
+    /// The library file extensions to copy.
+    pub fn extensions(self) -> &'static [&'static str] {
+        match self {
+            LibLayout::Msvc(_) => &["lib", "pdb", "dll"],
+            LibLayout::Unix => &["a", "so"],
+            LibLayout::Apple => &["a", "dylib"],
+        }
+    }
+
+    /// The configuration folder the libraries are built into, if the toolchain uses one.
+    pub fn config_dir(self) -> Option<&'static str> {
+        match self {
+            LibLayout::Msvc(config) => Some(config.name()),
+            LibLayout::Unix | LibLayout::Apple => None,
+        }
+    }
+
+    /// Returns true if `path` is a library this layout should copy, including the
+    /// versioned names of shared libraries (see [`LibLayout::is_versioned_shared`]).
+    pub fn selects(self, path: &Path) -> bool {
+        let extension_matches = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| {
+                self.extensions()
+                    .iter()
+                    .any(|e| ext.eq_ignore_ascii_case(e))
+            });
+        (extension_matches || self.is_versioned_shared(path))
+            && self.config_dir().is_none_or(|config| {
+                path.components()
+                    .any(|component| component == Component::Normal(config.as_ref()))
+            })
+    }
+
+    /// Returns true for a shared library named with its version, such as
+    /// `libabsl_base.so.2501.0.0` or `libabsl_base.2501.0.0.dylib`. The unversioned name
+    /// is linked against, but the loader looks for the versioned one it records.
+    pub fn is_versioned_shared(self, path: &Path) -> bool {
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            return false;
+        };
+        let is_version = |version: &str| {
+            version.starts_with(|c: char| c.is_ascii_digit())
+                && version.chars().all(|c| c.is_ascii_digit() || c == '.')
+        };
+        match self {
+            LibLayout::Unix => name
+                .split_once(".so.")
+                .is_some_and(|(_, version)| is_version(version)),
+            LibLayout::Apple => name
+                .strip_suffix(".dylib")
+                .and_then(|stem| stem.split_once('.'))
+                .is_some_and(|(_, version)| is_version(version)),
+            LibLayout::Msvc(_) => false,
+        }
+    }
+
+    /// The C++ runtime library to link alongside Abseil, if the toolchain needs one named.
+    pub fn runtime_lib(self) -> Option<&'static str> {
+        match self {
+            LibLayout::Msvc(_) => None,
+            LibLayout::Unix => Some("stdc++"),
+            LibLayout::Apple => Some("c++"),
+        }
+    }
+
+    /// Turns a library file into the value of a `cargo:rustc-link-lib` directive, such
+    /// as `static=absl_base` for `libabsl_base.a`. An MSVC `.lib` links as `kind`, being
+    /// an import library in a shared build. Returns `None` for files that are not
+    /// linked by name, like `.pdb` and `.dll` files and versioned shared libraries.
+    pub fn link_arg(self, file: &Path, kind: LibKind) -> Option<String> {
+        if self.is_versioned_shared(file) {
+            return None;
+        }
+        let extension = file.extension()?.to_str()?.to_ascii_lowercase();
+        let stem = file.file_stem()?.to_str()?;
+        let kind = match (self, extension.as_str()) {
+            (LibLayout::Msvc(_), "lib") if kind == LibKind::Shared => {
+                return Some(format!("dylib={}", stem));
+            }
+            (LibLayout::Msvc(_), "lib") => return Some(format!("static={}", stem)),
+            (LibLayout::Unix | LibLayout::Apple, "a") => "static",
+            (LibLayout::Unix, "so") | (LibLayout::Apple, "dylib") => "dylib",
+            _ => return None,
+        };
+        Some(format!(
+            "{}={}",
+            kind,
+            stem.strip_prefix("lib").unwrap_or(stem)
+        ))
+    }
+
+    /// Maps a path relative to the build tree to its destination, dropping the
+    /// configuration folder.
+    pub fn destination(self, relative: &Path) -> PathBuf {
+        match self.config_dir() {
+            Some(config) => relative
+                .components()
+                .filter(|component| *component != Component::Normal(config.as_ref()))
+                .collect(),
+            None => relative.to_path_buf(),
+        }
+    }
+}
+
+/// The target triple Cargo is building for, such as `aarch64-unknown-linux-gnu`, or
+/// `host` outside a build script.
+pub fn target() -> String {
+    env::var("TARGET").unwrap_or_else(|_| "host".to_owned())
+}
+
+/// The architecture being built for, from `CARGO_CFG_TARGET_ARCH`, such as `x86_64` or
+/// `aarch64`, falling back to the host architecture outside of a build script.
+pub fn target_arch() -> String {
+    env::var("CARGO_CFG_TARGET_ARCH").unwrap_or_else(|_| env::consts::ARCH.to_owned())
+}
+
+/// The folder under `lib_dir` the libraries built for `target` are gathered into, so
+/// host and cross-compiled libraries do not collide. A custom target given as the path
+/// of its `.json` specification uses the file name without the extension.
+pub fn target_lib_dir(lib_dir: &Path, target: &str) -> PathBuf {
+    let target = Path::new(target);
+    let name = match target.extension() {
+        Some(ext) if ext.eq_ignore_ascii_case("json") => target.file_stem(),
+        _ => target.file_name(),
+    };
+    lib_dir.join(name.unwrap_or(target.as_os_str()))
+}
+
+/// Returns true when ASTD_DRY_RUN=1 asks for the copies to be listed but not made.
+pub fn dry_run() -> bool {
+    println!("cargo:rerun-if-env-changed=ASTD_DRY_RUN");
+    env::var("ASTD_DRY_RUN").is_ok_and(|value| value == "1")
+}
+
+/// Recursively copies the files under `src_dir` accepted by `predicate` into `dest_dir`,
+/// placing each at `rename(path relative to base)`. Returns the number of files copied.
+/// A dry run prints each copy instead of making it, and still returns the count.
+pub fn copy_files_with_filter(
+    src_dir: &Path,
+    dest_dir: &Path,
+    base: &Path,
+    predicate: &dyn Fn(&Path) -> bool,
+    rename: &dyn Fn(&Path) -> PathBuf,
+    dry_run: bool,
+) -> Result<usize> {
+    let mut copied = 0;
+    for entry in fs::read_dir(src_dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            copied += copy_files_with_filter(&path, dest_dir, base, predicate, rename, dry_run)?;
+        } else if predicate(&path) {
+            let relative = path.strip_prefix(base).unwrap_or(&path);
+            let dest_file_path = dest_dir.join(rename(relative));
+            if dry_run {
+                crate::log_info(format_args!(
+                    "Would copy {:?} -> {:?}",
+                    path, dest_file_path
+                ));
+                copied += 1;
+                continue;
+            }
+            if let Some(parent) = dest_file_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            crate::log_debug(format_args!("Copying {:?} -> {:?}", path, dest_file_path));
+            fs::copy(&path, &dest_file_path)?;
+            copied += 1;
+        }
+    }
+    Ok(copied)
+}
+
+/// Lists the `cargo:` directives that link the libraries under `lib_dir`: a search path
+/// for each folder holding one, each library, and then the C++ runtime if anything is
+/// linked. A library present both static and shared is linked as `kind`.
+///
+/// The archives of a static Unix build depend on each other, and GNU ld resolves them
+/// left to right, so when there are several they are also passed again between
+/// `--start-group` and `--end-group`, which ld rescans until nothing is left unresolved.
+pub fn link_directives(lib_dir: &Path, layout: LibLayout, kind: LibKind) -> Result<Vec<String>> {
+    let mut files = Vec::new();
+    if lib_dir.exists() {
+        collect_files(lib_dir, &mut files)?;
+    }
+    files.sort();
+    let mut search = Vec::new();
+    let mut libs: Vec<String> = Vec::new();
+    let mut archives = Vec::new();
+    for file in &files {
+        let Some(arg) = layout.link_arg(file, kind) else {
+            continue;
+        };
+        let (linked, name) = arg.split_once('=').unwrap_or(("", arg.as_str()));
+        let preferred = match kind {
+            LibKind::Static => "static",
+            LibKind::Shared => "dylib",
+        };
+        let shadowed = linked != preferred
+            && files.iter().any(|other| {
+                layout.link_arg(other, kind) == Some(format!("{}={}", preferred, name))
+            });
+        if shadowed || libs.contains(&arg) {
+            continue;
+        }
+        let dir = file.parent().unwrap_or(lib_dir).to_path_buf();
+        if !search.contains(&dir) {
+            search.push(dir);
+        }
+        if layout == LibLayout::Unix && linked == "static" {
+            archives.push(file);
+        }
+        libs.push(arg);
+    }
+    let mut directives: Vec<String> = search
+        .iter()
+        .map(|dir| format!("cargo:rustc-link-search=native={}", dir.display()))
+        .collect();
+    directives.extend(
+        libs.iter()
+            .map(|lib| format!("cargo:rustc-link-lib={}", lib)),
+    );
+    if archives.len() > 1 {
+        directives.push("cargo:rustc-link-arg=-Wl,--start-group".to_owned());
+        directives.extend(
+            archives
+                .iter()
+                .map(|archive| format!("cargo:rustc-link-arg={}", archive.display())),
+        );
+        directives.push("cargo:rustc-link-arg=-Wl,--end-group".to_owned());
+    }
+    if let Some(runtime) = layout.runtime_lib().filter(|_| !libs.is_empty()) {
+        directives.push(format!("cargo:rustc-link-lib=dylib={}", runtime));
+    }
+    Ok(directives)
+}
+
+// Collects the paths of all files under `dir`.
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Copies the libraries from the build tree into `lib_dir` using the given layout, or
+/// only lists them on a dry run.
+pub fn gather_libs(
+    build_dir: &Path,
+    lib_dir: &Path,
+    layout: LibLayout,
+    dry_run: bool,
+) -> Result<usize> {
+    if !build_dir.exists() {
+        crate::log_warn(format_args!(
+            "Build directory {:?} missing, skipping.",
+            build_dir
+        ));
+        return Ok(0);
+    }
+    copy_files_with_filter(
+        build_dir,
+        lib_dir,
+        build_dir,
+        &|path| layout.selects(path),
+        &|path| layout.destination(path),
+        dry_run,
+    )
+}
@@ -0,0 +1,411 @@
+use astd::{
+    emit_rust_bindings, emit_wrappers, extract_function_details, mangle_overload_name,
+    map_cpp_type_to_c, map_cpp_type_to_rust,
+};
+
+fn wrappers(source: &str) -> String {
+    let mut out = Vec::new();
+    emit_wrappers(&extract_function_details(source), false, &mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn test_free_function_wrapper() {
+    let source = "namespace absl {\nsize_t StrLen(const char* s);\nvoid Reset(int);\n}";
+    assert_eq!(
+        wrappers(source),
+        "size_t absl_StrLen_wrapper(const char* s) { return absl::StrLen(s); }\n\
+         void absl_Reset_wrapper(int arg0) { absl::Reset(arg0); }\n"
+    );
+}
+
+#[test]
+fn test_unnamed_parameters_get_placeholder_names() {
+    let source = "namespace absl {\nint Pick(int, double weight, bool);\n}";
+    assert_eq!(
+        wrappers(source),
+        "int absl_Pick_wrapper(int arg0, double weight, bool arg2) { \
+         return absl::Pick(arg0, weight, arg2); }\n"
+    );
+}
+
+#[test]
+fn test_non_c_types_are_skipped() {
+    let source = r#"
+namespace absl {
+std::string StrCat(const AlphaNum& a, const AlphaNum& b);
+void CopyCordToString(const absl::Cord& src, std::string* dst);
+template <typename T> T Identity(T value);
+class Cord {
+ public:
+  bool empty() const;
+};
+}
+"#;
+    assert_eq!(
+        wrappers(source),
+        "// skipped absl::StrCat: returns an owning string `std::string`\n\
+         // skipped absl::CopyCordToString: takes non-C type `const absl::Cord&`\n\
+         // skipped absl::Identity: function template\n\
+         // skipped absl::Cord::empty: member function\n"
+    );
+}
+
+#[test]
+fn test_wrapper_names_do_not_collide() {
+    let source = "namespace absl {\nint Abs(int v);\n}\nint absl_Abs(int v);";
+    let out = wrappers(source);
+    let names: Vec<_> = out
+        .lines()
+        .map(|line| line.split_whitespace().nth(1).unwrap())
+        .collect();
+    assert_eq!(names, ["absl_Abs_wrapper(int", "absl_Abs_wrapper_2(int"]);
+}
+
+#[test]
+fn test_overloads_get_distinct_names() {
+    let source = r#"
+namespace absl {
+void Append(int v);
+void Append(int v, float scale);
+void Append(const char* s, size_t* out);
+void Flush();
+}
+"#;
+    let extracted = extract_function_details(source);
+    let mangled: Vec<_> = extracted.iter().map(mangle_overload_name).collect();
+    assert_eq!(
+        mangled,
+        [
+            "absl_Append_int_wrapper",
+            "absl_Append_int_float_wrapper",
+            "absl_Append_const_char_ptr_size_t_ptr_wrapper",
+            "absl_Flush_void_wrapper",
+        ]
+    );
+    let out = wrappers(source);
+    let names: Vec<_> = out
+        .lines()
+        .map(|line| line.split(['(', ' ']).nth(1).unwrap())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "absl_Append_int_wrapper",
+            "absl_Append_int_float_wrapper",
+            "absl_Append_const_char_ptr_size_t_ptr_wrapper",
+            "absl_Flush_wrapper",
+        ]
+    );
+    assert_eq!(out, wrappers(source));
+}
+
+#[test]
+fn test_map_cpp_type_to_c() {
+    let cases: &[(&str, Option<(&str, bool)>)] = &[
+        ("int", Some(("int", false))),
+        ("bool", Some(("bool", false))),
+        ("const int", Some(("int", false))),
+        ("std::int64_t", Some(("int64_t", false))),
+        ("unsigned long long", Some(("unsigned long long", false))),
+        ("const char*", Some(("const char*", false))),
+        ("int**", Some(("int**", false))),
+        ("const double&", Some(("double", false))),
+        ("size_t&", Some(("size_t*", false))),
+        ("std::string", Some(("const char*", true))),
+        ("const std::string&", Some(("const char*", true))),
+        ("absl::string_view", Some(("const char*", true))),
+        ("std::string&", None),
+        ("int&&", None),
+        ("std::vector<int>", None),
+        ("absl::Cord", None),
+    ];
+    for (cpp, expected) in cases {
+        let mapped = map_cpp_type_to_c(cpp);
+        let mapped = mapped
+            .as_ref()
+            .map(|c| (c.spelling.as_str(), c.needs_length));
+        assert_eq!(mapped, *expected, "mapping {}", cpp);
+    }
+}
+
+#[test]
+fn test_wrappers_use_the_type_map() {
+    let source = r#"
+namespace absl {
+bool SimpleAtoi(absl::string_view str, int& out);
+absl::string_view StripLeadingAsciiWhitespace(absl::string_view str);
+std::string AsciiStrToLower(absl::string_view s);
+int64_t ToInt64Seconds(const int64_t& d);
+}
+"#;
+    assert_eq!(
+        wrappers(source),
+        "bool absl_SimpleAtoi_wrapper(const char* str, size_t str_len, int* out) { \
+         return absl::SimpleAtoi(absl::string_view(str, str_len), *out); }\n\
+         const char* absl_StripLeadingAsciiWhitespace_wrapper(const char* str, size_t str_len, \
+         size_t* out_len) { auto result = absl::StripLeadingAsciiWhitespace(\
+         absl::string_view(str, str_len)); *out_len = result.size(); return result.data(); }\n\
+         // skipped absl::AsciiStrToLower: returns an owning string `std::string`\n\
+         int64_t absl_ToInt64Seconds_wrapper(int64_t d) { return absl::ToInt64Seconds(d); }\n"
+    );
+}
+
+#[test]
+fn test_variadic_functions_are_reported_unbindable() {
+    let source = "namespace absl {\nint PrintF(const char* format, ...);\n}";
+    assert_eq!(
+        wrappers(source),
+        "// skipped absl::PrintF: C variadic arguments cannot be forwarded\n"
+    );
+}
+
+#[test]
+fn test_trailing_and_deduced_returns() {
+    let source =
+        "namespace absl {\nauto Size(int n) -> size_t;\nauto Twice(int x) { return x * 2; }\n}";
+    assert_eq!(
+        wrappers(source),
+        "size_t absl_Size_wrapper(int n) { return absl::Size(n); }\n\
+         // skipped absl::Twice: deduced return type\n"
+    );
+}
+
+#[test]
+fn test_operators_are_skipped() {
+    let source = "void* operator new(size_t size);\nvoid operator delete(void* p) noexcept;";
+    assert_eq!(
+        wrappers(source),
+        "// skipped operator new: operator\n// skipped operator delete: operator\n"
+    );
+}
+
+#[test]
+fn test_deleted_functions_are_skipped() {
+    // A deleted overload only exists to reject a conversion, so the remaining
+    // overload keeps its plain name.
+    let source =
+        "namespace absl {\nint64_t ToInt64(int64_t v);\nint64_t ToInt64(double v) = delete;\n}";
+    assert_eq!(
+        wrappers(source),
+        "int64_t absl_ToInt64_wrapper(int64_t v) { return absl::ToInt64(v); }\n\
+         // skipped absl::ToInt64: deleted function\n"
+    );
+}
+
+#[test]
+fn test_duplicate_declarations_get_one_wrapper() {
+    let source = "namespace absl {\nint Abs(int v);\nint Abs(int value);\n}";
+    assert_eq!(
+        wrappers(source),
+        "int absl_Abs_wrapper(int v) { return absl::Abs(v); }\n\
+         // skipped absl::Abs: duplicate declaration\n"
+    );
+}
+
+#[test]
+fn test_non_public_members_are_reported() {
+    let source = "namespace absl {\nclass Cord {\n public:\n  bool empty() const;\n \
+                  private:\n  void ForceFlat();\n};\n}";
+    assert_eq!(
+        wrappers(source),
+        "// skipped absl::Cord::empty: member function\n\
+         // skipped absl::Cord::ForceFlat: non-public member function\n"
+    );
+}
+
+#[test]
+fn test_enable_if_templates_are_skipped() {
+    let source = "namespace absl {\ntemplate <typename T, \
+                  typename = std::enable_if_t<std::is_integral<T>::value>>\n\
+                  T Abs(T v);\nint Sign(int v);\n}";
+    assert_eq!(
+        wrappers(source),
+        "// skipped absl::Abs: function template\n\
+         int absl_Sign_wrapper(int v) { return absl::Sign(v); }\n"
+    );
+}
+
+#[test]
+fn test_friends_are_reported() {
+    let source = "namespace absl {\nclass Cord {\n public:\n  \
+                  friend bool operator==(const Cord& a, const Cord& b);\n};\n}";
+    assert_eq!(
+        wrappers(source),
+        "// skipped absl::Cord::operator==: friend declaration\n"
+    );
+}
+
+#[test]
+fn test_c_linkage_is_not_wrapped() {
+    let source = "extern \"C\" {\nint CAbs(int v);\n}\nint Abs(int v);";
+    assert_eq!(
+        wrappers(source),
+        "// skipped CAbs: already has C linkage\nint Abs_wrapper(int v) { return Abs(v); }\n"
+    );
+}
+
+#[test]
+fn test_map_cpp_type_to_rust() {
+    let cases = [
+        ("int", "core::ffi::c_int"),
+        ("bool", "bool"),
+        ("size_t", "usize"),
+        ("int64_t", "i64"),
+        ("double", "f64"),
+        ("void", "()"),
+        ("void*", "*mut core::ffi::c_void"),
+        ("const char*", "*const core::ffi::c_char"),
+        ("const char**", "*mut *const core::ffi::c_char"),
+        ("uint8_t*", "*mut u8"),
+    ];
+    for (cpp, rust) in cases {
+        let c_type = map_cpp_type_to_c(cpp).unwrap();
+        assert_eq!(map_cpp_type_to_rust(&c_type), rust, "mapping {}", cpp);
+    }
+    let string = map_cpp_type_to_c("absl::string_view").unwrap();
+    assert_eq!(map_cpp_type_to_rust(&string), "*const core::ffi::c_char");
+}
+
+#[test]
+fn test_rust_bindings() {
+    let source = r#"
+namespace absl {
+// Returns the length of `s`.
+//
+// Counts bytes, not characters.
+size_t StrLen(absl::string_view s);
+void Reset(int type);
+void Fill(char* buf, size_t n);
+absl::string_view Strip(absl::string_view s);
+class Cord;
+void Use(const Cord& c);
+}
+"#;
+    let mut out = Vec::new();
+    emit_rust_bindings(&extract_function_details(source), false, &mut out).unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        r#"// Auto-generated: Rust declarations for bindings.cpp
+#![allow(non_snake_case, dead_code)]
+
+unsafe extern "C" {
+    pub fn absl_StrLen_wrapper(s: *const core::ffi::c_char, s_len: usize) -> usize;
+    pub fn absl_Reset_wrapper(type_: core::ffi::c_int);
+    pub fn absl_Fill_wrapper(buf: *mut core::ffi::c_char, n: usize);
+    pub fn absl_Strip_wrapper(s: *const core::ffi::c_char, s_len: usize, out_len: *mut usize) -> *const core::ffi::c_char;
+}
+
+/// Returns the length of `s`.
+///
+/// Counts bytes, not characters.
+pub fn absl_StrLen(s: &str) -> usize {
+    unsafe { absl_StrLen_wrapper(s.as_ptr().cast(), s.len()) }
+}
+
+pub fn absl_Reset(type_: core::ffi::c_int) {
+    unsafe { absl_Reset_wrapper(type_) }
+}
+"#
+    );
+}
+
+#[test]
+fn test_skip_deprecated_wrappers() {
+    let source = r#"
+namespace absl {
+ABSL_DEPRECATED("Use absl::StrLen instead")
+size_t OldStrLen(const char* s);
+[[deprecated]] void OldReset(int n);
+size_t StrLen(const char* s);
+}
+"#;
+    let details = extract_function_details(source);
+    let mut out = Vec::new();
+    emit_wrappers(&details, true, &mut out).unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "// skipped (deprecated): absl::OldStrLen\n\
+         // skipped (deprecated): absl::OldReset\n\
+         size_t absl_StrLen_wrapper(const char* s) { return absl::StrLen(s); }\n"
+    );
+    assert!(wrappers(source).contains("absl_OldStrLen_wrapper"));
+}
+
+#[test]
+fn test_rust_bindings_keep_deprecation_note() {
+    let source = r#"
+namespace absl {
+ABSL_DEPRECATED("Use absl::Clear instead")
+void Reset(int n);
+[[deprecated]] void Fill(char* buf, size_t n);
+}
+"#;
+    let mut out = Vec::new();
+    emit_rust_bindings(&extract_function_details(source), false, &mut out).unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        r#"// Auto-generated: Rust declarations for bindings.cpp
+#![allow(non_snake_case, dead_code)]
+
+unsafe extern "C" {
+    #[deprecated = "Use absl::Clear instead"]
+    pub fn absl_Reset_wrapper(n: core::ffi::c_int);
+    #[deprecated]
+    pub fn absl_Fill_wrapper(buf: *mut core::ffi::c_char, n: usize);
+}
+
+#[deprecated = "Use absl::Clear instead"]
+pub fn absl_Reset(n: core::ffi::c_int) {
+    unsafe { absl_Reset_wrapper(n) }
+}
+"#
+    );
+}
+
+#[test]
+fn test_rust_deprecation_note_keeps_escaped_quotes() {
+    let source = r#"
+namespace absl {
+ABSL_DEPRECATED("Use \"absl::NewThing\" instead; see C:\\docs")
+void OldThing(int n);
+}
+"#;
+    let mut out = Vec::new();
+    emit_rust_bindings(&extract_function_details(source), false, &mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    assert!(
+        out.contains(r#"#[deprecated = "Use \"absl::NewThing\" instead; see C:\\docs"]"#),
+        "{}",
+        out
+    );
+}
+
+#[test]
+fn test_rvalue_overloads_are_skipped_with_their_own_reason() {
+    let source = r#"
+namespace absl {
+class Status {
+ public:
+  std::string_view message() const&;
+  std::string message() &&;
+};
+}
+"#;
+    assert_eq!(
+        wrappers(source),
+        "// skipped absl::Status::message: member function\n\
+         // skipped absl::Status::message: rvalue ref-qualified member function\n"
+    );
+    let names: Vec<_> = extract_function_details(source)
+        .iter()
+        .map(mangle_overload_name)
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "absl_Status_message_void_const_lref_wrapper",
+            "absl_Status_message_void_rref_wrapper"
+        ]
+    );
+}
@@ -0,0 +1,155 @@
+use std::path::Path;
+
+use astd::{
+    cmake_build_flags, cmake_cache_is_stale, cmake_config_flags, cmake_configure_args,
+    gather_libs::{BuildConfig, LibKind, LibLayout},
+};
+
+#[test]
+fn test_static_flags() {
+    let flags = cmake_config_flags(
+        BuildConfig::Debug,
+        LibLayout::Unix,
+        LibKind::Static,
+        None,
+        "x86_64",
+    );
+    assert!(flags.contains(&"-DCMAKE_BUILD_TYPE=Debug".to_owned()));
+    assert!(flags.contains(&"-DBUILD_SHARED_LIBS=OFF".to_owned()));
+    assert!(!flags.contains(&"-G".to_owned()));
+}
+
+#[test]
+fn test_shared_flags() {
+    let flags = cmake_config_flags(
+        BuildConfig::Release,
+        LibLayout::Unix,
+        LibKind::Shared,
+        Some("Ninja"),
+        "x86_64",
+    );
+    assert_eq!(flags[..2], ["-G", "Ninja"]);
+    assert!(flags.contains(&"-DCMAKE_BUILD_TYPE=Release".to_owned()));
+    assert!(flags.contains(&"-DBUILD_SHARED_LIBS=ON".to_owned()));
+    assert!(!flags.contains(&"-DBUILD_SHARED_LIBS=OFF".to_owned()));
+    assert!(!flags.contains(&"-DABSL_MSVC_STATIC_RUNTIME=ON".to_owned()));
+}
+
+#[test]
+fn test_configure_args() {
+    let flags = cmake_config_flags(
+        BuildConfig::Release,
+        LibLayout::Unix,
+        LibKind::Shared,
+        Some("Ninja"),
+        "x86_64",
+    );
+    let args = cmake_configure_args(Path::new("abseil-cpp"), Path::new("build"), &flags);
+    assert_eq!(args[..4], ["-S", "abseil-cpp", "-B", "build"]);
+    assert_eq!(args[4..], flags[..]);
+    assert!(args.contains(&"-DBUILD_SHARED_LIBS=ON".to_owned()));
+}
+
+#[test]
+fn test_generator_change_makes_cache_stale() {
+    let cache = "# This is the CMakeCache file.\n\
+                 CMAKE_BUILD_TYPE:STRING=Debug\n\
+                 CMAKE_GENERATOR:INTERNAL=Unix Makefiles\n";
+    let ninja = cmake_config_flags(
+        BuildConfig::Debug,
+        LibLayout::Unix,
+        LibKind::Static,
+        Some("Ninja"),
+        "x86_64",
+    );
+    let make = cmake_config_flags(
+        BuildConfig::Debug,
+        LibLayout::Unix,
+        LibKind::Static,
+        Some("Unix Makefiles"),
+        "x86_64",
+    );
+    let default = cmake_config_flags(
+        BuildConfig::Debug,
+        LibLayout::Unix,
+        LibKind::Static,
+        None,
+        "x86_64",
+    );
+    assert!(cmake_cache_is_stale(cache, &ninja));
+    assert!(!cmake_cache_is_stale(cache, &make));
+    assert!(!cmake_cache_is_stale(cache, &default));
+}
+
+#[test]
+fn test_toolchain_change_makes_cache_stale() {
+    let host = "CMAKE_GENERATOR:INTERNAL=Ninja\n";
+    let cross = "CMAKE_GENERATOR:INTERNAL=Ninja\n\
+                 CMAKE_TOOLCHAIN_FILE:FILEPATH=/toolchains/aarch64.cmake\n";
+    let flags = cmake_config_flags(
+        BuildConfig::Debug,
+        LibLayout::Unix,
+        LibKind::Static,
+        Some("Ninja"),
+        "x86_64",
+    );
+    let mut cross_flags = flags.clone();
+    cross_flags.push("-DCMAKE_TOOLCHAIN_FILE=/toolchains/aarch64.cmake".to_owned());
+    let mut other_flags = flags.clone();
+    other_flags.push("-DCMAKE_TOOLCHAIN_FILE=/toolchains/riscv64.cmake".to_owned());
+    assert!(!cmake_cache_is_stale(host, &flags));
+    assert!(cmake_cache_is_stale(host, &cross_flags));
+    assert!(!cmake_cache_is_stale(cross, &cross_flags));
+    assert!(cmake_cache_is_stale(cross, &other_flags));
+    assert!(cmake_cache_is_stale(cross, &flags));
+}
+
+#[test]
+fn test_msvc_flags_follow_the_target() {
+    let msvc = LibLayout::Msvc(BuildConfig::Release);
+    let flags = cmake_config_flags(BuildConfig::Release, msvc, LibKind::Static, None, "aarch64");
+    assert_eq!(flags[..2], ["-A", "ARM64"]);
+    assert!(flags.contains(&"-DABSL_MSVC_STATIC_RUNTIME=ON".to_owned()));
+    let flags = cmake_config_flags(
+        BuildConfig::Release,
+        LibLayout::Unix,
+        LibKind::Static,
+        None,
+        "x86_64",
+    );
+    assert!(!flags.contains(&"-A".to_owned()));
+    assert!(!flags.contains(&"-DABSL_MSVC_STATIC_RUNTIME=ON".to_owned()));
+    let cache = "CMAKE_GENERATOR_PLATFORM:INTERNAL=x64\n";
+    let x64 = cmake_config_flags(BuildConfig::Release, msvc, LibKind::Static, None, "x86_64");
+    assert!(!cmake_cache_is_stale(cache, &x64));
+    let arm64 = cmake_config_flags(BuildConfig::Release, msvc, LibKind::Static, None, "aarch64");
+    assert!(cmake_cache_is_stale(cache, &arm64));
+}
+
+#[test]
+fn test_msbuild_flags_follow_the_target() {
+    let msvc = LibLayout::Msvc(BuildConfig::Debug);
+    assert_eq!(
+        cmake_build_flags(msvc, None, "aarch64", 4),
+        [
+            "--build",
+            ".",
+            "--",
+            "/p:Platform=ARM64",
+            "/p:Configuration=Debug",
+            "/m:4"
+        ]
+    );
+    assert_eq!(
+        cmake_build_flags(msvc, None, "x86", 4)[3],
+        "/p:Platform=Win32"
+    );
+    assert_eq!(
+        cmake_build_flags(msvc, Some("Ninja"), "x86_64", 4),
+        ["--build", ".", "--parallel", "4"]
+    );
+    assert_eq!(
+        cmake_build_flags(LibLayout::Unix, None, "x86_64", 4),
+        ["--build", ".", "--parallel", "4"]
+    );
+}
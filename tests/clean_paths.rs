@@ -0,0 +1,87 @@
+use astd::clean_paths;
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+// Returns the listed paths relative to `root`, with `/` separators.
+fn relative(paths: &[PathBuf], root: &Path) -> Vec<String> {
+    paths
+        .iter()
+        .map(|path| {
+            path.strip_prefix(root)
+                .unwrap()
+                .to_string_lossy()
+                .replace('\\', "/")
+                .trim_end_matches('/')
+                .to_owned()
+        })
+        .collect()
+}
+
+#[test]
+fn test_default_layout() {
+    let root = Path::new("/work/astd");
+    let paths = clean_paths(
+        &root.join("target/debug/build"),
+        &root.join("external"),
+        &root.join("external/lib/x86_64-unknown-linux-gnu"),
+        None,
+    );
+    assert_eq!(
+        relative(&paths, root),
+        [
+            "target/debug/build/abseil-cpp/build",
+            "external/include",
+            "external/lib/x86_64-unknown-linux-gnu",
+            "external/bindings.cpp",
+            "external/signatures.json",
+            "target/debug/build/astd.stamp",
+        ]
+    );
+}
+
+#[test]
+fn test_vendored_source_is_never_removed() {
+    let root = env::temp_dir().join(format!("astd-clean-paths-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    let vendored = root.join("abseil-cpp");
+    fs::create_dir_all(vendored.join("external/include")).unwrap();
+    fs::create_dir_all(root.join("build")).unwrap();
+
+    // An unrelated checkout keeps nothing back.
+    let elsewhere = root.join("elsewhere");
+    let all = clean_paths(
+        &root.join("build"),
+        &root.join("external"),
+        &root.join("external/lib"),
+        Some(&elsewhere),
+    );
+    assert_eq!(all.len(), 6);
+
+    // Artifacts placed inside the checkout stay, however the path is spelled.
+    let paths = clean_paths(
+        &root.join("build"),
+        &vendored.join("external"),
+        &vendored.join("external/lib"),
+        Some(&root.join("build/../abseil-cpp")),
+    );
+    assert_eq!(
+        relative(&paths, &root),
+        ["build/abseil-cpp/build", "build/astd.stamp"]
+    );
+
+    // So does a CMake build tree that would land inside the checkout.
+    let paths = clean_paths(
+        &root,
+        &root.join("external"),
+        &root.join("lib"),
+        Some(&vendored),
+    );
+    assert!(
+        !relative(&paths, &root).contains(&"abseil-cpp/build".to_owned()),
+        "{:?}",
+        paths
+    );
+    fs::remove_dir_all(&root).unwrap();
+}
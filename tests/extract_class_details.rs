@@ -0,0 +1,221 @@
+use astd::{
+    Access, ClassKind, FunctionKind, TemplateParameter, TemplateParameterKind,
+    extract_class_details,
+};
+
+// A trimmed-down skeleton of `absl::Cord` from absl/strings/cord.h.
+const CORD: &str = r#"
+namespace absl {
+ABSL_NAMESPACE_BEGIN
+
+class Cord;
+class CordTestPeer;
+
+// Cord
+//
+// A Cord is a sequence of characters.
+class Cord {
+ private:
+  template <typename T>
+  using EnableIfString = absl::enable_if_t<std::is_same<T, std::string>::value, int>;
+
+ public:
+  // Cord::Cord() Constructors.
+  constexpr Cord() noexcept;
+  Cord(const Cord& src);
+  Cord(Cord&& src) noexcept;
+  Cord& operator=(const Cord& x);
+  Cord& operator=(Cord&& x) noexcept;
+  explicit Cord(absl::string_view src);
+  ~Cord() {
+    if (contents_.is_tree()) DestroyCordSlow();
+  }
+
+  void Clear();
+  void Append(const Cord& src);
+  size_t size() const;
+  bool empty() const;
+
+  class CharIterator {
+   public:
+    CharIterator() = default;
+    CharIterator& operator++();
+  };
+
+ protected:
+  void AppendPrecise(absl::string_view src, int method);
+
+ private:
+  friend class CordTestPeer;
+  void DestroyCordSlow();
+  InlineRep contents_;
+};
+
+ABSL_NAMESPACE_END
+}  // namespace absl
+"#;
+
+#[test]
+fn test_cord_skeleton() {
+    let classes = extract_class_details(CORD);
+    let names: Vec<_> = classes
+        .iter()
+        .map(|c| (c.name.as_str(), c.enclosing_type.as_deref()))
+        .collect();
+    assert_eq!(names, [("Cord", None), ("CharIterator", Some("Cord"))]);
+    let cord = &classes[0];
+    assert_eq!(cord.kind, ClassKind::Class);
+    assert_eq!(cord.namespace.as_deref(), Some("absl"));
+    assert_eq!(cord.line, 11);
+    assert!(cord.bases.is_empty());
+    assert!(cord.is_copyable && cord.is_movable);
+    let methods: Vec<_> = cord.methods.iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(
+        methods,
+        [
+            "Cord",
+            "Cord",
+            "Cord",
+            "operator=",
+            "operator=",
+            "Cord",
+            "~Cord",
+            "Clear",
+            "Append",
+            "size",
+            "empty",
+        ]
+    );
+    assert!(
+        cord.methods
+            .iter()
+            .all(|m| m.access == Some(Access::Public))
+    );
+    assert_eq!(cord.methods[6].kind, FunctionKind::Destructor);
+    let iterator = &classes[1];
+    let methods: Vec<_> = iterator.methods.iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(methods, ["CharIterator", "operator++"]);
+}
+
+#[test]
+fn test_struct_defaults_to_public_and_bases() {
+    let source = r#"
+template <typename T>
+struct Holder final : public Base<T, int>, private virtual Mixin {
+  T get() const;
+ private:
+  void hidden();
+};
+union Bits {
+  int as_int();
+};
+"#;
+    let classes = extract_class_details(source);
+    let holder = &classes[0];
+    assert_eq!(holder.kind, ClassKind::Struct);
+    assert_eq!(holder.template_prefix, "template <typename T>");
+    assert_eq!(holder.bases, ["Base<T, int>", "Mixin"]);
+    assert!(holder.is_final);
+    let methods: Vec<_> = holder.methods.iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(methods, ["get"]);
+    assert_eq!(classes[1].kind, ClassKind::Union);
+    assert_eq!(classes[1].methods[0].name, "as_int");
+}
+
+#[test]
+fn test_copy_and_move() {
+    let source = r#"
+class MoveOnly {
+ public:
+  MoveOnly(MoveOnly&& other);
+  MoveOnly(const MoveOnly&) = delete;
+};
+class Pinned {
+ public:
+  Pinned(const Pinned&) = delete;
+  Pinned& operator=(const Pinned&) = delete;
+};
+class HiddenCopy {
+  HiddenCopy(const HiddenCopy&);
+ public:
+  HiddenCopy();
+};
+class ImplicitlyMoveOnly {
+ public:
+  ImplicitlyMoveOnly(ImplicitlyMoveOnly&&) = default;
+};
+struct Plain {};
+"#;
+    let flags: Vec<_> = extract_class_details(source)
+        .iter()
+        .map(|c| (c.name.clone(), c.is_copyable, c.is_movable))
+        .collect();
+    assert_eq!(
+        flags,
+        [
+            ("MoveOnly".to_owned(), false, true),
+            ("Pinned".to_owned(), false, false),
+            ("HiddenCopy".to_owned(), false, false),
+            ("ImplicitlyMoveOnly".to_owned(), false, true),
+            ("Plain".to_owned(), true, true),
+        ]
+    );
+}
+
+#[test]
+fn test_forward_declarations_are_ignored() {
+    let source = "class Cord;\nstruct Hash;\nnamespace absl { class Status; }";
+    assert!(extract_class_details(source).is_empty());
+}
+
+#[test]
+fn test_flat_hash_map_template_parameters() {
+    // The parameter list of `absl::flat_hash_map` from absl/container/flat_hash_map.h.
+    let source = r#"
+namespace absl {
+template <class K, class V, class Hash = DefaultHashContainerHash<K>,
+          class Eq = DefaultHashContainerEq<K>,
+          class Allocator = std::allocator<std::pair<const K, V>>>
+class ABSL_ATTRIBUTE_OWNER flat_hash_map
+    : public absl::container_internal::raw_hash_map<
+          absl::container_internal::FlatHashMapPolicy<K, V>, Hash, Eq,
+          Allocator> {
+ public:
+  flat_hash_map() {}
+};
+}  // namespace absl
+"#;
+    let classes = extract_class_details(source);
+    assert_eq!(classes.len(), 1);
+    let type_param = |name: &str, default: Option<&str>| TemplateParameter {
+        kind: TemplateParameterKind::Type,
+        name: Some(name.to_owned()),
+        default: default.map(str::to_owned),
+        is_pack: false,
+    };
+    assert_eq!(
+        classes[0].template_parameters(),
+        [
+            type_param("K", None),
+            type_param("V", None),
+            type_param("Hash", Some("DefaultHashContainerHash<K>")),
+            type_param("Eq", Some("DefaultHashContainerEq<K>")),
+            type_param("Allocator", Some("std::allocator<std::pair<const K, V>>")),
+        ]
+    );
+}
+
+#[test]
+fn test_friends_are_not_methods() {
+    let source = r#"
+class Duration {
+ public:
+  friend bool operator<(Duration lhs, Duration rhs);
+  friend Duration operator-(Duration d) { return Duration(); }
+  int64_t Seconds() const;
+};
+"#;
+    let classes = extract_class_details(source);
+    let methods: Vec<_> = classes[0].methods.iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(methods, ["Seconds"]);
+}
@@ -0,0 +1,108 @@
+use astd::{ConstantDetail, extract_constants};
+
+#[test]
+fn test_namespace_constants() {
+    let source = r#"
+namespace absl {
+constexpr int kMax = 42;
+inline constexpr absl::string_view kName = "absl";
+const char* const kPrefixes[] = {"a", "b"};
+ABSL_CONST_INIT extern const int kExternal;
+constexpr Duration InfiniteDuration();
+inline constexpr Duration ZeroDuration() { return Duration(); }
+int mutable_global = 3;
+constexpr absl::Span<const int> kEmpty{};
+constexpr std::pair<int, int> kRange = {
+    0,
+    1 << 4,
+};
+namespace time_internal {
+static constexpr uint32_t kTicksPerSecond = 1000 * 1000 * 1000;
+}  // namespace time_internal
+}  // namespace absl
+"#;
+    let constants = extract_constants(source);
+    let summary: Vec<_> = constants
+        .iter()
+        .map(|c| {
+            (
+                c.name.as_str(),
+                c.type_name.as_str(),
+                c.initializer.as_deref(),
+                c.is_constexpr(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            ("kMax", "int", Some("42"), true),
+            ("kName", "absl::string_view", Some("\"absl\""), true),
+            (
+                "kPrefixes",
+                "const char* const[]",
+                Some("{\"a\", \"b\"}"),
+                false
+            ),
+            ("kExternal", "const int", None, false),
+            ("kEmpty", "absl::Span<const int>", Some("{}"), true),
+            (
+                "kRange",
+                "std::pair<int, int>",
+                Some("{ 0, 1 << 4, }"),
+                true
+            ),
+            (
+                "kTicksPerSecond",
+                "uint32_t",
+                Some("1000 * 1000 * 1000"),
+                true
+            ),
+        ]
+    );
+    assert_eq!(constants[1].specifiers, ["inline", "constexpr"]);
+    assert_eq!(constants[0].namespace.as_deref(), Some("absl"));
+    assert_eq!(constants[0].line, 3);
+    assert_eq!(
+        constants[6].namespace.as_deref(),
+        Some("absl::time_internal")
+    );
+}
+
+#[test]
+fn test_static_member_constant() {
+    let source = r#"
+namespace absl {
+class Cord {
+ public:
+  static constexpr size_t kMaxInline = 15;
+  static const int kFlags;
+  int size() const;
+};
+}  // namespace absl
+"#;
+    let constants = extract_constants(source);
+    assert_eq!(
+        constants,
+        [
+            ConstantDetail {
+                specifiers: vec!["static".to_owned(), "constexpr".to_owned()],
+                type_name: "size_t".to_owned(),
+                name: "kMaxInline".to_owned(),
+                initializer: Some("15".to_owned()),
+                namespace: Some("absl".to_owned()),
+                enclosing_type: Some("Cord".to_owned()),
+                line: 5,
+            },
+            ConstantDetail {
+                specifiers: vec!["static".to_owned()],
+                type_name: "const int".to_owned(),
+                name: "kFlags".to_owned(),
+                initializer: None,
+                namespace: Some("absl".to_owned()),
+                enclosing_type: Some("Cord".to_owned()),
+                line: 6,
+            },
+        ]
+    );
+}
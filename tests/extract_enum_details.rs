@@ -0,0 +1,167 @@
+use astd::{
+    EnumDef, Enumerator, extract_enum_details, extract_enum_details_checked, extract_enums,
+};
+
+fn enumerator(name: &str, value: Option<&str>) -> Enumerator {
+    Enumerator {
+        name: name.to_owned(),
+        value: value.map(str::to_owned),
+    }
+}
+
+#[test]
+fn test_status_code() {
+    // From absl/status/status.h.
+    let source = r#"
+namespace absl {
+
+// absl::StatusCode
+//
+// An `absl::StatusCode` is an enumerated type indicating either no error ("OK")
+// or an error condition.
+enum class StatusCode : int {
+  // StatusCode::kOk
+  //
+  // kOK (gRPC code "OK") does not indicate an error; this value is returned on
+  // success.
+  kOk = 0,
+  kCancelled = 1,
+  kUnknown = 2,
+  kInvalidArgument = 3,
+  kDeadlineExceeded = 4,
+  kNotFound = 5,
+  kAlreadyExists = 6,
+  kPermissionDenied = 7,
+  kResourceExhausted = 8,
+  kFailedPrecondition = 9,
+  kAborted = 10,
+  kOutOfRange = 11,
+  kUnimplemented = 12,
+  kInternal = 13,
+  kUnavailable = 14,
+  kDataLoss = 15,
+  kUnauthenticated = 16,
+  // The purpose of this enumerated value is to force people who handle status
+  // codes with `switch()` statements to *not* simply enumerate all possible
+  // values, but instead provide a "default:" case.
+  kDoNotUseReservedForFutureExpansionUseDefaultInSwitchInstead_ = 20
+};
+
+}  // namespace absl
+"#;
+    let enums = extract_enum_details(source);
+    assert_eq!(enums.len(), 1);
+    let status = &enums[0];
+    assert_eq!(status.name.as_deref(), Some("StatusCode"));
+    assert!(status.is_scoped);
+    assert_eq!(status.underlying_type.as_deref(), Some("int"));
+    assert_eq!(status.namespace.as_deref(), Some("absl"));
+    assert_eq!(status.line, 8);
+    let names: Vec<_> = status.enumerators.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names.len(), 18);
+    assert_eq!(names[0], "kOk");
+    assert_eq!(names[16], "kUnauthenticated");
+    assert_eq!(
+        status.enumerators[17],
+        enumerator(
+            "kDoNotUseReservedForFutureExpansionUseDefaultInSwitchInstead_",
+            Some("20")
+        )
+    );
+    assert!(status.enumerators.iter().all(|e| e.value.is_some()));
+}
+
+#[test]
+fn test_expression_values_and_trailing_comma() {
+    let source = r#"
+class Flags {
+ public:
+  enum Bits : uint8_t {
+    kNone,
+    kRead = 1 << 0,
+    kWrite = 1 << 1,
+    kBoth = kRead | kWrite,
+    kMax = (kBoth > 2 ? kBoth : 2),
+  };
+};
+enum { kAnonymous = 'a' };
+enum class Opaque : int;
+"#;
+    let enums = extract_enum_details(source);
+    assert_eq!(enums.len(), 2);
+    let bits = &enums[0];
+    assert_eq!(bits.name.as_deref(), Some("Bits"));
+    assert!(!bits.is_scoped);
+    assert_eq!(bits.underlying_type.as_deref(), Some("uint8_t"));
+    assert_eq!(bits.enclosing_type.as_deref(), Some("Flags"));
+    assert_eq!(
+        bits.enumerators,
+        [
+            enumerator("kNone", None),
+            enumerator("kRead", Some("1 << 0")),
+            enumerator("kWrite", Some("1 << 1")),
+            enumerator("kBoth", Some("kRead | kWrite")),
+            enumerator("kMax", Some("(kBoth > 2 ? kBoth : 2)")),
+        ]
+    );
+    assert_eq!(enums[1].name, None);
+    assert_eq!(
+        enums[1].enumerators,
+        [enumerator("kAnonymous", Some("'a'"))]
+    );
+}
+
+#[test]
+fn test_plain_and_scoped_enums() {
+    let source = r#"
+namespace absl {
+enum Color { Red, Green = 5 };
+class Result {
+ public:
+  enum class Status : int { Ok, Err };
+};
+}
+"#;
+    let enums = extract_enums(source);
+    assert_eq!(
+        enums,
+        [
+            EnumDef {
+                name: Some("Color".to_owned()),
+                is_scoped: false,
+                underlying_type: None,
+                enumerators: vec![enumerator("Red", None), enumerator("Green", Some("5"))],
+                namespace: Some("absl".to_owned()),
+                enclosing_type: None,
+                line: 3,
+            },
+            EnumDef {
+                name: Some("Status".to_owned()),
+                is_scoped: true,
+                underlying_type: Some("int".to_owned()),
+                enumerators: vec![enumerator("Ok", None), enumerator("Err", None)],
+                namespace: Some("absl".to_owned()),
+                enclosing_type: Some("Result".to_owned()),
+                line: 6,
+            },
+        ]
+    );
+    assert_eq!(enums, extract_enum_details(source));
+}
+
+#[test]
+fn test_unclosed_body_is_skipped_with_a_diagnostic() {
+    for source in [
+        "enum E {",
+        "namespace a { enum class E : int {",
+        "enum E { A, B",
+    ] {
+        let (enums, diagnostics) = extract_enum_details_checked(source);
+        assert!(enums.is_empty(), "{}", source);
+        assert_eq!(diagnostics.len(), 1, "{}", source);
+        assert_eq!(diagnostics[0].reason, "unclosed enum body");
+    }
+    let (enums, diagnostics) = extract_enum_details_checked("enum E { A }");
+    assert_eq!(enums[0].enumerators, [enumerator("A", None)]);
+    assert!(diagnostics.is_empty());
+}
@@ -0,0 +1,76 @@
+use astd::extract_from_dir;
+use std::fs;
+
+mod scratch;
+use scratch::scratch_dir;
+
+#[test]
+fn test_nested_headers_in_path_order() {
+    let root = scratch_dir("nested");
+    fs::create_dir_all(root.join("absl/strings/internal")).unwrap();
+    fs::create_dir_all(root.join("absl/base")).unwrap();
+    fs::write(
+        root.join("absl/strings/str_cat.h"),
+        "namespace absl {\nstd::string StrCat();\n}",
+    )
+    .unwrap();
+    fs::write(
+        root.join("absl/strings/internal/utf8.inc"),
+        "size_t EncodeUTF8Char(char* buffer, char32_t utf8_char);",
+    )
+    .unwrap();
+    fs::write(root.join("absl/base/macros.h"), "int f(int x);\nint g();").unwrap();
+    fs::write(
+        root.join("absl/base/macros.cc"),
+        "int f(int x) { return x; }",
+    )
+    .unwrap();
+    // Not valid UTF-8, which is replaced rather than failing the read.
+    fs::write(root.join("absl/base/latin1.h"), b"// \xe9t\xe9\nint h();").unwrap();
+    // A dangling symlink, so it cannot be read at all.
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(root.join("absent.h"), root.join("absl/base/broken.h")).unwrap();
+    let extracted = extract_from_dir(&root).unwrap();
+    let summary: Vec<_> = extracted
+        .headers
+        .iter()
+        .map(|(path, details)| {
+            (
+                path.strip_prefix(&root)
+                    .unwrap()
+                    .to_string_lossy()
+                    .replace('\\', "/"),
+                details.iter().map(|d| d.name.as_str()).collect::<Vec<_>>(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            ("absl/base/latin1.h".to_owned(), vec!["h"]),
+            ("absl/base/macros.h".to_owned(), vec!["f", "g"]),
+            (
+                "absl/strings/internal/utf8.inc".to_owned(),
+                vec!["EncodeUTF8Char"]
+            ),
+            ("absl/strings/str_cat.h".to_owned(), vec!["StrCat"]),
+        ]
+    );
+    let (path, details) = &extracted.headers[0];
+    assert!(details.iter().all(|d| d.path.as_ref() == Some(path)));
+    #[cfg(unix)]
+    {
+        assert_eq!(extracted.errors.len(), 1);
+        let (path, err) = &extracted.errors[0];
+        assert_eq!(path, &root.join("absl/base/broken.h"));
+        assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+    }
+}
+
+#[test]
+fn test_missing_directory_is_an_error() {
+    let root = scratch_dir("missing");
+    assert!(extract_from_dir(&root.join("absent")).is_err());
+    let extracted = extract_from_dir(&root).unwrap();
+    assert!(extracted.headers.is_empty() && extracted.errors.is_empty());
+}
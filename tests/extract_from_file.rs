@@ -0,0 +1,77 @@
+use astd::{IncludeDirective, IncludeKind, extract_from_file, extract_includes};
+use std::fs;
+
+mod scratch;
+use scratch::scratch_dir;
+
+#[test]
+fn test_header_with_two_includes() {
+    let root = scratch_dir("includes");
+    fs::write(root.join("ascii.h"), "char ascii_tolower(char c);").unwrap();
+    fs::write(root.join("string_view.h"), "class string_view;").unwrap();
+    let header = root.join("match.h");
+    fs::write(
+        &header,
+        r#"#ifndef ABSL_STRINGS_MATCH_H_
+#define ABSL_STRINGS_MATCH_H_
+
+#include <cstring>
+#include "ascii.h"
+  #  include "string_view.h"
+// #include "commented_out.h"
+
+namespace absl {
+bool StrContains(absl::string_view haystack, absl::string_view needle);
+bool EqualsIgnoreCase(absl::string_view a, absl::string_view b);
+}  // namespace absl
+
+#endif  // ABSL_STRINGS_MATCH_H_
+"#,
+    )
+    .unwrap();
+    let details = extract_from_file(&header).unwrap();
+    assert_eq!(details.path, header);
+    assert_eq!(
+        details.includes,
+        [
+            IncludeDirective {
+                path: "cstring".into(),
+                kind: IncludeKind::System,
+                line: 4,
+            },
+            IncludeDirective {
+                path: "ascii.h".into(),
+                kind: IncludeKind::Quoted,
+                line: 5,
+            },
+            IncludeDirective {
+                path: "string_view.h".into(),
+                kind: IncludeKind::Quoted,
+                line: 6,
+            },
+        ]
+    );
+    assert_eq!(details.system_includes().collect::<Vec<_>>(), ["cstring"]);
+    assert_eq!(
+        details.quoted_includes().collect::<Vec<_>>(),
+        ["ascii.h", "string_view.h"]
+    );
+    let names: Vec<_> = details.functions.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(names, ["StrContains", "EqualsIgnoreCase"]);
+    assert!(
+        details
+            .functions
+            .iter()
+            .all(|f| f.path.as_deref() == Some(header.as_path()))
+    );
+    assert!(extract_from_file(&root.join("missing.h")).is_err());
+}
+
+#[test]
+fn test_includes_in_comments_and_strings_are_ignored() {
+    let src = "/* #include <hidden> */\nconst char* s = \"#include <nope>\";\n#include <map>\n";
+    let includes = extract_includes(src);
+    assert_eq!(includes.len(), 1);
+    assert_eq!(includes[0].path, "map");
+    assert_eq!(includes[0].line, 3);
+}
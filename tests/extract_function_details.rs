@@ -1,14 +1,22 @@
-use astd::extract_function_details;
+use astd::{
+    ANONYMOUS_NAMESPACE, Access, ExtractOptions, FunctionKind, Linkage, NamespaceMacro, Parameter,
+    RefQualifier, SpecialDefinition, TemplateParameter, TemplateParameterKind,
+    extract_function_details, extract_function_details_checked, extract_function_details_iter,
+    extract_function_details_with_attributes, extract_function_details_with_conventions,
+    extract_function_details_with_options, extract_public_function_details, group_overloads,
+    normalize_type, parse_template_parameters, strip_preprocessor,
+};
+use std::collections::HashMap;
 
 #[test]
 fn test_simple_function() {
     let source = "int my_function(int a, float b);";
     let extracted = extract_function_details(source);
     assert_eq!(extracted.len(), 1);
-    let (prefix, ret, name) = &extracted[0];
-    assert_eq!(prefix, "");
-    assert_eq!(ret, "int");
-    assert_eq!(name, "my_function");
+    let detail = &extracted[0];
+    assert_eq!(detail.template_prefix, "");
+    assert_eq!(detail.return_type, "int");
+    assert_eq!(detail.name, "my_function");
 }
 
 #[test]
@@ -16,10 +24,10 @@ fn test_function_with_template() {
     let source = "template <typename T> T func_template(T a);";
     let extracted = extract_function_details(source);
     assert_eq!(extracted.len(), 1);
-    let (prefix, ret, name) = &extracted[0];
-    assert_eq!(prefix, "template <typename T>");
-    assert_eq!(ret, "T");
-    assert_eq!(name, "func_template");
+    let detail = &extracted[0];
+    assert_eq!(detail.template_prefix, "template <typename T>");
+    assert_eq!(detail.return_type, "T");
+    assert_eq!(detail.name, "func_template");
 }
 
 #[test]
@@ -33,12 +41,10 @@ fn test_functions_with_comments() {
     "#;
     let extracted = extract_function_details(source);
     assert_eq!(extracted.len(), 2);
-    let (_, ret1, name1) = &extracted[0];
-    assert_eq!(ret1, "int");
-    assert_eq!(name1, "sum");
-    let (_, ret2, name2) = &extracted[1];
-    assert_eq!(ret2, "double");
-    assert_eq!(name2, "average");
+    assert_eq!(extracted[0].return_type, "int");
+    assert_eq!(extracted[0].name, "sum");
+    assert_eq!(extracted[1].return_type, "double");
+    assert_eq!(extracted[1].name, "average");
 }
 
 #[test]
@@ -46,7 +52,2481 @@ fn test_complex_signature() {
     let source = "const std::vector<int>& get_vector() const;";
     let extracted = extract_function_details(source);
     assert_eq!(extracted.len(), 1);
-    let (_, ret, name) = &extracted[0];
-    assert_eq!(ret, "const std::vector<int>&");
-    assert_eq!(name, "get_vector");
+    assert_eq!(extracted[0].return_type, "const std::vector<int>&");
+    assert_eq!(extracted[0].name, "get_vector");
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_tuple_shim() {
+    let source = "template <typename T> T func_template(T a);";
+    let extracted = astd::extract_function_details_tuples(source);
+    assert_eq!(
+        extracted,
+        vec![(
+            "template <typename T>".to_owned(),
+            "T".to_owned(),
+            "func_template".to_owned()
+        )]
+    );
+}
+
+#[test]
+fn test_parameters() {
+    let source = "int sum(int a, int b);";
+    let extracted = extract_function_details(source);
+    let params: Vec<_> = extracted[0]
+        .parameters
+        .iter()
+        .map(|p| (p.type_name.as_str(), p.name.as_deref()))
+        .collect();
+    assert_eq!(params, [("int", Some("a")), ("int", Some("b"))]);
+}
+
+#[test]
+fn test_parameters_with_nested_templates() {
+    let source = "void merge(const std::map<int, std::string>& m, std::pair<int, int> p);";
+    let extracted = extract_function_details(source);
+    let params = &extracted[0].parameters;
+    assert_eq!(params.len(), 2);
+    assert_eq!(params[0].type_name, "const std::map<int, std::string>&");
+    assert_eq!(params[0].name.as_deref(), Some("m"));
+    assert_eq!(params[1].type_name, "std::pair<int, int>");
+    assert_eq!(params[1].name.as_deref(), Some("p"));
+}
+
+#[test]
+fn test_function_pointer_parameter() {
+    let source = "void sort(void* base, int (*cmp)(const void*, const void*));";
+    let extracted = extract_function_details(source);
+    let params = &extracted[0].parameters;
+    assert_eq!(params.len(), 2);
+    assert_eq!(params[0].type_name, "void*");
+    assert_eq!(params[0].name.as_deref(), Some("base"));
+    assert_eq!(params[1].type_name, "int (*)(const void*, const void*)");
+    assert_eq!(params[1].name.as_deref(), Some("cmp"));
+}
+
+#[test]
+fn test_empty_parameter_lists() {
+    let extracted = extract_function_details("int f();\nint g(void);");
+    assert!(extracted[0].parameters.is_empty());
+    assert!(extracted[1].parameters.is_empty());
+}
+
+#[test]
+fn test_parameter_defaults() {
+    let source = "int my_function(int a, float b = 1.0);";
+    let extracted = extract_function_details(source);
+    assert_eq!(
+        extracted[0].parameters,
+        [
+            Parameter {
+                type_name: "int".to_owned(),
+                name: Some("a".to_owned()),
+                default: None,
+                is_pack: false,
+            },
+            Parameter {
+                type_name: "float".to_owned(),
+                name: Some("b".to_owned()),
+                default: Some("1.0".to_owned()),
+                is_pack: false,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_unnamed_parameters() {
+    let source = "void f(int, const std::string&, absl::string_view = {});";
+    let extracted = extract_function_details(source);
+    let params = &extracted[0].parameters;
+    assert_eq!(params.len(), 3);
+    assert!(params.iter().all(|p| p.name.is_none()));
+    assert_eq!(params[0].type_name, "int");
+    assert_eq!(params[1].type_name, "const std::string&");
+    assert_eq!(params[2].type_name, "absl::string_view");
+    assert_eq!(params[2].default.as_deref(), Some("{}"));
+}
+
+#[test]
+fn test_all_unnamed_parameters_keep_their_types() {
+    let extracted = extract_function_details("void Ignore(int, const Foo&);");
+    let params = &extracted[0].parameters;
+    assert_eq!(params.len(), 2);
+    assert_eq!(params[0].type_name, "int");
+    assert_eq!(params[1].type_name, "const Foo&");
+    assert!(params.iter().all(|p| p.name.is_none()));
+    assert_eq!(params[0].name_or_placeholder(0), "arg0");
+    assert_eq!(params[1].name_or_placeholder(1), "arg1");
+}
+
+#[test]
+fn test_mixed_named_and_unnamed_parameters() {
+    let source = "void f(int);
+void f(int x);
+void g(unsigned, long long n, Tag, Tag t);";
+    let extracted = extract_function_details(source);
+    assert_eq!(extracted[0].parameters[0].name, None);
+    assert_eq!(extracted[1].parameters[0].name.as_deref(), Some("x"));
+    let named: Vec<_> = extracted[2]
+        .parameters
+        .iter()
+        .map(|p| (p.type_name.as_str(), p.name.as_deref()))
+        .collect();
+    assert_eq!(
+        named,
+        [
+            ("unsigned", None),
+            ("long long", Some("n")),
+            ("Tag", None),
+            ("Tag", Some("t")),
+        ]
+    );
+    assert_eq!(extracted[2].parameters[1].name_or_placeholder(1), "n");
+}
+
+#[test]
+fn test_void_parameter_list_is_empty() {
+    let extracted = extract_function_details(
+        "int V(void);
+int W( void );",
+    );
+    assert!(extracted[0].parameters.is_empty());
+    assert!(extracted[1].parameters.is_empty());
+}
+
+#[test]
+fn test_is_templated() {
+    let source = "template <typename T> T func_template(T a);\nint plain(int a);";
+    let extracted = extract_function_details(source);
+    assert!(extracted[0].is_templated());
+    assert!(!extracted[1].is_templated());
+}
+
+#[test]
+fn test_arity() {
+    let source = "int none();\nint two(int a, int b);";
+    let extracted = extract_function_details(source);
+    assert_eq!(extracted[0].arity(), 0);
+    assert_eq!(extracted[1].arity(), 2);
+}
+
+#[test]
+fn test_line_comment_declaration_is_ignored() {
+    assert!(extract_function_details("// int ghost(int);").is_empty());
+}
+
+#[test]
+fn test_block_comment_declaration_is_ignored() {
+    let source = "/*\nint ghost(int);\ndouble phantom(double x);\n*/\nint real(int a);";
+    let extracted = extract_function_details(source);
+    assert_eq!(extracted.len(), 1);
+    assert_eq!(extracted[0].name, "real");
+}
+
+#[test]
+fn test_line_comment_continuation() {
+    let source = "// a comment that continues \\\nint ghost(int);\nint real(int a);";
+    let extracted = extract_function_details(source);
+    assert_eq!(extracted.len(), 1);
+    assert_eq!(extracted[0].name, "real");
+}
+
+#[test]
+fn test_string_literal_with_parens() {
+    let source = r#"void log(const char* msg = "a string with ( parens", int level);"#;
+    let extracted = extract_function_details(source);
+    assert_eq!(extracted.len(), 1);
+    let params = &extracted[0].parameters;
+    assert_eq!(params.len(), 2);
+    assert_eq!(
+        params[0].default.as_deref(),
+        Some(r#""a string with ( parens""#)
+    );
+    assert_eq!(params[1].name.as_deref(), Some("level"));
+}
+
+#[test]
+fn test_string_literal_declaration_is_ignored() {
+    let source = "const char* kExample =\n\"int ghost(int);\\n\"\n\"int phantom(int);\";";
+    assert!(extract_function_details(source).is_empty());
+}
+
+#[test]
+fn test_raw_string_declaration_is_ignored() {
+    let source = "const char* kExample = R\"cpp(\nint ghost(int);\n)cpp\";\nint real(int a);";
+    let extracted = extract_function_details(source);
+    assert_eq!(extracted.len(), 1);
+    assert_eq!(extracted[0].name, "real");
+}
+
+#[test]
+fn test_raw_string_parentheses_do_not_leak() {
+    let source = r#"
+const char* kBalanced = R"((int fake(int)))";
+const char* kUnbalanced = R"delim(int fake2(int x; ) ( {)delim";
+const char* kPrefixed = u8R"~(}){ int fake3();)~";
+char Close(char c = ')');
+int After(int x);
+"#;
+    let names: Vec<_> = extract_function_details(source)
+        .into_iter()
+        .map(|d| d.name)
+        .collect();
+    assert_eq!(names, ["Close", "After"]);
+}
+
+#[test]
+fn test_char_literal_paren() {
+    let source = "bool is_open(char c = '(', int n = 1'000);";
+    let extracted = extract_function_details(source);
+    let params = &extracted[0].parameters;
+    assert_eq!(params.len(), 2);
+    assert_eq!(params[0].default.as_deref(), Some("'('"));
+    assert_eq!(params[1].default.as_deref(), Some("1'000"));
+}
+
+#[test]
+fn test_namespace_from_str_cat() {
+    let source = r#"
+namespace absl {
+ABSL_NAMESPACE_BEGIN
+
+namespace strings_internal {
+
+// AlphaNumBuffer allows a way to pass a string to StrCat without having to do
+// memory allocation.
+template <size_t max_size>
+struct AlphaNumBuffer {
+  std::array<char, max_size> data;
+  size_t size;
+};
+
+std::string StrCatInternal(std::initializer_list<absl::string_view> pieces);
+
+}  // namespace strings_internal
+
+ABSL_MUST_USE_RESULT std::string StrCat(const AlphaNum& a, const AlphaNum& b);
+
+ABSL_NAMESPACE_END
+}  // namespace absl
+"#;
+    let extracted = extract_function_details(source);
+    let str_cat = extracted.iter().find(|d| d.name == "StrCat").unwrap();
+    assert_eq!(str_cat.namespace.as_deref(), Some("absl"));
+    let internal = extracted
+        .iter()
+        .find(|d| d.name == "StrCatInternal")
+        .unwrap();
+    assert_eq!(
+        internal.namespace.as_deref(),
+        Some("absl::strings_internal")
+    );
+}
+
+// Adapted from absl/strings/str_split.h.
+const STR_SPLIT: &str = r#"
+#include "absl/base/config.h"
+
+namespace absl {
+ABSL_NAMESPACE_BEGIN
+
+namespace strings_internal {
+absl::string_view::size_type Find(absl::string_view text, size_t pos);
+}  // namespace strings_internal
+
+class ByString {
+ public:
+  explicit ByString(absl::string_view sp);
+  absl::string_view Find(absl::string_view text, size_t pos) const;
+};
+
+ABSL_NAMESPACE_END
+}  // namespace absl
+
+int Global();
+"#;
+
+#[test]
+fn test_namespace_macros_open_an_inline_namespace() {
+    let extracted = extract_function_details(STR_SPLIT);
+    let scopes: Vec<_> = extracted
+        .iter()
+        .map(|d| {
+            (
+                d.namespace.as_deref(),
+                d.enclosing_type.as_deref(),
+                d.name.as_str(),
+                d.return_type.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        scopes,
+        [
+            (
+                Some("absl::strings_internal"),
+                None,
+                "Find",
+                "absl::string_view::size_type"
+            ),
+            (Some("absl"), Some("ByString"), "ByString", ""),
+            (Some("absl"), Some("ByString"), "Find", "absl::string_view"),
+            (None, None, "Global", "int"),
+        ]
+    );
+}
+
+#[test]
+fn test_inline_namespaces_can_be_kept() {
+    let options = ExtractOptions {
+        keep_inline_namespaces: true,
+        ..ExtractOptions::default()
+    };
+    let (extracted, _) = extract_function_details_with_options(STR_SPLIT, &options);
+    let namespaces: Vec<_> = extracted.iter().map(|d| d.namespace.as_deref()).collect();
+    assert_eq!(
+        namespaces,
+        [
+            Some("absl::lts_20250127::strings_internal"),
+            Some("absl::lts_20250127"),
+            Some("absl::lts_20250127"),
+            None,
+        ]
+    );
+}
+
+#[test]
+fn test_literal_inline_namespace_is_collapsed() {
+    let source = "namespace absl {\ninline namespace lts_20240116 {\nint StrCat();\n}\n}";
+    assert_eq!(
+        extract_function_details(source)[0].namespace.as_deref(),
+        Some("absl")
+    );
+    let options = ExtractOptions {
+        keep_inline_namespaces: true,
+        ..ExtractOptions::default()
+    };
+    let (extracted, _) = extract_function_details_with_options(source, &options);
+    assert_eq!(
+        extracted[0].namespace.as_deref(),
+        Some("absl::lts_20240116")
+    );
+}
+
+#[test]
+fn test_registered_namespace_macros() {
+    let source =
+        "namespace google {\nPROTOBUF_NAMESPACE_OPEN\nint Parse();\nPROTOBUF_NAMESPACE_CLOSE\n}";
+    let options = ExtractOptions {
+        namespace_macros: vec![NamespaceMacro {
+            begin: "PROTOBUF_NAMESPACE_OPEN",
+            end: "PROTOBUF_NAMESPACE_CLOSE",
+            name: "v3",
+        }],
+        keep_inline_namespaces: true,
+        ..ExtractOptions::default()
+    };
+    let (extracted, _) = extract_function_details_with_options(source, &options);
+    assert_eq!(extracted[0].return_type, "int");
+    assert_eq!(extracted[0].namespace.as_deref(), Some("google::v3"));
+    // Unregistered, the macros are just words in front of the declaration.
+    assert_ne!(extract_function_details(source)[0].return_type, "int");
+}
+
+#[test]
+fn test_nested_namespaces() {
+    let source = r#"
+int global(int a);
+namespace absl {
+namespace strings_internal {
+int inner(int a);
+}  // namespace strings_internal
+int outer(int a);
+}  // namespace absl
+namespace absl::container_internal {
+int compact(int a);
+}
+"#;
+    let namespaces: Vec<_> = extract_function_details(source)
+        .into_iter()
+        .map(|d| (d.name, d.namespace))
+        .collect();
+    assert_eq!(
+        namespaces,
+        [
+            ("global".to_owned(), None),
+            (
+                "inner".to_owned(),
+                Some("absl::strings_internal".to_owned())
+            ),
+            ("outer".to_owned(), Some("absl".to_owned())),
+            (
+                "compact".to_owned(),
+                Some("absl::container_internal".to_owned())
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_anonymous_namespace_and_using() {
+    let source = r#"
+using namespace std;
+namespace {
+int hidden(int a);
+}
+using namespace absl;
+int visible(int a);
+"#;
+    let extracted = extract_function_details(source);
+    assert_eq!(extracted[0].namespace.as_deref(), Some(ANONYMOUS_NAMESPACE));
+    assert_eq!(extracted[1].namespace, None);
+}
+
+#[test]
+fn test_member_functions_record_their_class() {
+    let source = r#"
+class Foo;
+int free_function(int a);
+class Foo {
+ public:
+  int bar();
+};
+struct Outer {
+  struct Inner {
+    void reset();
+  };
+  int size() const;
+};
+class Sealed final : public Foo {
+ public:
+  bool done() const;
+};
+"#;
+    let owners: Vec<_> = extract_function_details(source)
+        .into_iter()
+        .map(|d| (d.name, d.return_type, d.enclosing_type))
+        .collect();
+    let owner = |s: &str| Some(s.to_owned());
+    assert_eq!(
+        owners,
+        [
+            ("free_function".to_owned(), "int".to_owned(), None),
+            ("bar".to_owned(), "int".to_owned(), owner("Foo")),
+            ("reset".to_owned(), "void".to_owned(), owner("Outer::Inner")),
+            ("size".to_owned(), "int".to_owned(), owner("Outer")),
+            ("done".to_owned(), "bool".to_owned(), owner("Sealed")),
+        ]
+    );
+}
+
+#[test]
+fn test_enum_class_is_not_an_owner() {
+    let source = "namespace absl {\nenum class StatusCode : int { kOk = 0 };\nint code(int a);\n}";
+    let extracted = extract_function_details(source);
+    assert_eq!(extracted.len(), 1);
+    assert_eq!(extracted[0].enclosing_type, None);
+    assert_eq!(extracted[0].namespace.as_deref(), Some("absl"));
+}
+
+#[test]
+fn test_multi_line_signatures_from_str_split() {
+    let source = r#"
+template <typename... AV>
+inline std::string StrCat(
+    const AlphaNum& a, const AlphaNum& b, const AlphaNum& c,
+    const AlphaNum& d, const AlphaNum& e, const AV&... args);
+
+ABSL_MUST_USE_RESULT inline absl::string_view
+StripPrefix(absl::string_view str, absl::string_view prefix);
+
+template <typename T>
+T
+ParseValue(
+    absl::string_view text,
+    int base);
+"#;
+    let extracted = extract_function_details(source);
+    let names: Vec<_> = extracted.iter().map(|d| d.name.as_str()).collect();
+    assert_eq!(names, ["StrCat", "StripPrefix", "ParseValue"]);
+    assert_eq!(extracted[0].template_prefix, "template <typename... AV>");
+    assert_eq!(extracted[0].return_type, "std::string");
+    assert_eq!(extracted[0].parameters.len(), 6);
+    assert_eq!(extracted[1].parameters.len(), 2);
+    assert_eq!(extracted[2].template_prefix, "template <typename T>");
+    assert_eq!(extracted[2].return_type, "T");
+    let params: Vec<_> = extracted[2]
+        .parameters
+        .iter()
+        .map(|p| (p.type_name.as_str(), p.name.as_deref()))
+        .collect();
+    assert_eq!(
+        params,
+        [("absl::string_view", Some("text")), ("int", Some("base"))]
+    );
+}
+
+#[test]
+fn test_declarations_sharing_a_line() {
+    let source = "class Foo { public: int bar(); };";
+    let extracted = extract_function_details(source);
+    assert_eq!(extracted.len(), 1);
+    assert_eq!(extracted[0].return_type, "int");
+    assert_eq!(extracted[0].enclosing_type.as_deref(), Some("Foo"));
+}
+
+#[test]
+fn test_leading_specifiers() {
+    let source = r#"
+static inline int compute(int a);
+constexpr explicit bool ready();
+class Widget {
+ public:
+  virtual void draw();
+  friend bool check(const Widget& w);
+};
+extern double ratio(double a);
+consteval int size();
+"#;
+    let extracted = extract_function_details(source);
+    let specs: Vec<_> = extracted
+        .iter()
+        .map(|d| (d.specifiers.join(" "), d.return_type.as_str()))
+        .collect();
+    assert_eq!(
+        specs,
+        [
+            ("static inline".to_owned(), "int"),
+            ("constexpr explicit".to_owned(), "bool"),
+            ("virtual".to_owned(), "void"),
+            ("friend".to_owned(), "bool"),
+            ("extern".to_owned(), "double"),
+            ("consteval".to_owned(), "int"),
+        ]
+    );
+}
+
+#[test]
+fn test_no_specifiers() {
+    let extracted = extract_function_details("const std::string& name();");
+    assert!(extracted[0].specifiers.is_empty());
+    assert_eq!(extracted[0].return_type, "const std::string&");
+}
+
+#[test]
+fn test_commented_out_old_api() {
+    let source = "// old API: int legacy_fn(int);\nint current_fn(int a);";
+    let names: Vec<_> = extract_function_details(source)
+        .into_iter()
+        .map(|d| d.name)
+        .collect();
+    assert_eq!(names, ["current_fn"]);
+}
+
+#[test]
+fn test_doc_comment_containing_a_prototype() {
+    let source = r#"
+// Example:
+//
+//   std::string s = WrapText(str, 80);
+//   int Unwrap(std::string text);
+std::string WrapText(absl::string_view text, int width);
+"#;
+    let extracted = extract_function_details(source);
+    assert_eq!(extracted.len(), 1);
+    assert_eq!(extracted[0].name, "WrapText");
+}
+
+#[test]
+fn test_multi_line_block_comment_with_fake_prototypes() {
+    let source = r#"
+/* Deprecated overloads, kept for reference:
+int first(int a);
+double second(double b);
+   void third();
+*/
+int kept(int a);
+"#;
+    let extracted = extract_function_details(source);
+    assert_eq!(extracted.len(), 1);
+    assert_eq!(extracted[0].name, "kept");
+}
+
+#[test]
+fn test_comment_markers_inside_strings_are_kept() {
+    let source = r#"void emit(const char* a = "// not a comment", const char* b = "/* nor this");"#;
+    let extracted = extract_function_details(source);
+    let defaults: Vec<_> = extracted[0]
+        .parameters
+        .iter()
+        .map(|p| p.default.as_deref())
+        .collect();
+    assert_eq!(
+        defaults,
+        [Some(r#""// not a comment""#), Some(r#""/* nor this""#)]
+    );
+}
+
+#[test]
+fn test_trailing_qualifiers() {
+    let source = r#"
+class Widget : public Base {
+ public:
+  const std::vector<int>& get_vector() const;
+  size_t size() const noexcept;
+  void swap(Widget& other) noexcept(noexcept(other.clear()));
+  void draw() const override;
+  void seal() final;
+  T& value() &;
+  T&& value() &&;
+  virtual void reset() = 0;
+  int count();
+};
+"#;
+    let qualifiers: Vec<_> = extract_function_details(source)
+        .into_iter()
+        .map(|d| d.trailing_qualifiers.join(" "))
+        .collect();
+    assert_eq!(
+        qualifiers,
+        [
+            "const",
+            "const noexcept",
+            "noexcept(noexcept(other.clear()))",
+            "const override",
+            "final",
+            "&",
+            "&&",
+            "= 0",
+            "",
+        ]
+    );
+}
+
+#[test]
+fn test_volatile_qualifier() {
+    let extracted = extract_function_details("int load() const volatile;");
+    assert_eq!(extracted[0].trailing_qualifiers, ["const", "volatile"]);
+}
+
+#[test]
+fn test_strip_preprocessor_multi_line_define() {
+    let source =
+        "#define ABSL_INTERNAL_FOO(x) \\\n  int x(int);   \\\n  int x##_2(int)\nint real(int a);\n";
+    let stripped = strip_preprocessor(source);
+    assert_eq!(stripped.len(), source.len());
+    assert_eq!(stripped.lines().count(), source.lines().count());
+    assert!(!stripped.contains("define"));
+    assert!(!stripped.contains("x##_2"));
+    assert!(stripped.contains("int real(int a);"));
+}
+
+#[test]
+fn test_strip_preprocessor_if_zero() {
+    let source = r#"
+#include "absl/base/config.h"
+#pragma once
+#if 0
+int disabled(int a);
+#if defined(_MSC_VER)
+int nested(int a);
+#endif
+#endif
+#if defined(_MSC_VER)
+int msvc_only(int a);
+#endif
+"#;
+    let stripped = strip_preprocessor(source);
+    assert!(!stripped.contains('#'));
+    assert!(!stripped.contains("disabled"));
+    assert!(!stripped.contains("nested"));
+    assert!(stripped.contains("int msvc_only(int a);"));
+}
+
+#[test]
+fn test_strip_preprocessor_if_zero_else_branch() {
+    let source = "#if 0\nint old_api(int a);\n#else\nint new_api(int a);\n#endif\n";
+    let stripped = strip_preprocessor(source);
+    assert!(!stripped.contains("old_api"));
+    assert!(stripped.contains("int new_api(int a);"));
+}
+
+#[test]
+fn test_macro_definitions_are_not_extracted() {
+    let source = r#"
+#define ABSL_INTERNAL_FOO(x) int x(int)
+#if defined(_MSC_VER)
+int real(int a);
+#endif
+"#;
+    let names: Vec<_> = extract_function_details(source)
+        .into_iter()
+        .map(|d| d.name)
+        .collect();
+    assert_eq!(names, ["real"]);
+}
+
+#[test]
+fn test_constructors_and_destructors() {
+    let source = r#"
+class Buffer {
+ public:
+  Buffer();
+  explicit Buffer(size_t capacity);
+  Buffer(const Buffer&) = delete;
+  Buffer(Buffer&& other) = default;
+  virtual ~Buffer();
+  size_t capacity() const;
+};
+"#;
+    let extracted = extract_function_details(source);
+    let kinds: Vec<_> = extracted
+        .iter()
+        .map(|d| (d.name.as_str(), d.kind, d.special_definition))
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            ("Buffer", FunctionKind::Constructor, None),
+            ("Buffer", FunctionKind::Constructor, None),
+            (
+                "Buffer",
+                FunctionKind::Constructor,
+                Some(SpecialDefinition::Delete)
+            ),
+            (
+                "Buffer",
+                FunctionKind::Constructor,
+                Some(SpecialDefinition::Default)
+            ),
+            ("~Buffer", FunctionKind::Destructor, None),
+            ("capacity", FunctionKind::Regular, None),
+        ]
+    );
+    assert!(extracted[0].parameters.is_empty());
+    assert_eq!(extracted[1].specifiers, ["explicit"]);
+    assert_eq!(extracted[1].return_type, "");
+    assert_eq!(extracted[1].parameters[0].type_name, "size_t");
+    assert_eq!(extracted[1].parameters[0].name.as_deref(), Some("capacity"));
+    assert_eq!(extracted[4].specifiers, ["virtual"]);
+    assert!(
+        extracted
+            .iter()
+            .all(|d| d.enclosing_type.as_deref() == Some("Buffer"))
+    );
+}
+
+#[test]
+fn test_constructor_call_outside_class_is_not_a_constructor() {
+    let extracted = extract_function_details("Buffer make_buffer(int n);");
+    assert_eq!(extracted[0].kind, FunctionKind::Regular);
+    assert_eq!(extracted[0].return_type, "Buffer");
+}
+
+#[test]
+fn test_operator_overloads() {
+    let source = r#"
+class Span {
+ public:
+  reference operator[](size_type i) const;
+  bool operator()(int a, int b) const;
+  explicit operator bool() const;
+  Span& operator=(const Span& other);
+  Span& operator+=(size_type n);
+  void* operator new[](size_t size);
+  friend bool operator==(const Span& a, const Span& b);
+  friend bool operator<=(const Span& a, const Span& b);
+  friend bool operator!=(const Span& a, const Span& b);
+  friend std::ostream& operator<<(std::ostream& os, const Span& s);
+};
+Span operator""_span(const char* s, size_t n);
+"#;
+    let extracted = extract_function_details(source);
+    let names: Vec<_> = extracted.iter().map(|d| d.name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "operator[]",
+            "operator()",
+            "operator bool",
+            "operator=",
+            "operator+=",
+            "operator new[]",
+            "operator==",
+            "operator<=",
+            "operator!=",
+            "operator<<",
+            "operator\"\"_span",
+        ]
+    );
+    assert_eq!(extracted[0].return_type, "reference");
+    assert_eq!(extracted[1].parameters.len(), 2);
+    assert_eq!(extracted[2].specifiers, ["explicit"]);
+    assert_eq!(extracted[3].return_type, "Span&");
+    assert_eq!(extracted[9].return_type, "std::ostream&");
+    assert_eq!(extracted[10].parameters.len(), 2);
+}
+
+fn member(decl: &str) -> astd::FunctionDetail {
+    let source = format!("class C {{\n public:\n  {}\n}};", decl);
+    let mut extracted = extract_function_details(&source);
+    assert_eq!(extracted.len(), 1, "parsing {}", decl);
+    extracted.remove(0)
+}
+
+#[test]
+fn test_const_qualifier() {
+    assert!(member("size_t size() const;").is_const());
+    assert!(!member("size_t size();").is_const());
+    assert!(!member("const char* data();").is_const());
+}
+
+#[test]
+fn test_noexcept_qualifier() {
+    assert!(member("void clear() noexcept;").is_noexcept());
+    assert!(member("void swap(C& o) noexcept(true);").is_noexcept());
+    assert!(!member("void clear();").is_noexcept());
+}
+
+#[test]
+fn test_override_and_final_qualifiers() {
+    let detail = member("void Send(const LogEntry& e) override;");
+    assert!(detail.is_override() && !detail.is_final());
+    let detail = member("void Flush() final;");
+    assert!(detail.is_final() && !detail.is_override());
+    let detail = member("void Flush() override final;");
+    assert!(detail.is_final() && detail.is_override());
+}
+
+#[test]
+fn test_ref_qualifiers() {
+    assert_eq!(
+        member("T& value() &;").ref_qualifier(),
+        Some(RefQualifier::LValue)
+    );
+    assert_eq!(
+        member("T&& value() &&;").ref_qualifier(),
+        Some(RefQualifier::RValue)
+    );
+    assert_eq!(member("T&& value();").ref_qualifier(), None);
+}
+
+#[test]
+fn test_const_and_ref_qualified_overloads_stay_apart() {
+    let source = r#"
+namespace absl {
+template <typename T>
+class StatusOr {
+ public:
+  const T& value() const&;
+  T& value() &;
+  const T&& value() const&&;
+  T&& value() &&;
+};
+}
+"#;
+    let details = extract_function_details(source);
+    let qualifiers: Vec<_> = details
+        .iter()
+        .map(|detail| {
+            (
+                detail.return_type.as_str(),
+                detail.is_const(),
+                detail.ref_qualifier(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        qualifiers,
+        [
+            ("const T&", true, Some(RefQualifier::LValue)),
+            ("T&", false, Some(RefQualifier::LValue)),
+            ("const T&&", true, Some(RefQualifier::RValue)),
+            ("T&&", false, Some(RefQualifier::RValue)),
+        ]
+    );
+    assert!(
+        details
+            .iter()
+            .all(|detail| detail.name == "value" && detail.parameters.is_empty())
+    );
+    let sets = group_overloads(&details);
+    assert_eq!(sets.len(), 1);
+    assert_eq!(sets[0].members, details);
+}
+
+#[test]
+fn test_qualifier_combinations() {
+    let detail = member("size_t size() const noexcept;");
+    assert!(detail.is_const() && detail.is_noexcept());
+    let detail = member("const T& value() const& noexcept;");
+    assert!(detail.is_const() && detail.is_noexcept());
+    assert_eq!(detail.ref_qualifier(), Some(RefQualifier::LValue));
+    let detail = member("virtual void Send() const = 0;");
+    assert_eq!(detail.trailing_qualifiers, ["const", "= 0"]);
+    let detail = member("C& operator=(const C&) & = delete;");
+    assert_eq!(detail.trailing_qualifiers, ["&"]);
+    assert_eq!(detail.special_definition, Some(SpecialDefinition::Delete));
+}
+
+#[test]
+fn test_default_arguments_are_verbatim() {
+    let source = r#"
+std::vector<std::string> Split(absl::string_view text, char delim = ' ',
+                               const char* sep = ", ", int limit = Limit(1, 2),
+                               std::vector<int> init = std::vector<int>{1, 2},
+                               std::map<int, int> m = {{1, 2}, {3, 4}});
+"#;
+    let extracted = extract_function_details(source);
+    let defaults: Vec<_> = extracted[0]
+        .parameters
+        .iter()
+        .map(|p| (p.name.as_deref().unwrap(), p.default.as_deref()))
+        .collect();
+    assert_eq!(
+        defaults,
+        [
+            ("text", None),
+            ("delim", Some("' '")),
+            ("sep", Some(r#"", ""#)),
+            ("limit", Some("Limit(1, 2)")),
+            ("init", Some("std::vector<int>{1, 2}")),
+            ("m", Some("{{1, 2}, {3, 4}}")),
+        ]
+    );
+}
+
+#[test]
+fn test_defaulted_template_typed_parameter() {
+    let source =
+        "template <typename T>\nvoid Fill(std::pair<T, int> p = std::pair<T, int>(T(), 0));";
+    let extracted = extract_function_details(source);
+    let param = &extracted[0].parameters[0];
+    assert_eq!(param.type_name, "std::pair<T, int>");
+    assert_eq!(param.name.as_deref(), Some("p"));
+    assert_eq!(param.default.as_deref(), Some("std::pair<T, int>(T(), 0)"));
+}
+
+#[test]
+fn test_parameter_pack() {
+    let source = "template <typename... Args>\nstd::string StrCat(const Args&... args);";
+    let extracted = extract_function_details(source);
+    let detail = &extracted[0];
+    assert_eq!(detail.template_prefix, "template <typename... Args>");
+    assert!(!detail.is_variadic);
+    assert_eq!(
+        detail.parameters,
+        [Parameter {
+            type_name: "const Args&".to_owned(),
+            name: Some("args".to_owned()),
+            default: None,
+            is_pack: true,
+        }]
+    );
+}
+
+#[test]
+fn test_unnamed_parameter_pack() {
+    let source = "template <typename... Ts>\nvoid Ignore(Ts&&...);";
+    let extracted = extract_function_details(source);
+    let param = &extracted[0].parameters[0];
+    assert_eq!(param.type_name, "Ts&&");
+    assert_eq!(param.name, None);
+    assert!(param.is_pack);
+}
+
+#[test]
+fn test_c_variadic() {
+    let extracted =
+        extract_function_details("int snprintf(char* buf, size_t n, const char* fmt, ...);");
+    let detail = &extracted[0];
+    assert!(detail.is_variadic);
+    assert_eq!(detail.arity(), 3);
+    assert!(detail.parameters.iter().all(|p| !p.is_pack));
+}
+
+#[test]
+fn test_mixed_pack_and_fixed_parameters() {
+    let source = "template <typename T, typename... Rest>\nvoid Log(const char* fmt, T first, Rest... rest);";
+    let extracted = extract_function_details(source);
+    let detail = &extracted[0];
+    assert_eq!(
+        detail.template_prefix,
+        "template <typename T, typename... Rest>"
+    );
+    let packs: Vec<_> = detail
+        .parameters
+        .iter()
+        .map(|p| (p.type_name.as_str(), p.name.as_deref(), p.is_pack))
+        .collect();
+    assert_eq!(
+        packs,
+        [
+            ("const char*", Some("fmt"), false),
+            ("T", Some("first"), false),
+            ("Rest", Some("rest"), true),
+        ]
+    );
+}
+
+#[test]
+fn test_trailing_return_types() {
+    let source = "auto MakePair(int a, int b) -> std::pair<int, int>;\n\
+                  auto Lookup(const Key& k)\n    -> std::map<std::string, std::vector<int>>;";
+    let extracted = extract_function_details(source);
+    let returns: Vec<_> = extracted
+        .iter()
+        .map(|d| (d.return_type.as_str(), d.has_trailing_return, d.is_deduced))
+        .collect();
+    assert_eq!(
+        returns,
+        [
+            ("std::pair<int, int>", true, false),
+            ("std::map<std::string, std::vector<int>>", true, false),
+        ]
+    );
+    assert_eq!(extracted[0].arity(), 2);
+}
+
+#[test]
+fn test_trailing_return_simple_and_templated() {
+    let source = "auto f() -> int;\n\
+                  auto Foo(int x) -> absl::StatusOr<int>;\n\
+                  template <typename T, typename U>\n\
+                  auto Add(T t, U u) -> decltype(t + u);\n\
+                  template <typename T>\n\
+                  auto Wrap(T value) -> absl::StatusOr<std::vector<T>> {\n  return {value};\n}";
+    let extracted = extract_function_details(source);
+    let returns: Vec<_> = extracted
+        .iter()
+        .map(|d| (d.name.as_str(), d.return_type.as_str(), d.is_templated()))
+        .collect();
+    assert_eq!(
+        returns,
+        [
+            ("f", "int", false),
+            ("Foo", "absl::StatusOr<int>", false),
+            ("Add", "decltype(t + u)", true),
+            ("Wrap", "absl::StatusOr<std::vector<T>>", true),
+        ]
+    );
+    assert!(
+        extracted
+            .iter()
+            .all(|d| d.has_trailing_return && !d.is_deduced)
+    );
+    assert!(extracted[3].is_definition);
+}
+
+#[test]
+fn test_trailing_return_type_is_read_word_by_word() {
+    let source = "auto Size() -> Größe;\n\
+                  auto Policy() -> finalizer_policy;\n\
+                  auto Rider() -> overrider&;";
+    let returns: Vec<_> = extract_function_details(source)
+        .into_iter()
+        .map(|d| (d.name, d.return_type))
+        .collect();
+    assert_eq!(
+        returns,
+        [
+            ("Size".to_owned(), "Größe".to_owned()),
+            ("Policy".to_owned(), "finalizer_policy".to_owned()),
+            ("Rider".to_owned(), "overrider&".to_owned()),
+        ]
+    );
+}
+
+#[test]
+fn test_trailing_return_with_qualifiers() {
+    let detail = member("auto begin() const noexcept -> const_iterator override;");
+    assert_eq!(detail.return_type, "const_iterator");
+    assert!(detail.has_trailing_return);
+    assert_eq!(
+        detail.trailing_qualifiers,
+        ["const", "noexcept", "override"]
+    );
+    let detail = member("auto get() && -> T&&;");
+    assert_eq!(detail.return_type, "T&&");
+    assert_eq!(detail.ref_qualifier(), Some(RefQualifier::RValue));
+}
+
+#[test]
+fn test_deduced_return_type() {
+    let extracted = extract_function_details("inline auto Twice(int x) { return x * 2; }");
+    let detail = &extracted[0];
+    assert_eq!(detail.return_type, "auto");
+    assert!(detail.is_deduced);
+    assert!(!detail.has_trailing_return);
+    let extracted = extract_function_details("int Plain(int x);");
+    assert!(!extracted[0].is_deduced && !extracted[0].has_trailing_return);
+}
+
+#[test]
+fn test_qualified_function_names() {
+    let source = r#"
+void absl::container_internal::SanitizerPoison(void* p);
+namespace absl {
+bool strings_internal::IsSpace(char c) { return c == ' '; }
+}
+"#;
+    let extracted = extract_function_details(source);
+    let qualified: Vec<_> = extracted
+        .iter()
+        .map(|d| {
+            (
+                d.namespace.as_deref(),
+                d.name.as_str(),
+                d.return_type.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        qualified,
+        [
+            (Some("absl::container_internal"), "SanitizerPoison", "void"),
+            (Some("absl::strings_internal"), "IsSpace", "bool"),
+        ]
+    );
+}
+
+#[test]
+fn test_function_returning_a_function_pointer() {
+    let source = "void (*signal_handler(int sig))(int);\n\
+                  int (&Table(size_t n) noexcept)(const char*, size_t);";
+    let extracted = extract_function_details(source);
+    let detail = &extracted[0];
+    assert_eq!(detail.name, "signal_handler");
+    assert_eq!(detail.return_type, "void (*)(int)");
+    assert_eq!(detail.parameters[0].type_name, "int");
+    assert_eq!(detail.parameters[0].name.as_deref(), Some("sig"));
+    let detail = &extracted[1];
+    assert_eq!(detail.name, "Table");
+    assert_eq!(detail.return_type, "int (&)(const char*, size_t)");
+    assert_eq!(detail.arity(), 1);
+    assert_eq!(detail.trailing_qualifiers, ["noexcept"]);
+}
+
+#[test]
+fn test_function_type_template_argument_returns() {
+    let source = r#"
+void (*get_handler())(int);
+std::function<int(int)> make_adder();
+absl::FunctionRef<void(absl::string_view)> Visitor(int x);
+class Callback {
+ public:
+  operator std::function<void()>() const;
+  bool operator<(const Callback& other) const;
+};
+"#;
+    let extracted = extract_function_details(source);
+    let summary: Vec<_> = extracted
+        .iter()
+        .map(|d| (d.name.as_str(), d.return_type.as_str(), d.arity()))
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            ("get_handler", "void (*)(int)", 0),
+            ("make_adder", "std::function<int(int)>", 0),
+            ("Visitor", "absl::FunctionRef<void(absl::string_view)>", 1),
+            ("operator std::function<void()>", "std::function<void()>", 0),
+            ("operator<", "bool", 1),
+        ]
+    );
+    assert_eq!(extracted[3].kind, FunctionKind::Conversion);
+}
+
+#[test]
+fn test_two_callback_parameters() {
+    let source = "void InstallFailureSignalHandler(void (*writerfn)(const char*),\n\
+                  \x20   bool (*filter)(int signo, void* context), int flags = 0);";
+    let extracted = extract_function_details(source);
+    assert_eq!(extracted[0].name, "InstallFailureSignalHandler");
+    let params: Vec<_> = extracted[0]
+        .parameters
+        .iter()
+        .map(|p| (p.type_name.as_str(), p.name.as_deref()))
+        .collect();
+    assert_eq!(
+        params,
+        [
+            ("void (*)(const char*)", Some("writerfn")),
+            ("bool (*)(int signo, void* context)", Some("filter")),
+            ("int", Some("flags")),
+        ]
+    );
+}
+
+#[test]
+fn test_split_declarations_match_single_line() {
+    let single =
+        "std::vector<std::string> Tally(const std::map<std::string, int>& counts, int min);";
+    let variants = [
+        "std::vector<std::string>\nTally(const std::map<std::string, int>& counts, int min);",
+        "std::vector<std::string> Tally(\n    const std::map<std::string, int>& counts,\n    int min);",
+        "std::vector<\n    std::string> Tally(const std::map<std::string,\n    int>& counts, int min);",
+    ];
+    // Positions differ with the layout; everything else must not.
+    let without_span = |source: &str| -> Vec<_> {
+        extract_function_details(source)
+            .into_iter()
+            .map(|d| astd::FunctionDetail {
+                byte_span: 0..0,
+                ..d
+            })
+            .collect()
+    };
+    let expected = without_span(single);
+    assert_eq!(expected.len(), 1);
+    for variant in variants {
+        assert_eq!(without_span(variant), expected, "{}", variant);
+    }
+}
+
+#[test]
+fn test_raw_string_with_three_prototypes_yields_nothing() {
+    let source = r#"
+const char kProgram[] = R"prog(
+int fake(int);
+void also_fake(const char* s = ")");
+double (*still_fake(int))(double);
+)prog";
+const char kQuoted[] = "say \"int quoted(int);\" twice";
+const char kQuote = '"';
+"#;
+    assert!(extract_function_details(source).is_empty());
+}
+
+#[test]
+fn test_explicit_conversion_operator() {
+    let source = "namespace absl {\nclass Handle {\n public:\n  explicit operator bool() const noexcept;\n};\n}";
+    let extracted = extract_function_details(source);
+    assert_eq!(
+        extracted,
+        [astd::FunctionDetail {
+            template_prefix: String::new(),
+            specifiers: vec!["explicit".to_owned()],
+            return_type: "bool".to_owned(),
+            has_trailing_return: false,
+            is_deduced: false,
+            name: "operator bool".to_owned(),
+            kind: FunctionKind::Conversion,
+            parameters: Vec::new(),
+            is_variadic: false,
+            trailing_qualifiers: vec!["const".to_owned(), "noexcept".to_owned()],
+            special_definition: None,
+            namespace: Some("absl".to_owned()),
+            enclosing_type: Some("Handle".to_owned()),
+            linkage: Linkage::Cpp,
+            calling_convention: None,
+            line: 4,
+            column: 3,
+            path: None,
+            access: Some(Access::Public),
+            byte_span: 43..82,
+            attributes: Vec::new(),
+            deprecated: None,
+            is_definition: false,
+            doc: None,
+        }]
+    );
+    assert!(extracted[0].is_const() && extracted[0].is_noexcept());
+}
+
+#[test]
+fn test_operator_kinds() {
+    let source = r#"
+class Duration {
+ public:
+  Duration& operator+=(Duration d);
+  Duration operator-() const;
+  int64_t operator[](size_t i) const;
+  bool operator()(Duration a, Duration b) const;
+  explicit operator bool() const;
+  operator absl::string_view() const;
+  void* operator new(size_t size);
+};
+Duration operator+(Duration lhs, Duration rhs);
+Duration operator*(Duration lhs, int64_t rhs);
+bool operator<(Duration lhs, Duration rhs);
+bool operator>=(Duration lhs, Duration rhs);
+"#;
+    let extracted = extract_function_details(source);
+    let kinds: Vec<_> = extracted
+        .iter()
+        .map(|d| (d.name.as_str(), d.kind, d.return_type.as_str()))
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            ("operator+=", FunctionKind::Operator, "Duration&"),
+            ("operator-", FunctionKind::Operator, "Duration"),
+            ("operator[]", FunctionKind::Operator, "int64_t"),
+            ("operator()", FunctionKind::Operator, "bool"),
+            ("operator bool", FunctionKind::Conversion, "bool"),
+            (
+                "operator absl::string_view",
+                FunctionKind::Conversion,
+                "absl::string_view"
+            ),
+            ("operator new", FunctionKind::Operator, "void*"),
+            ("operator+", FunctionKind::Operator, "Duration"),
+            ("operator*", FunctionKind::Operator, "Duration"),
+            ("operator<", FunctionKind::Operator, "bool"),
+            ("operator>=", FunctionKind::Operator, "bool"),
+        ]
+    );
+    assert_eq!(extracted[4].specifiers, ["explicit"]);
+    let plain = extract_function_details("int Add(int a, int b);");
+    assert_eq!(plain[0].kind, FunctionKind::Regular);
+}
+
+#[test]
+fn test_unknown_operator_symbol_is_skipped() {
+    let extracted = extract_function_details("bool operator~=(int a);\nbool operator~(int a);");
+    let names: Vec<_> = extracted.iter().map(|d| d.name.as_str()).collect();
+    assert_eq!(names, ["operator~"]);
+}
+
+#[test]
+fn test_extern_c_linkage() {
+    let source = r#"
+#ifdef __cplusplus
+extern "C" {
+#endif
+int absl_Abs_wrapper(int v);
+namespace inner {
+extern "C++" {
+int CppOnly(int v);
+}
+}
+int AfterNested(int v);
+#ifdef __cplusplus
+}
+#endif
+extern "C" size_t CStrLen(const char* s);
+int Outside(int v);
+"#;
+    let extracted = extract_function_details(source);
+    let linkages: Vec<_> = extracted
+        .iter()
+        .map(|d| (d.name.as_str(), d.linkage))
+        .collect();
+    assert_eq!(
+        linkages,
+        [
+            ("absl_Abs_wrapper", Linkage::C),
+            ("CppOnly", Linkage::Cpp),
+            ("AfterNested", Linkage::C),
+            ("CStrLen", Linkage::C),
+            ("Outside", Linkage::Cpp),
+        ]
+    );
+    assert_eq!(extracted[3].return_type, "size_t");
+    assert!(extracted[3].specifiers.is_empty());
+}
+
+#[test]
+fn test_source_positions() {
+    let source = "// fixture\nnamespace absl {\nint First(int a);\n\n// Second doc.\n  ABSL_MUST_USE_RESULT bool\n  Second();\n}\n";
+    let extracted = extract_function_details(source);
+    let lines: Vec<_> = extracted.iter().map(|d| d.line).collect();
+    assert_eq!(lines, [3, 6]);
+    assert_eq!(&source[extracted[0].byte_span.clone()], "int First(int a)");
+    assert_eq!(
+        &source[extracted[1].byte_span.clone()],
+        "ABSL_MUST_USE_RESULT bool\n  Second()"
+    );
+}
+
+#[test]
+fn test_attribute_macros() {
+    let source = r#"
+ABSL_MUST_USE_RESULT int Foo();
+ABSL_DEPRECATED("Use absl::StrCat() instead")
+ABSL_MUST_USE_RESULT std::string OldCat(int a, int b);
+void Abort() ABSL_ATTRIBUTE_NORETURN;
+ABSL_PRINTF_ATTRIBUTE(1, 2) void Log(const char* fmt, ...);
+"#;
+    let extracted = extract_function_details(source);
+    let found: Vec<_> = extracted
+        .iter()
+        .map(|d| {
+            (
+                d.name.as_str(),
+                d.return_type.as_str(),
+                d.attributes.clone(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        found,
+        [
+            ("Foo", "int", vec!["ABSL_MUST_USE_RESULT".to_owned()]),
+            (
+                "OldCat",
+                "std::string",
+                vec![
+                    "ABSL_DEPRECATED(\"Use absl::StrCat() instead\")".to_owned(),
+                    "ABSL_MUST_USE_RESULT".to_owned(),
+                ]
+            ),
+            ("Abort", "void", vec!["ABSL_ATTRIBUTE_NORETURN".to_owned()]),
+            (
+                "Log",
+                "void",
+                vec!["ABSL_PRINTF_ATTRIBUTE(1, 2)".to_owned()]
+            ),
+        ]
+    );
+    assert_eq!(extracted[1].arity(), 2);
+    assert!(extracted[3].is_variadic);
+    assert!(
+        extract_function_details("int Plain();")[0]
+            .attributes
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_standard_attributes() {
+    let source = r#"
+[[nodiscard]] int Size();
+int [[deprecated("use Foo2 [since 2024]")]] Foo(int x);
+[[gnu::pure, nodiscard("check the status")]] absl::Status Check();
+void Clear();
+"#;
+    let extracted = extract_function_details(source);
+    let found: Vec<_> = extracted
+        .iter()
+        .map(|d| (d.name.as_str(), d.return_type.as_str(), d.must_use()))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            ("Size", "int", true),
+            ("Foo", "int", false),
+            ("Check", "absl::Status", true),
+            ("Clear", "void", false),
+        ]
+    );
+    assert_eq!(
+        extracted[1].attributes,
+        ["deprecated(\"use Foo2 [since 2024]\")"]
+    );
+    assert!(extracted[1].is_deprecated());
+    assert_eq!(
+        extracted[2].attributes,
+        ["gnu::pure", "nodiscard(\"check the status\")"]
+    );
+    assert!(extract_function_details("ABSL_MUST_USE_RESULT int Foo();")[0].must_use());
+}
+
+#[test]
+fn test_calling_conventions() {
+    let source = r#"
+int __stdcall Foo(void);
+WINAPI BOOL InitOnce(PINIT_ONCE once);
+static void* __cdecl Allocate(size_t n);
+int Plain();
+"#;
+    let found: Vec<_> = extract_function_details(source)
+        .into_iter()
+        .map(|d| (d.name, d.return_type, d.calling_convention))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            (
+                "Foo".to_owned(),
+                "int".to_owned(),
+                Some("__stdcall".to_owned())
+            ),
+            (
+                "InitOnce".to_owned(),
+                "BOOL".to_owned(),
+                Some("WINAPI".to_owned())
+            ),
+            (
+                "Allocate".to_owned(),
+                "void*".to_owned(),
+                Some("__cdecl".to_owned())
+            ),
+            ("Plain".to_owned(), "int".to_owned(), None),
+        ]
+    );
+
+    let custom = "int ABSL_CC Bar(int x);";
+    let extracted = extract_function_details_with_conventions(custom, &["ABSL_CC"]);
+    assert_eq!(extracted[0].return_type, "int");
+    assert_eq!(extracted[0].calling_convention.as_deref(), Some("ABSL_CC"));
+    assert_eq!(
+        extract_function_details(custom)[0].return_type,
+        "int ABSL_CC"
+    );
+}
+
+#[test]
+fn test_extra_attribute_macros() {
+    let source = "MY_EXPORT MY_PURE int Bar(int x);";
+    let extracted = extract_function_details_with_attributes(source, &["MY_EXPORT", "MY_PURE"]);
+    assert_eq!(extracted[0].return_type, "int");
+    assert_eq!(extracted[0].attributes, ["MY_EXPORT", "MY_PURE"]);
+    assert_eq!(
+        extract_function_details(source)[0].return_type,
+        "MY_EXPORT MY_PURE int"
+    );
+}
+
+#[test]
+fn test_extra_function_like_macros() {
+    let source = "MY_ALIGN(alignof(T) * 2) MY_EXPORT\nstd::string Pad(int n) MY_ALIGN(4);";
+    let extracted = extract_function_details_with_attributes(source, &["MY_EXPORT", "MY_ALIGN"]);
+    assert_eq!(extracted[0].return_type, "std::string");
+    assert_eq!(extracted[0].name, "Pad");
+    assert_eq!(
+        extracted[0].attributes,
+        ["MY_ALIGN(alignof(T) * 2)", "MY_EXPORT", "MY_ALIGN(4)"]
+    );
+    assert!(extracted[0].trailing_qualifiers.is_empty());
+}
+
+#[test]
+fn test_definition_bodies_are_skipped() {
+    let source = r#"
+inline int add(int a, int b) { return a + b; }
+inline int Combine(int x, int y) {
+  if (x > 0) {
+    return helper(x) + other(y);
+  }
+  int scaled = Scale(y);
+  return scaled;
+}
+int Declared(int z);
+"#;
+    let extracted = extract_function_details(source);
+    let found: Vec<_> = extracted
+        .iter()
+        .map(|d| (d.name.as_str(), d.is_definition))
+        .collect();
+    assert_eq!(
+        found,
+        [("add", true), ("Combine", true), ("Declared", false)]
+    );
+}
+
+#[test]
+fn test_member_definitions_are_skipped() {
+    let source = r#"
+class Counter {
+ public:
+  Counter() : count_(Init(0)) { Reset(); }
+  int count() const { return Load(count_); }
+  void Bump();
+};
+"#;
+    let extracted = extract_function_details(source);
+    let names: Vec<_> = extracted.iter().map(|d| d.name.as_str()).collect();
+    assert_eq!(names, ["Counter", "count", "Bump"]);
+    assert!(
+        extracted
+            .iter()
+            .all(|d| d.enclosing_type.as_deref() == Some("Counter"))
+    );
+}
+
+#[test]
+fn test_doc_comments() {
+    let source = r#"
+namespace absl {
+
+// StrCat()
+//
+// Merges the given strings or numbers, using no delimiter(s),
+// returning the merged result as a string.
+ABSL_MUST_USE_RESULT std::string StrCat(const AlphaNum& a);
+ABSL_MUST_USE_RESULT std::string StrCat(const AlphaNum& a, const AlphaNum& b);
+
+// A comment separated by a blank line documents nothing below it.
+
+int Detached();
+
+/**
+ * Returns the length of `s`.
+ *
+ *   Code examples keep their relative indentation.
+ */
+size_t Length(absl::string_view s);
+
+// The Cord class.
+class Cord {
+ public:
+  bool empty() const;
+  /// Clears the cord.
+  void Clear();
+};
+}
+"#;
+    let extracted = extract_function_details(source);
+    let docs: Vec<_> = extracted
+        .iter()
+        .map(|d| (d.name.as_str(), d.doc.as_deref()))
+        .collect();
+    assert_eq!(
+        docs,
+        [
+            (
+                "StrCat",
+                Some(
+                    "StrCat()\n\nMerges the given strings or numbers, using no delimiter(s),\n\
+                     returning the merged result as a string."
+                )
+            ),
+            ("StrCat", None),
+            ("Detached", None),
+            (
+                "Length",
+                Some(
+                    "Returns the length of `s`.\n\n  Code examples keep their relative indentation."
+                )
+            ),
+            ("empty", None),
+            ("Clear", Some("Clears the cord.")),
+        ]
+    );
+}
+
+#[test]
+fn test_cpp11_attributes() {
+    let source = r#"
+[[nodiscard]] int f();
+[[deprecated("Use NewApi(int, int) instead")]] [[nodiscard]]
+bool OldApi(int a);
+[[nodiscard, gnu::pure]] ABSL_MUST_USE_RESULT size_t Hash(const char* s [[maybe_unused]]);
+"#;
+    let extracted = extract_function_details(source);
+    let found: Vec<_> = extracted
+        .iter()
+        .map(|d| {
+            (
+                d.return_type.as_str(),
+                d.name.as_str(),
+                d.attributes.clone(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        found,
+        [
+            ("int", "f", vec!["nodiscard".to_owned()]),
+            (
+                "bool",
+                "OldApi",
+                vec![
+                    "deprecated(\"Use NewApi(int, int) instead\")".to_owned(),
+                    "nodiscard".to_owned(),
+                ]
+            ),
+            (
+                "size_t",
+                "Hash",
+                vec![
+                    "nodiscard".to_owned(),
+                    "gnu::pure".to_owned(),
+                    "ABSL_MUST_USE_RESULT".to_owned(),
+                    "maybe_unused".to_owned(),
+                ]
+            ),
+        ]
+    );
+    assert_eq!(extracted[2].parameters[0].type_name, "const char*");
+    assert_eq!(extracted[2].parameters[0].name.as_deref(), Some("s"));
+}
+
+#[test]
+fn test_lines_and_columns() {
+    let source = "/* License\n * banner\n */\n\n// Leading comment.\nint A();\n\n\nnamespace n {\n  void B(int x);   double C();\n\n}  // namespace n\n";
+    let extracted = extract_function_details(source);
+    let positions: Vec<_> = extracted
+        .iter()
+        .map(|d| (d.name.as_str(), d.line, d.column))
+        .collect();
+    assert_eq!(positions, [("A", 6, 1), ("B", 10, 3), ("C", 10, 20)]);
+    assert_eq!(extracted[1].diagnostic(), "<input>:10:3: n::B");
+    let from_file = astd::FunctionDetail {
+        path: Some("absl/strings/str_cat.h".into()),
+        ..extracted[2].clone()
+    };
+    assert_eq!(from_file.diagnostic(), "absl/strings/str_cat.h:10:20: n::C");
+}
+
+#[test]
+fn test_template_parameter_kinds() {
+    let params = parse_template_parameters(
+        "template <typename T, size_t N = 4, typename... Ts, \
+         template <typename, typename> class Container, typename = void, bool>",
+    );
+    let summary: Vec<_> = params
+        .iter()
+        .map(|p| (&p.kind, p.name.as_deref(), p.default.as_deref(), p.is_pack))
+        .collect();
+    let nested = TemplateParameter {
+        kind: TemplateParameterKind::Type,
+        name: None,
+        default: None,
+        is_pack: false,
+    };
+    assert_eq!(
+        summary,
+        [
+            (&TemplateParameterKind::Type, Some("T"), None, false),
+            (
+                &TemplateParameterKind::NonType("size_t".to_owned()),
+                Some("N"),
+                Some("4"),
+                false
+            ),
+            (&TemplateParameterKind::Type, Some("Ts"), None, true),
+            (
+                &TemplateParameterKind::Template(vec![nested.clone(), nested]),
+                Some("Container"),
+                None,
+                false
+            ),
+            (&TemplateParameterKind::Type, None, Some("void"), false),
+            (
+                &TemplateParameterKind::NonType("bool".to_owned()),
+                None,
+                None,
+                false
+            ),
+        ]
+    );
+    assert!(parse_template_parameters("").is_empty());
+    assert!(parse_template_parameters("template <>").is_empty());
+    let extracted = extract_function_details("template <typename T, int N>\nT Get(T t);");
+    assert_eq!(extracted[0].template_parameters().len(), 2);
+}
+
+#[test]
+fn test_deleted_defaulted_and_pure_virtual() {
+    let source = r#"
+class Mutex {
+ public:
+  Mutex(const Mutex&) = delete;
+  Mutex& operator=(const Mutex&) = delete;
+  ~Mutex() = default;
+  virtual void Lock() = 0;
+  void Unlock();
+};
+"#;
+    let extracted = extract_function_details(source);
+    let definitions: Vec<_> = extracted
+        .iter()
+        .map(|d| (d.name.as_str(), d.special_definition))
+        .collect();
+    assert_eq!(
+        definitions,
+        [
+            ("Mutex", Some(SpecialDefinition::Delete)),
+            ("operator=", Some(SpecialDefinition::Delete)),
+            ("~Mutex", Some(SpecialDefinition::Default)),
+            ("Lock", None),
+            ("Unlock", None),
+        ]
+    );
+    assert_eq!(extracted[3].trailing_qualifiers, ["= 0"]);
+    assert!(extracted[2].trailing_qualifiers.is_empty());
+}
+
+#[test]
+fn test_nested_template_arguments() {
+    let source = r#"
+std::vector<std::pair<int, int>> Pairs();
+std::map<std::string, std::vector<std::pair<int, int> > > Spaced(
+    const std::map<int, std::vector<std::set<int>>>& in);
+std::map<std::string, std::vector<std::pair<int, int>>> Packed();
+absl::flat_hash_map<std::string, int> Counts(int limit = (kMax > 2 ? kMax : 2),
+                                             bool strict = kLimit > 3);
+void Fill(std::array<int, (kSize > 4 ? 8 : 4)> slots, int n);
+"#;
+    let extracted = extract_function_details(source);
+    let returns: Vec<_> = extracted
+        .iter()
+        .map(|d| (d.name.as_str(), d.return_type.as_str()))
+        .collect();
+    assert_eq!(
+        returns,
+        [
+            ("Pairs", "std::vector<std::pair<int, int>>"),
+            (
+                "Spaced",
+                "std::map<std::string, std::vector<std::pair<int, int>>>"
+            ),
+            (
+                "Packed",
+                "std::map<std::string, std::vector<std::pair<int, int>>>"
+            ),
+            ("Counts", "absl::flat_hash_map<std::string, int>"),
+            ("Fill", "void"),
+        ]
+    );
+    assert_eq!(
+        extracted[1].parameters[0].type_name,
+        "const std::map<int, std::vector<std::set<int>>>&"
+    );
+    let defaults: Vec<_> = extracted[3]
+        .parameters
+        .iter()
+        .map(|p| p.default.as_deref())
+        .collect();
+    assert_eq!(
+        defaults,
+        [Some("(kMax > 2 ? kMax : 2)"), Some("kLimit > 3")]
+    );
+    assert_eq!(extracted[4].arity(), 2);
+    assert_eq!(
+        extracted[4].parameters[0].type_name,
+        "std::array<int, (kSize > 4 ? 8 : 4)>"
+    );
+}
+
+#[test]
+fn test_group_overloads() {
+    let str_cat = r#"
+namespace absl {
+std::string StrCat();
+std::string StrCat(const AlphaNum& a);
+std::string StrCat(const AlphaNum& a, const AlphaNum& b);
+void StrAppend(std::string* dest, const AlphaNum& a);
+class Cord {
+ public:
+  void Append(const Cord& src);
+  void Append(Cord&& src);
+};
+}  // namespace absl
+"#;
+    // The same declaration reached through a second header, parameter name changed.
+    let forwarding = "namespace absl {\nstd::string StrCat(const AlphaNum& other);\n}";
+    let mut details = extract_function_details(str_cat);
+    details.extend(extract_function_details(forwarding));
+    let sets = group_overloads(&details);
+    let summary: Vec<_> = sets
+        .iter()
+        .map(|set| {
+            (
+                set.enclosing_type.as_deref(),
+                set.name.as_str(),
+                set.members.iter().map(|m| m.arity()).collect::<Vec<_>>(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            (None, "StrCat", vec![0, 1, 2]),
+            (None, "StrAppend", vec![2]),
+            (Some("Cord"), "Append", vec![1, 1]),
+        ]
+    );
+    assert!(
+        sets.iter()
+            .all(|set| set.namespace.as_deref() == Some("absl"))
+    );
+    assert!(sets[0].is_overloaded() && !sets[1].is_overloaded());
+    assert_eq!(sets[0].members[1].parameters[0].name.as_deref(), Some("a"));
+    assert_eq!(sets[2].members[1].parameter_types(), ["Cord&&"]);
+}
+
+#[test]
+fn test_member_access_and_enclosing_class() {
+    let source = r#"
+namespace absl {
+class Cord {
+ public:
+  bool empty() const;
+ private:
+  void ForceFlat();
+ protected:
+  size_t TreeSize() const;
+ public:
+  size_t size() const;
+};
+struct Options {
+  int Limit() const;
+};
+bool IsEmpty(const Cord& c);
+}  // namespace absl
+"#;
+    let members: Vec<_> = extract_function_details(source)
+        .into_iter()
+        .map(|d| (d.enclosing_type, d.name, d.access))
+        .collect();
+    let cord = Some("Cord".to_owned());
+    assert_eq!(
+        members,
+        [
+            (cord.clone(), "empty".to_owned(), Some(Access::Public)),
+            (cord.clone(), "ForceFlat".to_owned(), Some(Access::Private)),
+            (cord.clone(), "TreeSize".to_owned(), Some(Access::Protected)),
+            (cord, "size".to_owned(), Some(Access::Public)),
+            (
+                Some("Options".to_owned()),
+                "Limit".to_owned(),
+                Some(Access::Public)
+            ),
+            (None, "IsEmpty".to_owned(), None),
+        ]
+    );
+}
+
+#[test]
+fn test_virtual_and_pure_virtual() {
+    let source = r#"
+namespace absl {
+class LogSink {
+ public:
+  virtual ~LogSink() = default;
+  virtual void Send(const absl::LogEntry& entry) = 0;
+  virtual void Flush() {}
+};
+class StderrLogSink : public LogSink {
+ public:
+  void Send(const absl::LogEntry& entry) override;
+};
+}  // namespace absl
+"#;
+    let found: Vec<_> = extract_function_details(source)
+        .into_iter()
+        .map(|d| {
+            (
+                d.name.clone(),
+                d.return_type.clone(),
+                d.is_virtual(),
+                d.is_pure_virtual(),
+                d.is_override(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        found,
+        [
+            ("~LogSink".to_owned(), "".to_owned(), true, false, false),
+            ("Send".to_owned(), "void".to_owned(), true, true, false),
+            ("Flush".to_owned(), "void".to_owned(), true, false, false),
+            ("Send".to_owned(), "void".to_owned(), false, false, true),
+        ]
+    );
+}
+
+#[test]
+fn test_enable_if_templates_do_not_corrupt_later_declarations() {
+    let source = r#"
+namespace absl {
+template <typename T,
+          typename = std::enable_if_t<std::is_integral<T>::value && (sizeof(T) > 1)>>
+T Clamp(T value, T lo, T hi);
+template <typename T>
+typename std::enable_if<!std::is_same<T, bool>::value, T>::type Negate(T value);
+int Next(int x);
+}  // namespace absl
+"#;
+    let extracted = extract_function_details(source);
+    let found: Vec<_> = extracted
+        .iter()
+        .map(|d| (d.name.as_str(), d.return_type.as_str(), d.is_templated()))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            ("Clamp", "T", true),
+            (
+                "Negate",
+                "typename std::enable_if<!std::is_same<T, bool>::value, T>::type",
+                true
+            ),
+            ("Next", "int", false),
+        ]
+    );
+    assert_eq!(
+        extracted[0].template_prefix,
+        "template <typename T, typename = std::enable_if_t<std::is_integral<T>::value && \
+         (sizeof(T) > 1)>>"
+    );
+    assert_eq!(extracted[0].arity(), 3);
+    assert_eq!(extracted[2].namespace.as_deref(), Some("absl"));
+}
+
+#[test]
+fn test_friend_declarations() {
+    let source = r#"
+namespace absl {
+class Cord {
+ public:
+  friend class CordTestPeer;
+  friend bool operator==(const Cord& a, const Cord& b);
+  template <typename H>
+  friend H AbslHashValue(H h, const Cord& c) {
+    int Inner(int x);
+    return H::combine(std::move(h), c.size());
+  }
+  size_t size() const;
+};
+}  // namespace absl
+"#;
+    let found: Vec<_> = extract_function_details(source)
+        .into_iter()
+        .map(|d| (d.name.clone(), d.is_friend(), d.is_definition))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            ("operator==".to_owned(), true, false),
+            ("AbslHashValue".to_owned(), true, true),
+            ("size".to_owned(), false, false),
+        ]
+    );
+}
+
+#[test]
+fn test_public_function_details_skip_private_members() {
+    let source = r#"
+namespace absl {
+class Duration {
+  int64_t rep_hi_;
+  Duration(int64_t hi, uint32_t lo);
+  friend Duration MakeDuration(int64_t hi, uint32_t lo);
+
+ public:
+  Duration();
+  int64_t Seconds() const;
+
+ protected:
+  void Normalize();
+};
+struct Time {
+  int64_t Unix() const;
+
+ private:
+  static Time FromRep(int64_t rep);
+};
+Duration Seconds(int64_t n);
+}  // namespace absl
+"#;
+    let names: Vec<_> = extract_public_function_details(source)
+        .into_iter()
+        .map(|d| d.name)
+        .collect();
+    assert_eq!(names, ["Duration", "Seconds", "Unix", "Seconds"]);
+    assert_eq!(extract_function_details(source).len(), 8);
+}
+
+#[test]
+fn test_iterator_stops_early() {
+    let source: String = (0..10)
+        .map(|i| format!("int Function{}(int x);\n", i))
+        .collect();
+    let names: Vec<_> = extract_function_details_iter(&source)
+        .take(2)
+        .map(|d| d.name)
+        .collect();
+    assert_eq!(names, ["Function0", "Function1"]);
+}
+
+#[test]
+fn test_iterator_matches_vec() {
+    let source = r#"
+namespace absl {
+int Before(int x);
+this is ) not = valid ;
+class Cord {
+ public:
+  bool empty() const { return size() == 0; }
+  size_t size() const;
+};
+int After();
+}  // namespace absl
+"#;
+    let mut iter = extract_function_details_iter(source);
+    let lazily: Vec<_> = iter.by_ref().collect();
+    assert_eq!(lazily, extract_function_details(source));
+    assert_eq!(lazily.len(), 4);
+    assert_eq!(lazily[3].name, "After");
+    assert!(iter.next().is_none());
+}
+
+#[test]
+fn test_variadic_template_parameters() {
+    let source = r#"
+template <typename T, typename... Args>
+std::string StrCat(const T& first, const Args&... args);
+
+template <typename T, typename = std::enable_if_t<std::is_integral<T>::value>>
+T Abs(T value);
+"#;
+    let details = extract_function_details(source);
+    assert_eq!(details.len(), 2);
+
+    assert_eq!(details[0].name, "StrCat");
+    assert_eq!(
+        details[0].template_prefix,
+        "template <typename T, typename... Args>"
+    );
+    assert_eq!(
+        details[0].template_parameter_list(),
+        ["typename T", "typename... Args"]
+    );
+    assert_eq!(details[0].parameters[1].type_name, "const Args&");
+    assert!(details[0].parameters[1].is_pack);
+    assert!(details[0].template_parameters()[1].is_pack);
+
+    assert_eq!(details[1].name, "Abs");
+    assert_eq!(details[1].return_type, "T");
+    assert_eq!(
+        details[1].template_parameter_list(),
+        [
+            "typename T",
+            "typename = std::enable_if_t<std::is_integral<T>::value>"
+        ]
+    );
+}
+
+#[test]
+fn test_keyword_calls_are_not_functions() {
+    // The namespace block is from absl/base/macros.h; the rest are namespace-scope
+    // statements that start with or contain a keyword call.
+    let source = r#"
+#define ABSL_ARRAYSIZE(array) \
+  (sizeof(::absl::macros_internal::ArraySizeHelper(array)))
+
+namespace absl {
+ABSL_NAMESPACE_BEGIN
+namespace macros_internal {
+// Note: this internal template function declaration is used by ABSL_ARRAYSIZE.
+// The function doesn't need a definition, as we only use its type.
+template <typename T, size_t N>
+auto ArraySizeHelper(const T (&array)[N]) -> char (&)[N];
+}  // namespace macros_internal
+ABSL_NAMESPACE_END
+}  // namespace absl
+
+static_assert(sizeof(void*) == 8, "64-bit only");
+decltype(sizeof(int)) kSize;
+ABSL_INTERNAL_CHECK static_assert(alignof(int) == 4, "");
+ABSL_INTERNAL_CHECK sizeof(int);
+int After();
+"#;
+    let (details, diagnostics) = extract_function_details_checked(source);
+    let names: Vec<_> = details.iter().map(|d| d.name.as_str()).collect();
+    assert_eq!(names, ["ArraySizeHelper", "After"]);
+    assert_eq!(details[0].return_type, "char (&)[N]");
+    assert_eq!(
+        details[0].namespace.as_deref(),
+        Some("absl::macros_internal")
+    );
+    assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+}
+
+#[test]
+fn test_decltype_return_types() {
+    let source = "decltype(auto) Forward(int&& x);\n\
+                  template <typename T>\n\
+                  decltype(std::declval<T>().size()) Size(const T& t);";
+    let details = extract_function_details(source);
+    let returns: Vec<_> = details
+        .iter()
+        .map(|d| (d.name.as_str(), d.return_type.as_str(), d.is_deduced))
+        .collect();
+    assert_eq!(
+        returns,
+        [
+            ("Forward", "decltype(auto)", true),
+            ("Size", "decltype(std::declval<T>().size())", false),
+        ]
+    );
+    assert_eq!(details[1].parameters[0].type_name, "const T&");
+}
+
+#[test]
+fn test_pointer_declarator_binds_to_the_name() {
+    let source = "int *Find(int key);\n\
+                  const char*Name(void);\n\
+                  std::string &Ref(std::string&s);\n\
+                  absl::Span<int>* ns::Get(int n);";
+    let (details, diagnostics) = extract_function_details_checked(source);
+    let returns: Vec<_> = details
+        .iter()
+        .map(|d| (d.name.as_str(), d.return_type.as_str()))
+        .collect();
+    assert_eq!(
+        returns,
+        [
+            ("Find", "int*"),
+            ("Name", "const char*"),
+            ("Ref", "std::string&"),
+            ("Get", "absl::Span<int>*"),
+        ]
+    );
+    assert_eq!(details[3].namespace.as_deref(), Some("ns"));
+    assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+}
+
+#[test]
+fn test_checked_reports_unparseable_declarations() {
+    let source = r#"
+namespace absl {
+int Parsed(int x);
+static_assert(sizeof(int) == 4, "int is 32 bits");
+(int) Broken(int x);
+void (*MissingPointee(int x));
+int Unclosed(int x, "#;
+    let (details, diagnostics) = extract_function_details_checked(source);
+    assert_eq!(details.len(), 1);
+    assert_eq!(details[0].name, "Parsed");
+
+    let reasons: Vec<_> = diagnostics.iter().map(|d| d.reason).collect();
+    assert_eq!(
+        reasons,
+        [
+            "no function declarator matched",
+            "function pointer return type has no parameter list",
+            "unclosed parameter list",
+        ]
+    );
+    assert_eq!(diagnostics[0].text, "(int) Broken(int x)");
+    assert_eq!((diagnostics[0].line, diagnostics[0].column), (5, 1));
+    assert_eq!(diagnostics[2].line, 7);
+
+    // The unchecked variants agree on what does parse.
+    assert_eq!(details, extract_function_details(source));
+}
+
+#[test]
+fn test_options_select_the_platform_branch() {
+    let source = r#"
+namespace absl {
+#if defined(_WIN32)
+void SleepFor(DWORD milliseconds);
+#elif defined(__linux__) && __GLIBC__ >= 2
+void SleepFor(unsigned int milliseconds);
+#else
+void SleepFor(int milliseconds);
+#endif
+}
+"#;
+    let parameter_type = |defines: &[(&str, Option<&str>)]| {
+        let options = ExtractOptions {
+            defines: defines
+                .iter()
+                .map(|&(name, value)| (name.to_owned(), value.map(str::to_owned)))
+                .collect(),
+            ..ExtractOptions::default()
+        };
+        let (details, diagnostics) = extract_function_details_with_options(source, &options);
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+        assert_eq!(details.len(), 1);
+        details[0].parameters[0].type_name.clone()
+    };
+    assert_eq!(parameter_type(&[("_WIN32", None)]), "DWORD");
+    assert_eq!(
+        parameter_type(&[("__linux__", None), ("__GLIBC__", Some("2"))]),
+        "unsigned int"
+    );
+    assert_eq!(
+        parameter_type(&[("__linux__", None), ("__GLIBC__", Some("1"))]),
+        "int"
+    );
+    assert_eq!(parameter_type(&[]), "int");
+    // Without options every branch is kept.
+    assert_eq!(extract_function_details(source).len(), 3);
+}
+
+#[test]
+fn test_options_nested_conditionals() {
+    let source = r#"
+#ifdef ABSL_HAVE_THREAD_LOCAL
+int* ThreadLocalSlot();
+#ifndef ABSL_HAVE_EXCEPTIONS
+void Abort() noexcept;
+#endif
+#if ABSL_OPTION_USE_STD_OPTIONAL == 1 || (!defined(NDEBUG) && -1 < 0)
+void CheckOptional();
+#endif
+#endif
+void Always();
+"#;
+    let names = |defines: HashMap<String, Option<String>>| -> Vec<String> {
+        extract_function_details_with_options(
+            source,
+            &ExtractOptions {
+                defines,
+                ..ExtractOptions::default()
+            },
+        )
+        .0
+        .into_iter()
+        .map(|detail| detail.name)
+        .collect()
+    };
+    assert_eq!(names(HashMap::new()), ["Always"]);
+    let mut defines = HashMap::from([("ABSL_HAVE_THREAD_LOCAL".to_owned(), None)]);
+    assert_eq!(
+        names(defines.clone()),
+        ["ThreadLocalSlot", "Abort", "CheckOptional", "Always"]
+    );
+    defines.insert("ABSL_HAVE_EXCEPTIONS".to_owned(), None);
+    defines.insert("NDEBUG".to_owned(), None);
+    defines.insert(
+        "ABSL_OPTION_USE_STD_OPTIONAL".to_owned(),
+        Some("0x0".to_owned()),
+    );
+    assert_eq!(names(defines), ["ThreadLocalSlot", "Always"]);
+}
+
+#[test]
+fn test_options_keep_unknown_conditions_with_a_diagnostic() {
+    let source = r#"
+#if __has_include(<span>)
+void WithSpan();
+#else
+void WithoutSpan();
+#endif
+#if defined(_WIN32)
+void Windows();
+#elif ABSL_HAVE_CPP_ATTRIBUTE(nodiscard)
+void Attribute();
+#endif
+"#;
+    let (details, diagnostics) =
+        extract_function_details_with_options(source, &ExtractOptions::default());
+    let names: Vec<&str> = details.iter().map(|detail| detail.name.as_str()).collect();
+    assert_eq!(names, ["WithSpan", "WithoutSpan", "Attribute"]);
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0].reason, "unknown preprocessor condition");
+    assert_eq!(diagnostics[0].text, "#if __has_include(<span>)");
+    assert_eq!((diagnostics[0].line, diagnostics[0].column), (2, 1));
+    assert_eq!(
+        diagnostics[1].text,
+        "#elif ABSL_HAVE_CPP_ATTRIBUTE(nodiscard)"
+    );
+    assert_eq!(diagnostics[1].line, 9);
+}
+
+#[test]
+fn test_legacy_exception_specifications() {
+    let source = r#"
+void* operator new(std::size_t size) throw(std::bad_alloc);
+void Free(void* ptr) throw();
+int Next(int x);
+class Allocator {
+ public:
+  Allocator() throw();
+  T* allocate(size_type n, const void* hint = 0) throw(std::bad_alloc, std::length_error);
+  size_type max_size() const throw();
+};
+bool Ready();
+"#;
+    let (details, diagnostics) = extract_function_details_checked(source);
+    assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+    let summary: Vec<_> = details
+        .iter()
+        .map(|detail| (detail.name.as_str(), detail.trailing_qualifiers.clone()))
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            ("operator new", vec!["throw(std::bad_alloc)".to_owned()]),
+            ("Free", vec!["throw()".to_owned()]),
+            ("Next", vec![]),
+            ("Allocator", vec!["throw()".to_owned()]),
+            (
+                "allocate",
+                vec!["throw(std::bad_alloc, std::length_error)".to_owned()]
+            ),
+            ("max_size", vec!["const".to_owned(), "throw()".to_owned()]),
+            ("Ready", vec![]),
+        ]
+    );
+    assert_eq!(details[4].parameters.len(), 2);
+    assert!(details[1].is_noexcept() && !details[0].is_noexcept() && !details[2].is_noexcept());
+}
+
+#[test]
+fn test_deprecation_messages() {
+    let source = r#"
+namespace absl {
+ABSL_DEPRECATED("Use absl::NewThing instead")
+void OldThing(int n);
+void OtherThing(int n) ABSL_DEPRECATED("Use \"absl::Other\" (with quotes)");
+[[deprecated("split " "message\n")]] int Split();
+[[deprecated]] int Bare();
+ABSL_DEPRECATED(kMessage) int FromMacro();
+int Current();
+}
+"#;
+    let messages: Vec<_> = extract_function_details(source)
+        .into_iter()
+        .map(|detail| (detail.name, detail.deprecated))
+        .collect();
+    let message = |text: &str| Some(text.to_owned());
+    assert_eq!(
+        messages,
+        [
+            ("OldThing".to_owned(), message("Use absl::NewThing instead")),
+            (
+                "OtherThing".to_owned(),
+                message(r#"Use "absl::Other" (with quotes)"#)
+            ),
+            ("Split".to_owned(), message("split message\n")),
+            ("Bare".to_owned(), message("")),
+            ("FromMacro".to_owned(), message("")),
+            ("Current".to_owned(), None),
+        ]
+    );
+}
+
+#[test]
+fn test_normalize_type() {
+    let cases = [
+        ("const\n    std::vector<int>&", "const std::vector<int>&"),
+        (
+            "  const   std::vector< int >  & ",
+            "const std::vector<int>&",
+        ),
+        ("char  *  const", "char* const"),
+        ("int * *", "int**"),
+        ("T &&", "T&&"),
+        (
+            "std::map< std::string , std::vector< int > >",
+            "std::map<std::string, std::vector<int>>",
+        ),
+        (
+            "std::array<int, (N > 2 ? N : 2)>",
+            "std::array<int, (N > 2 ? N : 2)>",
+        ),
+        ("unsigned\tlong\r\nlong", "unsigned long long"),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(normalize_type(input), expected, "normalizing {:?}", input);
+    }
+    assert_eq!(
+        normalize_type("const std::vector<int> &"),
+        normalize_type("const std::vector<int>&")
+    );
+}
+
+#[test]
+fn test_types_are_normalized_when_parsed() {
+    let source =
+        "const\n    std::vector<int> & Get(const char * s,\n    std::map< int , int > &m, T && x);";
+    let detail = extract_function_details(source).remove(0);
+    assert_eq!(detail.return_type, "const std::vector<int>&");
+    assert_eq!(
+        detail.parameter_types(),
+        ["const char*", "std::map<int, int>&", "T&&"]
+    );
 }
@@ -0,0 +1,82 @@
+use astd::{TypeAlias, extract_function_details, extract_type_aliases};
+
+fn alias(name: &str, aliased_type: &str) -> TypeAlias {
+    TypeAlias {
+        template_prefix: String::new(),
+        name: name.to_owned(),
+        aliased_type: aliased_type.to_owned(),
+        namespace: Some("absl".to_owned()),
+        enclosing_type: None,
+        line: 0,
+    }
+}
+
+#[test]
+fn test_typedef_and_using_forms() {
+    let source = r#"
+namespace absl {
+using std::swap;
+using namespace std::chrono;
+using string_view = std::string_view;
+typedef int64_t Time;
+typedef unsigned long long
+    uint128_storage;
+typedef char Buffer[16];
+typedef std::map<std::string, int> Counts;
+template <class T>
+using Span = absl::Span<const T>;
+}  // namespace absl
+"#;
+    let aliases: Vec<_> = extract_type_aliases(source)
+        .into_iter()
+        .map(|a| TypeAlias { line: 0, ..a })
+        .collect();
+    assert_eq!(
+        aliases,
+        [
+            alias("string_view", "std::string_view"),
+            alias("Time", "int64_t"),
+            alias("uint128_storage", "unsigned long long"),
+            alias("Buffer", "char[16]"),
+            alias("Counts", "std::map<std::string, int>"),
+            TypeAlias {
+                template_prefix: "template <class T>".to_owned(),
+                ..alias("Span", "absl::Span<const T>")
+            },
+        ]
+    );
+    assert!(aliases[5].is_templated());
+    assert!(!aliases[0].is_templated());
+}
+
+#[test]
+fn test_function_pointer_typedef() {
+    let source = r#"
+namespace absl {
+typedef void (*FlagCallback)(const char* name, int value);
+typedef int (absl::Duration::*Getter)() const;
+typedef bool Predicate(int);
+class Mutex {
+ public:
+  typedef void (*Hook)(const void* lock);
+  using Ptr = Mutex*;
+};
+}  // namespace absl
+"#;
+    let aliases = extract_type_aliases(source);
+    assert_eq!(aliases.len(), 5);
+    assert_eq!(aliases[0].name, "FlagCallback");
+    assert_eq!(
+        aliases[0].aliased_type,
+        "void (*)(const char* name, int value)"
+    );
+    assert_eq!(aliases[0].line, 3);
+    assert_eq!(aliases[1].name, "Getter");
+    assert_eq!(aliases[1].aliased_type, "int (absl::Duration::*)() const");
+    assert_eq!(aliases[2].name, "Predicate");
+    assert_eq!(aliases[2].aliased_type, "bool(int)");
+    assert_eq!(aliases[3].name, "Hook");
+    assert_eq!(aliases[3].enclosing_type.as_deref(), Some("Mutex"));
+    assert_eq!(aliases[4].aliased_type, "Mutex*");
+    assert!(extract_function_details(source).is_empty());
+}
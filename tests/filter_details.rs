@@ -0,0 +1,135 @@
+use astd::{
+    FunctionDetail, FunctionFilter, emit_wrappers, extract_function_details, filter_details,
+};
+use regex::Regex;
+use std::path::PathBuf;
+
+const STR_CAT_H: &str = r#"
+namespace absl {
+std::string StrCat(const AlphaNum& a);
+void StrAppend(std::string* dest, const AlphaNum& a);
+namespace strings_internal {
+std::string CatPieces(std::initializer_list<absl::string_view> pieces);
+}  // namespace strings_internal
+}  // namespace absl
+"#;
+
+const CORD_H: &str = r#"
+namespace absl {
+class Cord {
+ public:
+  void Append(const Cord& src);
+};
+namespace cord_internal {
+void LogFatalNodeType(CordRep* rep);
+}  // namespace cord_internal
+}  // namespace absl
+void GlobalHelper();
+"#;
+
+// The functions of both headers, each with its path set.
+fn details() -> Vec<FunctionDetail> {
+    let mut details = Vec::new();
+    for (path, src) in [
+        ("absl/strings/str_cat.h", STR_CAT_H),
+        ("absl/strings/cord.h", CORD_H),
+    ] {
+        details.extend(extract_function_details(src).into_iter().map(|mut detail| {
+            detail.path = Some(PathBuf::from(path));
+            detail
+        }));
+    }
+    details
+}
+
+fn names(details: Vec<FunctionDetail>) -> Vec<String> {
+    details.into_iter().map(|detail| detail.name).collect()
+}
+
+fn regex(pattern: &str) -> Regex {
+    Regex::new(pattern).unwrap()
+}
+
+#[test]
+fn test_empty_filter_keeps_everything() {
+    assert_eq!(filter_details(details(), &FunctionFilter::new()), details());
+}
+
+#[test]
+fn test_excluding_internal_namespaces() {
+    let filter = FunctionFilter::new().exclude_namespace(regex(r"_internal\b"));
+    assert_eq!(
+        names(filter_details(details(), &filter)),
+        ["StrCat", "StrAppend", "Append", "GlobalHelper"]
+    );
+}
+
+#[test]
+fn test_exclusions_take_precedence_over_inclusions() {
+    let filter = FunctionFilter::new()
+        .include_path(regex(r"absl/strings/str_cat\.h$"))
+        .include_path(regex(r"cord\.h$"))
+        .include_name(regex(r"^absl::"))
+        .exclude_name(regex(r"Append$"))
+        .exclude_namespace(regex(r"_internal\b"));
+    // GlobalHelper is in an included header but has no `absl::` prefix; StrAppend and
+    // Cord::Append match every include rule but also an exclude rule.
+    assert_eq!(names(filter_details(details(), &filter)), ["StrCat"]);
+}
+
+#[test]
+fn test_namespace_inclusion_requires_a_namespace() {
+    let filter = FunctionFilter::new().include_namespace(regex("^absl$"));
+    assert_eq!(
+        names(filter_details(details(), &filter)),
+        ["StrCat", "StrAppend", "Append"]
+    );
+    let detail = extract_function_details("void Free();").remove(0);
+    assert!(FunctionFilter::new().matches(&detail));
+    assert!(!filter.matches(&detail));
+    // Without a path, path inclusions match nothing and path exclusions nothing either.
+    assert!(
+        !FunctionFilter::new()
+            .include_path(regex(""))
+            .matches(&detail)
+    );
+    assert!(
+        FunctionFilter::new()
+            .exclude_path(regex(""))
+            .matches(&detail)
+    );
+}
+
+#[test]
+fn test_skip_templated() {
+    let details =
+        extract_function_details("template <typename T> T Identity(T v);\nint Abs(int v);\n");
+    assert_eq!(
+        names(filter_details(details.clone(), &FunctionFilter::new())).len(),
+        2
+    );
+    let filter = FunctionFilter::new().skip_templated(true);
+    assert_eq!(names(filter_details(details.clone(), &filter)), ["Abs"]);
+    let filter = FunctionFilter::new().skip_templated(false);
+    assert_eq!(filter_details(details.clone(), &filter), details);
+}
+
+#[test]
+fn test_excluded_functions_get_no_wrapper() {
+    let source = "namespace absl {\nint Abs(int v);\nint AbsInternal(int v);\n}\n";
+    let wrappers = |filter: &FunctionFilter| {
+        let mut out = Vec::new();
+        let details = filter_details(extract_function_details(source), filter);
+        emit_wrappers(&details, false, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    };
+    assert_eq!(
+        wrappers(&FunctionFilter::new()),
+        "int absl_Abs_wrapper(int v) { return absl::Abs(v); }\n\
+         int absl_AbsInternal_wrapper(int v) { return absl::AbsInternal(v); }\n"
+    );
+    assert_eq!(
+        wrappers(&FunctionFilter::new().exclude_name(regex("Internal$"))),
+        "int absl_Abs_wrapper(int v) { return absl::Abs(v); }\n"
+    );
+}
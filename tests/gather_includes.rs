@@ -0,0 +1,108 @@
+use astd::{bind_includes, prune_stale_headers, visit_dirs};
+use std::fs;
+
+mod scratch;
+use scratch::scratch_dir;
+
+#[test]
+fn test_copies_headers_only() {
+    let root = scratch_dir("copy");
+    let src = root.join("absl");
+    fs::create_dir_all(src.join("strings")).unwrap();
+    fs::write(src.join("strings/str_cat.h"), "int f();").unwrap();
+    fs::write(src.join("strings/str_cat.cc"), "int f() { return 0; }").unwrap();
+    let dest = root.join("include");
+    visit_dirs(&src, &dest, &src).unwrap();
+    assert!(dest.join("strings/str_cat.h").is_file());
+    assert!(!dest.join("strings/str_cat.cc").exists());
+}
+
+#[test]
+fn test_prunes_headers_removed_upstream() {
+    let root = scratch_dir("prune");
+    let src = root.join("absl");
+    fs::create_dir_all(src.join("strings")).unwrap();
+    fs::create_dir_all(src.join("base")).unwrap();
+    fs::write(src.join("strings/str_cat.h"), "int f();").unwrap();
+    fs::write(src.join("base/macros.h"), "int g();").unwrap();
+    let dest = root.join("include");
+    visit_dirs(&src, &dest, &src).unwrap();
+
+    // The next Abseil version drops base/ and renames str_cat.h.
+    fs::remove_dir_all(src.join("base")).unwrap();
+    fs::rename(
+        src.join("strings/str_cat.h"),
+        src.join("strings/str_join.h"),
+    )
+    .unwrap();
+    assert_eq!(prune_stale_headers(&src, &dest).unwrap(), 2);
+    visit_dirs(&src, &dest, &src).unwrap();
+    assert!(dest.join("strings/str_join.h").is_file());
+    assert!(!dest.join("strings/str_cat.h").exists());
+    assert!(!dest.join("base").exists());
+    assert_eq!(prune_stale_headers(&src, &dest).unwrap(), 0);
+    assert_eq!(prune_stale_headers(&src, &root.join("absent")).unwrap(), 0);
+}
+
+#[test]
+fn test_missing_source_is_an_error() {
+    let root = scratch_dir("missing");
+    let err = visit_dirs(&root.join("absent"), &root.join("include"), &root).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+    assert!(err.to_string().contains("absent"), "{}", err);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_permission_error_is_an_error() {
+    use std::os::unix::fs::PermissionsExt;
+    let root = scratch_dir("denied");
+    let src = root.join("absl");
+    let locked = src.join("locked");
+    fs::create_dir_all(&locked).unwrap();
+    fs::write(locked.join("hidden.h"), "int g();").unwrap();
+    fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+    let readable = fs::read_dir(&locked).is_ok();
+    let result = visit_dirs(&src, &root.join("include"), &src);
+    fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+    // Permission bits do not bind a privileged user, so there is nothing to observe.
+    if readable {
+        return;
+    }
+    let err = result.unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::PermissionDenied);
+    assert!(err.to_string().contains("locked"), "{}", err);
+}
+
+#[test]
+fn test_bind_includes_are_sorted() {
+    let root = scratch_dir("sorted");
+    for header in [
+        "strings/str_cat.h",
+        "base/macros.h",
+        "strings/match.h",
+        "base/config.inc",
+    ] {
+        let path = root.join(header);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "").unwrap();
+    }
+    let includes: Vec<String> = bind_includes(&root).unwrap().into_iter().collect();
+    assert_eq!(
+        includes,
+        ["base/macros.h", "strings/match.h", "strings/str_cat.h"]
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_symlinked_headers_are_included_once() {
+    use std::os::unix::fs::symlink;
+    let root = scratch_dir("symlink");
+    fs::create_dir_all(root.join("absl/strings")).unwrap();
+    fs::write(root.join("absl/strings/str_cat.h"), "int f();").unwrap();
+    symlink("str_cat.h", root.join("absl/strings/str_cat_alias.h")).unwrap();
+    symlink("absl", root.join("absl_alias")).unwrap();
+    let includes: Vec<String> = bind_includes(&root).unwrap().into_iter().collect();
+    assert_eq!(includes, ["absl/strings/str_cat.h"]);
+}
@@ -0,0 +1,394 @@
+use astd::gather_libs::{
+    BuildConfig, LibKind, LibLayout, copy_files_with_filter, gather_libs, link_directives,
+    target_lib_dir,
+};
+use std::{fs, path::Path};
+
+mod scratch;
+use scratch::scratch_dir;
+
+fn touch(root: &Path, files: &[&str]) {
+    for file in files {
+        let path = root.join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, b"").unwrap();
+    }
+}
+
+fn listing(root: &Path) -> Vec<String> {
+    let mut files = Vec::new();
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else {
+                let relative = path.strip_prefix(root).unwrap();
+                files.push(relative.to_string_lossy().replace('\\', "/"));
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+#[test]
+fn test_msvc_layout() {
+    let root = scratch_dir("msvc");
+    let build = root.join("build");
+    touch(
+        &build,
+        &[
+            "absl/base/Debug/absl_base.lib",
+            "absl/base/Debug/absl_base.pdb",
+            "absl/base/Release/absl_base.lib",
+            "absl/base/absl_base.vcxproj",
+        ],
+    );
+    let lib = root.join("lib");
+    assert_eq!(
+        gather_libs(&build, &lib, LibLayout::Msvc(BuildConfig::Debug), false).unwrap(),
+        2
+    );
+    assert_eq!(
+        listing(&lib),
+        ["absl/base/absl_base.lib", "absl/base/absl_base.pdb"]
+    );
+}
+
+#[test]
+fn test_msvc_release_layout() {
+    let root = scratch_dir("msvc-release");
+    let build = root.join("build");
+    touch(
+        &build,
+        &[
+            "absl/base/Debug/absl_base.lib",
+            "absl/base/Release/absl_base.lib",
+            "absl/base/Release/absl_base.pdb",
+            "absl/strings/Release/absl_strings.lib",
+        ],
+    );
+    let lib = root.join("lib");
+    let layout = LibLayout::Msvc(BuildConfig::Release);
+    assert_eq!(gather_libs(&build, &lib, layout, false).unwrap(), 3);
+    assert_eq!(
+        listing(&lib),
+        [
+            "absl/base/absl_base.lib",
+            "absl/base/absl_base.pdb",
+            "absl/strings/absl_strings.lib"
+        ]
+    );
+}
+
+#[test]
+fn test_unix_layout() {
+    let root = scratch_dir("unix");
+    let build = root.join("build");
+    touch(
+        &build,
+        &[
+            "absl/strings/libabsl_strings.a",
+            "absl/base/libabsl_base.so",
+            "absl/base/CMakeFiles/absl_base.dir/raw_logging.cc.o",
+            "absl/base/Debug/libabsl_debug.a",
+        ],
+    );
+    let lib = root.join("lib");
+    assert_eq!(
+        gather_libs(&build, &lib, LibLayout::Unix, false).unwrap(),
+        3
+    );
+    assert_eq!(
+        listing(&lib),
+        [
+            "absl/base/Debug/libabsl_debug.a",
+            "absl/base/libabsl_base.so",
+            "absl/strings/libabsl_strings.a"
+        ]
+    );
+}
+
+#[test]
+fn test_apple_layout() {
+    let root = scratch_dir("apple");
+    let build = root.join("build");
+    touch(
+        &build,
+        &[
+            "absl/base/libabsl_base.a",
+            "absl/base/libabsl_base.dylib",
+            "absl/base/libabsl_base.so",
+        ],
+    );
+    let lib = root.join("lib");
+    assert_eq!(
+        gather_libs(&build, &lib, LibLayout::Apple, false).unwrap(),
+        2
+    );
+    assert_eq!(
+        listing(&lib),
+        ["absl/base/libabsl_base.a", "absl/base/libabsl_base.dylib"]
+    );
+}
+
+#[test]
+fn test_versioned_shared_libraries() {
+    let root = scratch_dir("versioned");
+    let build = root.join("build");
+    touch(
+        &build,
+        &[
+            "absl/base/libabsl_base.so",
+            "absl/base/libabsl_base.so.2501.0.0",
+            "absl/base/libabsl_base.2501.0.0.dylib",
+            "absl/base/libabsl_base.dylib",
+            "absl/base/libabsl_base.so.conf",
+        ],
+    );
+    let unix = root.join("unix");
+    gather_libs(&build, &unix, LibLayout::Unix, false).unwrap();
+    assert_eq!(
+        listing(&unix),
+        [
+            "absl/base/libabsl_base.so",
+            "absl/base/libabsl_base.so.2501.0.0"
+        ]
+    );
+    assert_eq!(
+        link_directives(&unix, LibLayout::Unix, LibKind::Shared).unwrap()[1..],
+        [
+            "cargo:rustc-link-lib=dylib=absl_base",
+            "cargo:rustc-link-lib=dylib=stdc++"
+        ]
+    );
+    let apple = root.join("apple");
+    gather_libs(&build, &apple, LibLayout::Apple, false).unwrap();
+    assert_eq!(
+        listing(&apple),
+        [
+            "absl/base/libabsl_base.2501.0.0.dylib",
+            "absl/base/libabsl_base.dylib"
+        ]
+    );
+    assert_eq!(
+        link_directives(&apple, LibLayout::Apple, LibKind::Shared).unwrap()[1..],
+        [
+            "cargo:rustc-link-lib=dylib=absl_base",
+            "cargo:rustc-link-lib=dylib=c++"
+        ]
+    );
+}
+
+#[test]
+fn test_copy_files_with_filter_custom_predicate() {
+    let root = scratch_dir("custom");
+    touch(
+        &root.join("src"),
+        &["a.txt", "nested/b.txt", "nested/c.bin"],
+    );
+    let copied = copy_files_with_filter(
+        &root.join("src"),
+        &root.join("dest"),
+        &root.join("src"),
+        &|path| path.extension().is_some_and(|ext| ext == "txt"),
+        &|path| path.to_path_buf(),
+        false,
+    )
+    .unwrap();
+    assert_eq!(copied, 2);
+    assert_eq!(listing(&root.join("dest")), ["a.txt", "nested/b.txt"]);
+}
+
+#[test]
+fn test_dry_run_copies_nothing() {
+    let root = scratch_dir("dry-run");
+    let build = root.join("build");
+    touch(
+        &build,
+        &["absl/base/libabsl_base.a", "absl/strings/libabsl_strings.a"],
+    );
+    let lib = root.join("lib");
+    assert_eq!(gather_libs(&build, &lib, LibLayout::Unix, true).unwrap(), 2);
+    assert!(!lib.exists());
+}
+
+#[test]
+fn test_link_args() {
+    let msvc = LibLayout::Msvc(BuildConfig::Release);
+    let cases = [
+        (LibLayout::Unix, "libabsl_base.a", Some("static=absl_base")),
+        (LibLayout::Unix, "libabsl_base.so", Some("dylib=absl_base")),
+        (
+            LibLayout::Apple,
+            "libabsl_strings.dylib",
+            Some("dylib=absl_strings"),
+        ),
+        (LibLayout::Apple, "libabsl_strings.so", None),
+        (msvc, "absl_base.lib", Some("static=absl_base")),
+        (msvc, "absl_base.pdb", None),
+        (msvc, "absl_base.dll", None),
+        (msvc, "libabsl_base.a", None),
+    ];
+    for (layout, file, expected) in cases {
+        assert_eq!(
+            layout.link_arg(Path::new(file), LibKind::Static).as_deref(),
+            expected,
+            "{:?} {}",
+            layout,
+            file
+        );
+    }
+}
+
+#[test]
+fn test_link_directives() {
+    let root = scratch_dir("link");
+    touch(
+        &root,
+        &[
+            "absl/base/libabsl_base.a",
+            "absl/base/libabsl_base.so",
+            "absl/strings/libabsl_strings.so",
+            "absl/strings/README",
+        ],
+    );
+    let directives = link_directives(&root, LibLayout::Unix, LibKind::Static).unwrap();
+    assert_eq!(
+        directives,
+        [
+            format!(
+                "cargo:rustc-link-search=native={}",
+                root.join("absl/base").display()
+            ),
+            format!(
+                "cargo:rustc-link-search=native={}",
+                root.join("absl/strings").display()
+            ),
+            "cargo:rustc-link-lib=static=absl_base".to_owned(),
+            "cargo:rustc-link-lib=dylib=absl_strings".to_owned(),
+            "cargo:rustc-link-lib=dylib=stdc++".to_owned(),
+        ]
+    );
+    assert!(
+        link_directives(&root.join("missing"), LibLayout::Apple, LibKind::Static)
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_static_archives_are_grouped() {
+    let root = scratch_dir("link-group");
+    touch(
+        &root,
+        &["absl/base/libabsl_base.a", "absl/strings/libabsl_strings.a"],
+    );
+    let directives = link_directives(&root, LibLayout::Unix, LibKind::Static).unwrap();
+    assert_eq!(
+        directives[2..],
+        [
+            "cargo:rustc-link-lib=static=absl_base".to_owned(),
+            "cargo:rustc-link-lib=static=absl_strings".to_owned(),
+            "cargo:rustc-link-arg=-Wl,--start-group".to_owned(),
+            format!(
+                "cargo:rustc-link-arg={}",
+                root.join("absl/base/libabsl_base.a").display()
+            ),
+            format!(
+                "cargo:rustc-link-arg={}",
+                root.join("absl/strings/libabsl_strings.a").display()
+            ),
+            "cargo:rustc-link-arg=-Wl,--end-group".to_owned(),
+            "cargo:rustc-link-lib=dylib=stdc++".to_owned(),
+        ]
+    );
+    // Apple's linker resolves across archives already.
+    let directives = link_directives(&root, LibLayout::Apple, LibKind::Static).unwrap();
+    assert!(!directives.iter().any(|d| d.contains("--start-group")));
+}
+
+#[test]
+fn test_shared_link_directives() {
+    let msvc = LibLayout::Msvc(BuildConfig::Release);
+    assert_eq!(
+        msvc.link_arg(Path::new("absl_base.lib"), LibKind::Shared)
+            .as_deref(),
+        Some("dylib=absl_base")
+    );
+    let root = scratch_dir("link-shared");
+    touch(
+        &root,
+        &["absl/base/libabsl_base.a", "absl/base/libabsl_base.so"],
+    );
+    assert_eq!(
+        link_directives(&root, LibLayout::Unix, LibKind::Shared).unwrap()[1..],
+        [
+            "cargo:rustc-link-lib=dylib=absl_base",
+            "cargo:rustc-link-lib=dylib=stdc++"
+        ]
+    );
+}
+
+#[test]
+fn test_msvc_shared_layout_gathers_dlls() {
+    let root = scratch_dir("msvc-shared");
+    let build = root.join("build");
+    touch(
+        &build,
+        &[
+            "absl/base/Release/absl_base.dll",
+            "absl/base/Release/absl_base.lib",
+        ],
+    );
+    let lib = root.join("lib");
+    let layout = LibLayout::Msvc(BuildConfig::Release);
+    assert_eq!(gather_libs(&build, &lib, layout, false).unwrap(), 2);
+    assert_eq!(
+        listing(&lib),
+        ["absl/base/absl_base.dll", "absl/base/absl_base.lib"]
+    );
+}
+
+#[test]
+fn test_target_lib_dir() {
+    let lib = Path::new("external/lib");
+    assert_eq!(
+        target_lib_dir(lib, "aarch64-unknown-linux-gnu"),
+        Path::new("external/lib/aarch64-unknown-linux-gnu")
+    );
+    assert_eq!(
+        target_lib_dir(lib, "x86_64-pc-windows-msvc"),
+        Path::new("external/lib/x86_64-pc-windows-msvc")
+    );
+    // A custom target spec is named by its file.
+    assert_eq!(
+        target_lib_dir(lib, "/targets/my-board.json"),
+        Path::new("external/lib/my-board")
+    );
+    assert_ne!(
+        target_lib_dir(lib, "x86_64-unknown-linux-gnu"),
+        target_lib_dir(lib, "aarch64-unknown-linux-gnu")
+    );
+}
+
+#[test]
+fn test_layout_for_target() {
+    let debug = BuildConfig::Debug;
+    assert_eq!(
+        LibLayout::for_target(debug, "windows", "msvc"),
+        LibLayout::Msvc(debug)
+    );
+    assert_eq!(
+        LibLayout::for_target(debug, "windows", "gnu"),
+        LibLayout::Unix
+    );
+    assert_eq!(LibLayout::for_target(debug, "macos", ""), LibLayout::Apple);
+    assert_eq!(LibLayout::for_target(debug, "ios", ""), LibLayout::Apple);
+    assert_eq!(
+        LibLayout::for_target(debug, "linux", "gnu"),
+        LibLayout::Unix
+    );
+}
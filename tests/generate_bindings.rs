@@ -0,0 +1,21 @@
+use astd::{external_dir, generate_bindings};
+use std::{env, fs};
+
+#[test]
+fn test_external_dir_redirects_bindings() {
+    let root = env::temp_dir().join(format!("astd-external-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(root.join("include/absl/base")).unwrap();
+    fs::write(root.join("include/absl/base/macros.h"), "int f(int x);").unwrap();
+    // SAFETY: this is the only test in this binary, so no other thread reads the env.
+    unsafe { env::set_var("ASTD_EXTERNAL_DIR", &root) };
+    assert_eq!(external_dir(), root);
+    let written = generate_bindings(&external_dir()).unwrap();
+    assert_eq!(written, root.join("bindings.cpp"));
+    let bindings = fs::read_to_string(&written).unwrap();
+    let include = bindings.find("#include \"absl/base/macros.h\"");
+    let linkage = bindings.find("extern \"C\" {");
+    assert!(include.is_some() && linkage.is_some(), "{}", bindings);
+    // The headers must not get C linkage.
+    assert!(include < linkage, "{}", bindings);
+}
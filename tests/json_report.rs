@@ -0,0 +1,149 @@
+use astd::{extract_function_details, to_json, to_json_report};
+use std::path::PathBuf;
+
+const SNAPSHOT: &str = r#"{
+  "files": [
+    {
+      "path": null,
+      "functions": [
+        {
+          "template_prefix": "",
+          "specifiers": [
+            "inline"
+          ],
+          "return_type": "bool",
+          "has_trailing_return": false,
+          "is_deduced": false,
+          "name": "StartsWith",
+          "kind": "Regular",
+          "parameters": [
+            {
+              "type_name": "absl::string_view",
+              "name": "text",
+              "default": null,
+              "is_pack": false
+            },
+            {
+              "type_name": "absl::string_view",
+              "name": "prefix",
+              "default": "\"\"",
+              "is_pack": false
+            }
+          ],
+          "is_variadic": false,
+          "trailing_qualifiers": [
+            "noexcept"
+          ],
+          "special_definition": null,
+          "namespace": "absl",
+          "enclosing_type": null,
+          "linkage": "Cpp",
+          "calling_convention": null,
+          "line": 3,
+          "column": 1,
+          "path": null,
+          "access": null,
+          "byte_span": {
+            "start": 65,
+            "end": 151
+          },
+          "attributes": [],
+          "deprecated": null,
+          "is_definition": false,
+          "doc": "Returns whether `text` starts with `prefix`."
+        }
+      ]
+    }
+  ]
+}
+"#;
+
+#[test]
+fn test_report_snapshot() {
+    let src = r#"namespace absl {
+// Returns whether `text` starts with `prefix`.
+inline bool StartsWith(absl::string_view text, absl::string_view prefix = "") noexcept;
+}
+"#;
+    let details = extract_function_details(src);
+    assert_eq!(to_json_report(&details), SNAPSHOT);
+}
+
+#[test]
+fn test_report_groups_functions_by_sorted_path() {
+    let mut details = extract_function_details("void A();\nvoid B();\nvoid C();\n");
+    details[0].path = Some(PathBuf::from("absl/strings/str_cat.h"));
+    details[1].path = Some(PathBuf::from("absl/base/log_severity.h"));
+    details[2].path = Some(PathBuf::from("absl/strings/str_cat.h"));
+    let report = to_json_report(&details);
+    let severity = report
+        .find(r#""path": "absl/base/log_severity.h""#)
+        .unwrap();
+    let str_cat = report.find(r#""path": "absl/strings/str_cat.h""#).unwrap();
+    assert!(severity < str_cat);
+    assert_eq!(report.matches(r#""functions": ["#).count(), 2);
+    let (a, c) = (
+        report.find(r#""name": "A""#).unwrap(),
+        report.find(r#""name": "C""#).unwrap(),
+    );
+    assert!(str_cat < a && a < c);
+}
+
+#[test]
+fn test_report_escapes_strings() {
+    let mut details = extract_function_details("void F();\n");
+    details[0].doc = Some("Quotes \"x\",\ttabs and \\ on\ntwo lines\u{1}.".to_owned());
+    let report = to_json_report(&details);
+    assert!(report.contains(r#""doc": "Quotes \"x\",\ttabs and \\ on\ntwo lines\u0001.""#));
+}
+
+#[test]
+fn test_empty_report() {
+    assert_eq!(to_json_report(&[]), "{\n  \"files\": []\n}\n");
+}
+
+#[test]
+fn test_to_json_lists_functions_in_order() {
+    let details = extract_function_details("namespace absl {\nint A();\nvoid B(int x = 1);\n}\n");
+    let json = to_json(&details);
+    assert!(json.starts_with("[\n  {\n    \"template_prefix\": \"\","));
+    assert!(json.ends_with("  }\n]\n"));
+    let (a, b) = (
+        json.find(r#""name": "A""#).unwrap(),
+        json.find(r#""name": "B""#).unwrap(),
+    );
+    assert!(a < b);
+    assert!(json.contains(r#""default": "1""#));
+    assert_eq!(json.matches(r#""namespace": "absl""#).count(), 2);
+    assert_eq!(to_json(&[]), "[]\n");
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_round_trip_preserves_every_field() {
+    use astd::FunctionDetail;
+
+    let src = r#"namespace absl {
+class Cord {
+ public:
+  template <typename... Args>
+  [[nodiscard]] static Cord Make(const Args&... args) = delete;
+  virtual ~Cord() noexcept override;
+};
+extern "C" int __cdecl CAbs(int v, ...);
+}
+"#;
+    let details = extract_function_details(src);
+    let json = serde_json::to_string(&details).unwrap();
+    let back: Vec<FunctionDetail> = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, details);
+
+    // The report lays out each function the way the derive does.
+    let report: serde_json::Value = serde_json::from_str(&to_json_report(&details)).unwrap();
+    let functions: Vec<FunctionDetail> =
+        serde_json::from_value(report["files"][0]["functions"].clone()).unwrap();
+    assert_eq!(functions, details);
+
+    let signatures: Vec<FunctionDetail> = serde_json::from_str(&to_json(&details)).unwrap();
+    assert_eq!(signatures, details);
+}
@@ -0,0 +1,18 @@
+use astd::LogLevel;
+
+#[test]
+fn test_parse_levels() {
+    assert_eq!(LogLevel::parse("error"), Some(LogLevel::Error));
+    assert_eq!(LogLevel::parse("Warn"), Some(LogLevel::Warn));
+    assert_eq!(LogLevel::parse(" info "), Some(LogLevel::Info));
+    assert_eq!(LogLevel::parse("DEBUG"), Some(LogLevel::Debug));
+    assert_eq!(LogLevel::parse("trace"), None);
+    assert_eq!(LogLevel::parse(""), None);
+}
+
+#[test]
+fn test_levels_order_by_verbosity() {
+    assert!(LogLevel::Error < LogLevel::Warn);
+    assert!(LogLevel::Warn < LogLevel::Info);
+    assert!(LogLevel::Info < LogLevel::Debug);
+}
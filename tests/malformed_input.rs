@@ -0,0 +1,294 @@
+use std::collections::HashMap;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use astd::{
+    ExtractOptions, extract_class_details, extract_constants, extract_enum_details_checked,
+    extract_function_details, extract_function_details_checked,
+    extract_function_details_with_options, extract_includes, extract_type_aliases, group_overloads,
+    normalize_type, parse_template_parameters, parse_type, split_parameters, strip_preprocessor,
+    tokenize,
+};
+
+// Pieces of C++ and of malformed C++ that the generator strings together.
+const FRAGMENTS: &[&str] = &[
+    "template",
+    "<",
+    ">",
+    "(",
+    ")",
+    "{",
+    "}",
+    "[",
+    "]",
+    ";",
+    ",",
+    "::",
+    ":",
+    "*",
+    "&",
+    "&&",
+    "=",
+    "\"",
+    "'",
+    "/*",
+    "*/",
+    "//",
+    "\n",
+    "\r\n",
+    "\\",
+    "#",
+    "#if 0",
+    "#if X",
+    "#elif",
+    "#else",
+    "#endif",
+    "#define X \\\n",
+    "#include <",
+    "R\"(",
+    ")\"",
+    "u8\"",
+    "namespace",
+    "class",
+    "struct",
+    "enum",
+    "union",
+    "typedef",
+    "using",
+    "extern \"C\"",
+    "decltype",
+    "auto",
+    "->",
+    "...",
+    "operator",
+    "operator\"\"",
+    "operator()",
+    "operator new[]",
+    "<=>",
+    "~",
+    "const",
+    "virtual",
+    "public:",
+    "noexcept",
+    "= default",
+    "= 0",
+    "static_assert",
+    "sizeof",
+    "[[",
+    "]]",
+    "ABSL_NAMESPACE_BEGIN",
+    "ABSL_NAMESPACE_END",
+    "ABSL_DEPRECATED(\"x\")",
+    "(*",
+    "int",
+    "f",
+    "Foo",
+    " ",
+    "\t",
+    "\0",
+    "é",
+    "日本",
+    "\u{feff}",
+    "\u{2028}",
+    "0x1'2",
+    "1'",
+];
+
+// Whole declarations, so the generator also produces mostly valid headers.
+const DECLARATIONS: &[&str] = &[
+    "int f(int a, char* b);",
+    "template <typename T> T g(T);",
+    "class C { public: C(); ~C(); int m() const; };",
+    "namespace n { void h(); }",
+    "enum class E : int { A = 1, B };",
+    "constexpr int k = 3;",
+    "using A = int;",
+    "typedef void (*cb)(int);",
+    "void (*signal(int))(int);",
+    "auto t() -> decltype(x);",
+    "auto s() -> Größe;",
+    "enum E { A, B };",
+    "operator int() const;",
+];
+
+// Openers repeated to build deeply nested input.
+const OPENERS: &[&str] = &[
+    "(",
+    "<",
+    "{",
+    "[",
+    "[[",
+    "template <",
+    "template <template <",
+    "namespace a {",
+    "struct S {",
+    "extern \"C\" {",
+    "ABSL_NAMESPACE_BEGIN\n",
+    "decltype(",
+    "f(",
+    "/*",
+    "#if 1\n",
+];
+
+// A xorshift generator, so every run tries the same inputs.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.below(items.len())]
+    }
+}
+
+// Builds one input: random fragments, random bytes, deep nesting, or declarations with
+// fragments spliced between them.
+fn generate(rng: &mut Rng) -> String {
+    match rng.below(4) {
+        0 => (0..rng.below(200)).map(|_| rng.pick(FRAGMENTS)).collect(),
+        1 => {
+            let bytes: Vec<u8> = (0..rng.below(300)).map(|_| rng.next() as u8).collect();
+            String::from_utf8_lossy(&bytes).into_owned()
+        }
+        2 => {
+            let depth = rng.below(1000);
+            let mut src = rng.pick(OPENERS).repeat(depth);
+            src.push_str(rng.pick(DECLARATIONS));
+            if rng.below(2) == 0 {
+                src.push_str(&")>]}".repeat(rng.below(depth + 1)));
+            }
+            src
+        }
+        _ => (0..rng.below(20))
+            .flat_map(|_| [rng.pick(DECLARATIONS), rng.pick(FRAGMENTS)])
+            .collect(),
+    }
+}
+
+// Runs every parsing entry point on `src`.
+fn parse_everything(src: &str) {
+    let (details, _) = extract_function_details_checked(src);
+    let options = ExtractOptions {
+        defines: HashMap::from([("X".to_owned(), Some("2".to_owned()))]),
+        keep_inline_namespaces: true,
+        ..ExtractOptions::default()
+    };
+    extract_function_details_with_options(src, &options);
+    extract_class_details(src);
+    extract_enum_details_checked(src);
+    extract_constants(src);
+    extract_type_aliases(src);
+    extract_includes(src);
+    strip_preprocessor(src);
+    split_parameters(src);
+    tokenize(src);
+    parse_template_parameters(src);
+    normalize_type(src);
+    parse_type(src);
+    group_overloads(&details);
+    for detail in &details {
+        parse_type(&detail.return_type);
+        for param in &detail.parameters {
+            parse_type(&param.type_name);
+        }
+    }
+}
+
+// Parses `src` on its own thread, failing if that panics or has not returned within a
+// deadline that grows with the input, as a parser stuck in a loop never would.
+fn assert_returns(src: String) {
+    let deadline = Duration::from_secs(10) + Duration::from_micros(50 * src.len() as u64);
+    let (done, finished) = mpsc::channel();
+    let input = src.clone();
+    thread::spawn(move || {
+        parse_everything(&input);
+        let _ = done.send(());
+    });
+    match finished.recv_timeout(deadline) {
+        Ok(()) => {}
+        Err(mpsc::RecvTimeoutError::Disconnected) => panic!("parser panicked on {:?}", src),
+        Err(mpsc::RecvTimeoutError::Timeout) => panic!("parser did not return on {:?}", src),
+    }
+}
+
+#[test]
+fn test_generated_inputs() {
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+    for _ in 0..300 {
+        assert_returns(generate(&mut rng));
+    }
+}
+
+#[test]
+fn test_truncated_declarations() {
+    let source = "namespace absl {\n\
+                  template <typename T, typename = std::enable_if_t<(sizeof(T) > 1)>>\n\
+                  [[nodiscard]] ABSL_DEPRECATED(\"Use g\") std::vector<T>* f(\n\
+                  const T& t = T{1, 2}, void (*cb)(int) = nullptr, ...) const noexcept;\n\
+                  class C : public B { public: explicit operator bool() const; };\n\
+                  enum class E : int { kA = 1, kB = (2 > 1) };\n\
+                  auto Size() const -> Größe override;\n\
+                  }  // namespace absl\n";
+    for (end, _) in source.char_indices() {
+        assert_returns(source[..end].to_owned());
+    }
+}
+
+#[test]
+fn test_non_ascii_trailing_return_type() {
+    for source in [
+        "auto f() -> Größe;",
+        "int f() -> é;",
+        "auto f() -> 日本 final;",
+    ] {
+        assert_returns(source.to_owned());
+    }
+    let details = extract_function_details("auto f() -> Größe;");
+    assert_eq!(details[0].return_type, "Größe");
+}
+
+#[test]
+fn test_unclosed_enum() {
+    for source in ["enum E {", "namespace a { enum class E : int {"] {
+        assert_returns(source.to_owned());
+        let (enums, diagnostics) = extract_enum_details_checked(source);
+        assert!(enums.is_empty());
+        assert_eq!(diagnostics[0].reason, "unclosed enum body");
+    }
+}
+
+#[test]
+fn test_deep_nesting_is_skipped_with_a_diagnostic() {
+    let source = format!("{}void Deep();", "namespace a {".repeat(1000));
+    let (details, diagnostics) = extract_function_details_checked(&source);
+    assert!(details.is_empty());
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].reason, "scopes nested too deeply");
+
+    // Scopes closing again bring the parser back to the declarations after them.
+    let source = format!(
+        "{}void Deep();{} void Shallow();",
+        "namespace a {".repeat(1000),
+        "}".repeat(1000)
+    );
+    let (details, _) = extract_function_details_checked(&source);
+    let names: Vec<_> = details.iter().map(|d| d.name.as_str()).collect();
+    assert_eq!(names, ["Shallow"]);
+
+    let clause = format!(
+        "template <{}typename{} T>",
+        "template <".repeat(1000),
+        "> class".repeat(1000)
+    );
+    assert_eq!(parse_template_parameters(&clause).len(), 1);
+}
@@ -0,0 +1,71 @@
+use astd::{ParseCache, parse_header_file};
+use std::fs;
+
+mod scratch;
+use scratch::scratch_dir;
+
+const MATCH_H: &str = r#"
+namespace absl {
+// Returns whether `haystack` contains `needle`.
+//
+// Tabs	and \backslashes are kept.
+ABSL_MUST_USE_RESULT inline bool StrContains(absl::string_view haystack,
+                                             absl::string_view needle = "") noexcept;
+class Cord {
+ private:
+  template <typename... Args>
+  static Cord Make(const Args&... args) = delete;
+};
+extern "C" int CAbs(int v, ...);
+}  // namespace absl
+"#;
+
+#[test]
+fn test_cached_results_match_a_fresh_parse() {
+    let root = scratch_dir("roundtrip");
+    let header = root.join("match.h");
+    fs::write(&header, MATCH_H).unwrap();
+    let cache = ParseCache::new(root.join("cache"));
+    let parsed = cache.get_or_parse(&header).unwrap();
+    assert_eq!(parsed, parse_header_file(&header).unwrap());
+    assert!(cache.entry_path(&header).is_file());
+    assert_eq!(cache.get_or_parse(&header).unwrap(), parsed);
+}
+
+#[test]
+fn test_mismatched_entries_are_reparsed() {
+    let root = scratch_dir("mismatch");
+    let header = root.join("match.h");
+    fs::write(&header, MATCH_H).unwrap();
+    let cache = ParseCache::new(root.join("cache"));
+    let names = |cache: &ParseCache| -> Vec<String> {
+        cache
+            .get_or_parse(&header)
+            .unwrap()
+            .into_iter()
+            .map(|d| d.name)
+            .collect()
+    };
+    assert_eq!(names(&cache), ["StrContains", "Make", "CAbs"]);
+
+    // An entry whose header still matches is trusted as is.
+    let entry = cache.entry_path(&header);
+    let original = fs::read_to_string(&entry).unwrap();
+    fs::write(&entry, original.replace("StrContains", "Cached")).unwrap();
+    assert_eq!(names(&cache), ["Cached", "Make", "CAbs"]);
+
+    // A different content hash or version means the header is parsed again.
+    let (first, rest) = original.split_once('\n').unwrap();
+    let stale = format!("{}0\n{}", first, rest.replace("StrContains", "Cached"));
+    fs::write(&entry, stale).unwrap();
+    assert_eq!(names(&cache), ["StrContains", "Make", "CAbs"]);
+    assert_eq!(fs::read_to_string(&entry).unwrap(), original);
+
+    // So is a corrupt entry.
+    fs::write(&entry, format!("{}\nnot\tan\tentry", first)).unwrap();
+    assert_eq!(names(&cache), ["StrContains", "Make", "CAbs"]);
+
+    // And the entry follows the header when it changes.
+    fs::write(&header, "int Changed();").unwrap();
+    assert_eq!(names(&cache), ["Changed"]);
+}
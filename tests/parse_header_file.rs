@@ -0,0 +1,164 @@
+use astd::{
+    FunctionDetail, extract_class_details, extract_constants, extract_function_details,
+    extract_type_aliases, normalize_source, parse_directory, parse_header_file,
+};
+use std::fs;
+
+mod scratch;
+use scratch::scratch_dir;
+
+#[test]
+fn test_latin1_comments_are_read_lossily() {
+    let root = scratch_dir("latin1");
+    let header = root.join("civil_time.h");
+    fs::write(
+        &header,
+        b"// Gr\xfc\xdfe aus M\xfcnchen\nnamespace absl {\nint GetWeekday(int day);\n}\n",
+    )
+    .unwrap();
+    let details = parse_header_file(&header).unwrap();
+    assert_eq!(details.len(), 1);
+    assert_eq!(details[0].name, "GetWeekday");
+    assert_eq!(details[0].namespace.as_deref(), Some("absl"));
+    assert_eq!(details[0].path.as_deref(), Some(header.as_path()));
+    assert!(parse_header_file(root.join("missing.h")).is_err());
+}
+
+// A header exercising line counting, comments, continuations and defaults.
+const FIXTURE: &str = "// Copyright\n\
+    #define ABSL_HELPER(x) \\\n  (x)\n\
+    namespace absl {\n\
+    /* StrCat joins\n   its arguments. */\n\
+    std::string StrCat(absl::string_view a,\n                   absl::string_view b = \"\");\n\
+    int Clamp(int v, int lo = 0, int hi = 10);\n\
+    }  // namespace absl\n";
+
+// Parses `contents` as a header file.
+fn parse_fixture(name: &str, contents: &[u8]) -> Vec<astd::FunctionDetail> {
+    let root = scratch_dir(name);
+    let header = root.join("str_cat.h");
+    fs::write(&header, contents).unwrap();
+    let details = parse_header_file(&header).unwrap();
+    details
+        .into_iter()
+        .map(|detail| FunctionDetail {
+            path: None,
+            ..detail
+        })
+        .collect()
+}
+
+// Checks that `details`, parsed from `src`, match `expected`, parsed from FIXTURE, but
+// with byte spans indexing `src` and so covering the same text.
+fn assert_parses_like_fixture(
+    src: &str,
+    details: Vec<FunctionDetail>,
+    expected: &[FunctionDetail],
+) {
+    assert_eq!(details.len(), expected.len());
+    for (detail, expected) in details.into_iter().zip(expected) {
+        assert_eq!(
+            src[detail.byte_span.clone()].replace("\r\n", "\n"),
+            FIXTURE[expected.byte_span.clone()]
+        );
+        let detail = FunctionDetail {
+            byte_span: expected.byte_span.clone(),
+            ..detail
+        };
+        assert_eq!(&detail, expected);
+    }
+}
+
+#[test]
+fn test_crlf_header_parses_like_lf() {
+    let expected = extract_function_details(FIXTURE);
+    assert_eq!(expected.len(), 2);
+    assert_eq!((expected[0].line, expected[1].line), (7, 9));
+    let crlf = FIXTURE.replace('\n', "\r\n");
+    assert_parses_like_fixture(&crlf, parse_fixture("crlf", crlf.as_bytes()), &expected);
+    assert_parses_like_fixture(&crlf, extract_function_details(&crlf), &expected);
+}
+
+#[test]
+fn test_bom_prefixed_header_parses_like_plain() {
+    let expected = extract_function_details(FIXTURE);
+    let bom = format!("\u{feff}{}", FIXTURE);
+    assert_parses_like_fixture(&bom, parse_fixture("bom", bom.as_bytes()), &expected);
+    let bom_crlf = format!("\u{feff}{}", FIXTURE.replace('\n', "\r\n"));
+    let details = parse_fixture("bom-crlf", bom_crlf.as_bytes());
+    assert_parses_like_fixture(&bom_crlf, details, &expected);
+    // The BOM must not hide a declaration on the very first line.
+    let first = extract_function_details("\u{feff}int First();\r\n");
+    assert_eq!(first[0].name, "First");
+    assert_eq!((first[0].line, first[0].byte_span.clone()), (1, 3..14));
+}
+
+#[test]
+fn test_crlf_byte_spans_index_the_source() {
+    let src = "\r\nint f();\r\nint g(int a,\r\n      int b);\rint h();";
+    let spans: Vec<_> = extract_function_details(src)
+        .into_iter()
+        .map(|detail| (detail.line, &src[detail.byte_span]))
+        .collect();
+    assert_eq!(
+        spans,
+        [
+            (2, "int f()"),
+            (3, "int g(int a,\r\n      int b)"),
+            (5, "int h()"),
+        ]
+    );
+}
+
+#[test]
+fn test_crlf_lines_of_other_declarations() {
+    let src = "\r\n\r\nconstexpr int kMax = 1;\r\nusing Id = int;\r\nclass C {};\r\n";
+    assert_eq!(extract_constants(src)[0].line, 3);
+    assert_eq!(extract_type_aliases(src)[0].line, 4);
+    assert_eq!(extract_class_details(src)[0].line, 5);
+}
+
+#[test]
+fn test_normalize_source() {
+    assert_eq!(normalize_source("\u{feff}a\r\nb\rc\n"), "a\nb\nc\n");
+    assert!(matches!(
+        normalize_source("int f();\n"),
+        std::borrow::Cow::Borrowed("int f();\n")
+    ));
+}
+
+#[test]
+fn test_parse_directory_recurses_in_path_order() {
+    let root = scratch_dir("recurse");
+    fs::create_dir_all(root.join("absl/strings")).unwrap();
+    fs::write(root.join("absl/strings/match.h"), "bool StrContains();").unwrap();
+    fs::write(
+        root.join("absl/strings/ascii.h"),
+        "char ascii_tolower(char c);",
+    )
+    .unwrap();
+    fs::write(root.join("absl/strings/ascii.cc"), "int Ignored();").unwrap();
+    fs::write(root.join("base.h"), "int f();\nint g();").unwrap();
+    let parsed = parse_directory(&root).unwrap();
+    let summary: Vec<_> = parsed
+        .iter()
+        .map(|(path, details)| {
+            (
+                path.strip_prefix(&root)
+                    .unwrap()
+                    .to_string_lossy()
+                    .replace('\\', "/"),
+                details.len(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            ("absl/strings/ascii.h".to_owned(), 1),
+            ("absl/strings/match.h".to_owned(), 1),
+            ("base.h".to_owned(), 2),
+        ]
+    );
+    assert!(parse_directory(&root.join("missing")).is_err());
+}
@@ -0,0 +1,132 @@
+use astd::{TypeInfo, extract_function_details, parse_type};
+
+// base, is_const, is_pointer, is_const_pointer, is_reference, is_rvalue_ref
+type Row = (&'static str, &'static str, bool, bool, bool, bool, bool);
+
+const TYPES: &[Row] = &[
+    ("int", "int", false, false, false, false, false),
+    ("const int", "int", true, false, false, false, false),
+    ("const T&", "T", true, false, false, true, false),
+    ("T const&", "T", true, false, false, true, false),
+    ("T const &", "T", true, false, false, true, false),
+    ("T&", "T", false, false, false, true, false),
+    ("T&&", "T", false, false, false, false, true),
+    ("const T&&", "T", true, false, false, false, true),
+    // Pointer to const.
+    ("const char*", "char", true, true, false, false, false),
+    ("char const*", "char", true, true, false, false, false),
+    // Const pointer.
+    ("char* const", "char", false, true, true, false, false),
+    ("const char* const", "char", true, true, true, false, false),
+    ("int*&", "int", false, true, false, true, false),
+    (
+        "const char**",
+        "const char*",
+        false,
+        true,
+        false,
+        false,
+        false,
+    ),
+    (
+        "const char* const*",
+        "const char*",
+        true,
+        true,
+        false,
+        false,
+        false,
+    ),
+    (
+        "const std::vector<int>&",
+        "std::vector<int>",
+        true,
+        false,
+        false,
+        true,
+        false,
+    ),
+    (
+        "std::map<int, const char*>",
+        "std::map<int, const char*>",
+        false,
+        false,
+        false,
+        false,
+        false,
+    ),
+    (
+        "const std::vector< std::string >*",
+        "std::vector<std::string>",
+        true,
+        true,
+        false,
+        false,
+        false,
+    ),
+    (
+        "volatile unsigned long",
+        "unsigned long",
+        false,
+        false,
+        false,
+        false,
+        false,
+    ),
+    (
+        "constexpr_t",
+        "constexpr_t",
+        false,
+        false,
+        false,
+        false,
+        false,
+    ),
+];
+
+#[test]
+fn test_parse_type_table() {
+    for &(input, base, is_const, is_pointer, is_const_pointer, is_reference, is_rvalue_ref) in TYPES
+    {
+        assert_eq!(
+            parse_type(input),
+            TypeInfo {
+                base: base.to_owned(),
+                is_const,
+                is_pointer,
+                is_const_pointer,
+                is_reference,
+                is_rvalue_ref,
+            },
+            "parsing {:?}",
+            input
+        );
+    }
+}
+
+#[test]
+fn test_west_and_east_const_agree() {
+    assert_eq!(parse_type("const T&"), parse_type("T const&"));
+    assert_eq!(
+        parse_type("const absl::Cord*"),
+        parse_type("absl::Cord const *")
+    );
+    assert_ne!(parse_type("const int*"), parse_type("int* const"));
+}
+
+#[test]
+fn test_type_info_of_signatures() {
+    let detail = extract_function_details(
+        "const std::vector<int>& get_vector(absl::string_view const& key, char* const out);",
+    )
+    .remove(0);
+    let ret = detail.return_type_info();
+    assert_eq!(ret.base, "std::vector<int>");
+    assert!(ret.is_const && ret.is_reference && !ret.is_pointer);
+    let key = detail.parameters[0].type_info();
+    assert_eq!(key.base, "absl::string_view");
+    assert!(key.is_const && key.is_reference);
+    let out = detail.parameters[1].type_info();
+    assert_eq!(out.base, "char");
+    assert!(out.is_pointer && out.is_const_pointer && !out.is_const);
+}
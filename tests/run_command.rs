@@ -0,0 +1,149 @@
+use astd::{is_network_command, run_command, run_command_with_retry};
+use std::{env, io::ErrorKind, time::Duration};
+
+#[test]
+fn test_successful_command_returns_stdout() {
+    let stdout = run_command(env!("CARGO"), &["--version"], &env::temp_dir()).unwrap();
+    assert!(stdout.starts_with("cargo "), "{}", stdout);
+}
+
+#[test]
+fn test_failing_command_is_an_error() {
+    let err = run_command(
+        env!("CARGO"),
+        &["astd-no-such-subcommand"],
+        &env::temp_dir(),
+    )
+    .unwrap_err();
+    let message = err.to_string();
+    assert!(
+        message.starts_with("`") && message.contains("astd-no-such-subcommand` failed with"),
+        "{}",
+        message
+    );
+    assert!(message.contains("no such command"), "{}", message);
+}
+
+#[test]
+fn test_missing_program_is_an_error() {
+    let err = run_command("astd-no-such-program", &[], &env::temp_dir()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+    assert!(err.to_string().starts_with("`astd-no-such-program`: "));
+}
+
+#[test]
+fn test_network_commands() {
+    assert!(is_network_command(
+        "git",
+        &["clone", "--no-checkout", "url", "dir"]
+    ));
+    assert!(is_network_command(
+        "git",
+        &["-c", "fetch.prune=true", "fetch", "origin"]
+    ));
+    assert!(is_network_command("/usr/bin/git", &["ls-remote", "origin"]));
+    assert!(!is_network_command("git", &["rev-parse", "HEAD"]));
+    assert!(!is_network_command(
+        "git",
+        &["-c", "advice.detachedHead=false", "checkout"]
+    ));
+    assert!(!is_network_command("cmake", &["fetch"]));
+    assert!(!is_network_command("git", &[]));
+}
+
+// Creates a scratch directory holding a `git` script that fails `failures` times, then
+// prints `ok`. Each run appends a line to `attempts` in the directory.
+#[cfg(unix)]
+fn flaky_git(name: &str, failures: usize) -> std::path::PathBuf {
+    use std::{fs, os::unix::fs::PermissionsExt};
+    let dir = env::temp_dir().join(format!("astd-run-command-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let script = dir.join("git");
+    fs::write(
+        &script,
+        format!(
+            "#!/bin/sh\necho x >> attempts\n\
+             if [ $(wc -l < attempts) -le {} ]; then echo 'unable to access' >&2; exit 128; fi\n\
+             echo ok\n",
+            failures
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+    dir
+}
+
+#[cfg(unix)]
+fn attempts(dir: &std::path::Path) -> usize {
+    std::fs::read_to_string(dir.join("attempts"))
+        .unwrap()
+        .lines()
+        .count()
+}
+
+#[cfg(unix)]
+#[test]
+fn test_network_command_is_retried_until_it_succeeds() {
+    let dir = flaky_git("retry", 2);
+    let git = dir.join("git");
+    let stdout = run_command_with_retry(
+        git.to_str().unwrap(),
+        &["fetch", "origin"],
+        &dir,
+        3,
+        Duration::from_millis(1),
+    )
+    .unwrap();
+    assert_eq!(stdout, "ok\n");
+    assert_eq!(attempts(&dir), 3);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_retries_run_out() {
+    let dir = flaky_git("exhausted", 5);
+    let git = dir.join("git");
+    let err = run_command_with_retry(
+        git.to_str().unwrap(),
+        &["clone", "url"],
+        &dir,
+        2,
+        Duration::from_millis(1),
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("unable to access"), "{}", err);
+    assert_eq!(attempts(&dir), 3);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_other_commands_are_not_retried() {
+    let dir = flaky_git("no-retry", 1);
+    let git = dir.join("git");
+    let result = run_command_with_retry(
+        git.to_str().unwrap(),
+        &["checkout", "main"],
+        &dir,
+        3,
+        Duration::from_millis(1),
+    );
+    assert!(result.is_err());
+    assert_eq!(attempts(&dir), 1);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_missing_program_is_not_retried() {
+    let err = run_command_with_retry(
+        "/astd-no-such-dir/git",
+        &["fetch"],
+        &env::temp_dir(),
+        3,
+        Duration::from_secs(60),
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+}
@@ -0,0 +1,46 @@
+#![allow(unused)]
+
+use std::path::{Path, PathBuf};
+use std::{env, fs, ops::Deref, process, thread};
+
+/// A scratch directory that is removed with everything in it when dropped, like
+/// `common::TempDir`.
+pub(crate) struct ScratchDir(PathBuf);
+
+impl Deref for ScratchDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for ScratchDir {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        let result = fs::remove_dir_all(&self.0);
+        // Avoid panicking while panicking, which aborts without the test results.
+        if !thread::panicking() {
+            result.unwrap();
+        }
+    }
+}
+
+/// Creates an empty scratch directory unique to the calling test, named after the test
+/// binary and `name`.
+pub(crate) fn scratch_dir(name: &str) -> ScratchDir {
+    let dir = env::temp_dir().join(format!(
+        "astd-{}-{}-{}",
+        env!("CARGO_CRATE_NAME").replace('_', "-"),
+        name,
+        process::id()
+    ));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    ScratchDir(dir)
+}
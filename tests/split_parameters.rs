@@ -0,0 +1,80 @@
+use astd::split_parameters;
+
+#[test]
+fn test_plain_parameters() {
+    assert_eq!(
+        split_parameters("int a,  double b ,char"),
+        ["int a", "double b", "char"]
+    );
+}
+
+#[test]
+fn test_empty_list() {
+    assert!(split_parameters("").is_empty());
+    assert!(split_parameters("  \n ").is_empty());
+    assert_eq!(split_parameters("void"), ["void"]);
+}
+
+#[test]
+fn test_nested_templates() {
+    assert_eq!(
+        split_parameters(
+            "std::map<std::string, std::vector<std::pair<int, int>>> opts, \
+             std::function<void(int, int)> cb, int n"
+        ),
+        [
+            "std::map<std::string, std::vector<std::pair<int, int>>> opts",
+            "std::function<void(int, int)> cb",
+            "int n",
+        ]
+    );
+}
+
+#[test]
+fn test_braced_and_literal_defaults() {
+    assert_eq!(
+        split_parameters(r#"std::vector<int> v = {1, 2, 3}, const char* sep = ", ", char c = ','"#),
+        [
+            "std::vector<int> v = {1, 2, 3}",
+            r#"const char* sep = ", ""#,
+            "char c = ','",
+        ]
+    );
+}
+
+#[test]
+fn test_lambda_default_argument() {
+    assert_eq!(
+        split_parameters(
+            "int x, std::function<bool(int, int)> less = [](int a, int b) { return a < b; }, \
+             bool stable = true"
+        ),
+        [
+            "int x",
+            "std::function<bool(int, int)> less = [](int a, int b) { return a < b; }",
+            "bool stable = true",
+        ]
+    );
+}
+
+#[test]
+fn test_array_and_function_pointer_parameters() {
+    assert_eq!(
+        split_parameters("int (&arr)[4], int (*cmp)(const void*, const void*), T, U x[2][3]"),
+        [
+            "int (&arr)[4]",
+            "int (*cmp)(const void*, const void*)",
+            "T",
+            "U x[2][3]",
+        ]
+    );
+}
+
+#[test]
+fn test_comments_do_not_split() {
+    // Comments at either end of a parameter are trimmed along with the whitespace.
+    assert_eq!(
+        split_parameters("int a /* , b */, int c"),
+        ["int a", "int c"]
+    );
+}
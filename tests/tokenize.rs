@@ -0,0 +1,81 @@
+use astd::{TokenKind, tokenize};
+
+// Pairs each token's kind with its text.
+fn tokens(src: &str) -> Vec<(TokenKind, &str)> {
+    tokenize(src)
+        .into_iter()
+        .map(|token| (token.kind, &src[token.span]))
+        .collect()
+}
+
+#[test]
+fn test_declaration() {
+    use TokenKind::*;
+    assert_eq!(
+        tokens("absl::string_view f(int n = 1'000);"),
+        [
+            (Identifier, "absl"),
+            (Punct, ":"),
+            (Punct, ":"),
+            (Identifier, "string_view"),
+            (Identifier, "f"),
+            (Punct, "("),
+            (Identifier, "int"),
+            (Identifier, "n"),
+            (Punct, "="),
+            (Number, "1'000"),
+            (Punct, ")"),
+            (Punct, ";"),
+        ]
+    );
+}
+
+#[test]
+fn test_literals_and_comments() {
+    use TokenKind::*;
+    assert_eq!(
+        tokens("x = u8\"a;b\" /* c */ 'd' R\"(e\")\" // f\n0x1p-3f"),
+        [
+            (Identifier, "x"),
+            (Punct, "="),
+            (Identifier, "u8"),
+            (String, "\"a;b\""),
+            (Comment, "/* c */"),
+            (Char, "'d'"),
+            (Identifier, "R"),
+            (String, "\"(e\")\""),
+            (Comment, "// f"),
+            (Number, "0x1p-3f"),
+        ]
+    );
+}
+
+#[test]
+fn test_directives() {
+    use TokenKind::*;
+    let src = "#define A(x) \\\n  \"x\" // y\n  int a;\n/* */ # include <b.h>\n";
+    assert_eq!(
+        tokens(src),
+        [
+            (Directive, "#define A(x) \\\n  \"x\" // y"),
+            (String, "\"x\""),
+            (Comment, "// y"),
+            (Identifier, "int"),
+            (Identifier, "a"),
+            (Punct, ";"),
+            (Comment, "/* */"),
+            (Directive, "# include <b.h>"),
+        ]
+    );
+    // A `#` that is not first on its line is punctuation.
+    assert_eq!(tokens("a # b")[1], (Punct, "#"));
+}
+
+#[test]
+fn test_unterminated_input() {
+    use TokenKind::*;
+    assert_eq!(tokens("\"abc\nd"), [(String, "\"abc"), (Identifier, "d")]);
+    assert_eq!(tokens("/* abc"), [(Comment, "/* abc")]);
+    assert_eq!(tokens("é"), [(Identifier, "é")]);
+    assert!(tokenize("").is_empty());
+}
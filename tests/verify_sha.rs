@@ -0,0 +1,35 @@
+use astd::{normalize_sha, sha_matches};
+
+const HEAD: &str = "4447c7562e3bc702ade25105912dce503f0c4010";
+
+#[test]
+fn test_normalize_sha() {
+    assert_eq!(normalize_sha(HEAD).as_deref(), Some(HEAD));
+    assert_eq!(
+        normalize_sha("  4447C7562E3B\n").as_deref(),
+        Some("4447c7562e3b")
+    );
+    assert_eq!(normalize_sha(&"a".repeat(64)), Some("a".repeat(64)));
+    // Too short to name a commit, too long, or not hex.
+    assert_eq!(normalize_sha("4447c7"), None);
+    assert_eq!(normalize_sha(&"a".repeat(65)), None);
+    assert_eq!(normalize_sha("20250127.0"), None);
+    assert_eq!(normalize_sha("4447c75g"), None);
+    assert_eq!(normalize_sha(""), None);
+}
+
+#[test]
+fn test_full_and_short_shas_match_their_commit() {
+    assert!(sha_matches(HEAD, HEAD));
+    assert!(sha_matches("4447c75", HEAD));
+    assert!(sha_matches("4447C7562E3BC702", &format!("{}\n", HEAD)));
+    assert!(!sha_matches("4447c76", HEAD));
+    assert!(!sha_matches(
+        "5447c7562e3bc702ade25105912dce503f0c4010",
+        HEAD
+    ));
+    // A short head never matches a longer expectation, and nothing invalid matches.
+    assert!(!sha_matches(HEAD, "4447c7562e3b"));
+    assert!(!sha_matches("4447", HEAD));
+    assert!(!sha_matches(HEAD, "not a sha"));
+}